@@ -0,0 +1,11 @@
+/// Generates the gRPC service code from `proto/taskprinter.proto` with the bundled
+/// protoc, so no system installation of the protobuf compiler is needed.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let protoc = protoc_bin_vendored::protoc_bin_path()?;
+    // SAFETY: build scripts are single-threaded.
+    unsafe { std::env::set_var("PROTOC", protoc) };
+    tonic_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/taskprinter.proto"], &["proto"])?;
+    Ok(())
+}
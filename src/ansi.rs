@@ -0,0 +1,142 @@
+/// Text attributes that can be reproduced by ESC/POS printers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Emphasized (bold) text.
+    pub bold: bool,
+    /// Underlined text.
+    pub underline: bool,
+    /// White on black text.
+    pub invert: bool,
+}
+
+/// SGR state while parsing. Colors have no equivalent on a thermal printer, so colored
+/// foreground text is emphasized and colored background text is inverted.
+#[derive(Default)]
+struct SgrState {
+    bold: bool,
+    color: bool,
+    underline: bool,
+    reverse: bool,
+    background: bool,
+}
+
+impl SgrState {
+    fn style(&self) -> Style {
+        Style {
+            bold: self.bold || self.color,
+            underline: self.underline,
+            invert: self.reverse || self.background,
+        }
+    }
+
+    /// Applies the parameters of a `CSI ... m` sequence.
+    fn apply(&mut self, params: &str) {
+        let codes: Vec<u16> = params
+            .split([';', ':'])
+            .map(|code| code.parse().unwrap_or(0))
+            .collect();
+        let mut codes = codes.iter().copied();
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = SgrState::default(),
+                1 => self.bold = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                21 => self.underline = true,
+                22 => self.bold = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                30..=37 | 90..=97 => self.color = true,
+                39 => self.color = false,
+                40..=47 | 100..=107 => self.background = true,
+                49 => self.background = false,
+                38 | 48 => {
+                    // Extended colors: `38;5;n` or `38;2;r;g;b`.
+                    let skip = match codes.next() {
+                        Some(5) => 1,
+                        Some(2) => 3,
+                        _ => 0,
+                    };
+                    codes.by_ref().take(skip).for_each(drop);
+                    if code == 38 {
+                        self.color = true;
+                    } else {
+                        self.background = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Parses text containing ANSI escape sequences.
+///
+/// SGR sequences (`ESC [ ... m`) are translated into a [`Style`] per character. All other
+/// escape sequences, such as cursor movement or terminal titles, are dropped.
+///
+/// # Arguments
+///
+/// * `text` - The text, e.g. captured output of a program run with colors enabled
+///
+/// # Returns
+///
+/// * `(String, Vec<Style>)` - The text without escape sequences and the style of each of
+///   its characters
+pub fn parse(text: &str) -> (String, Vec<Style>) {
+    let mut plain = String::with_capacity(text.len());
+    let mut styles = Vec::with_capacity(text.len());
+    let mut state = SgrState::default();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            styles.push(state.style());
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters and intermediates up to a final byte in @..~
+            Some('[') => {
+                let mut params = String::new();
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        if c == 'm' {
+                            state.apply(&params);
+                        }
+                        break;
+                    }
+                    params.push(c);
+                }
+            }
+            // OSC: terminated by BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Two character sequences such as `ESC (B`
+            Some('(' | ')') => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+
+    (plain, styles)
+}
+
+/// Removes all ANSI escape sequences from text.
+///
+/// # Arguments
+///
+/// * `text` - The text containing escape sequences
+///
+/// # Returns
+///
+/// * `String` - The plain text
+pub fn strip(text: &str) -> String {
+    parse(text).0
+}
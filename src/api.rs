@@ -1,20 +1,46 @@
-use crate::printer::{PrintTask, print_qr_code, print_task};
+use crate::capabilities;
+use crate::config::Config;
+use crate::counters;
+use crate::dbus::start_dbus_service;
+use crate::graphql::{self, GraphqlSchema};
+use crate::grpc::start_grpc_server;
+use crate::metrics;
+use crate::mqtt;
+use crate::paper;
+use crate::pdf::rasterize_pdf;
+use crate::printer::{Chunking, PrintTask};
+use crate::queue::{self, ImageJob, JobOutcome, JobQueue};
+use crate::raster::{Bitmap, DEFAULT_WIDTH, Dither};
+use crate::scheduler;
+use crate::summary;
+use crate::templates;
+use crate::webhooks;
+use crate::webhooks::{forge::forge_webhook_handler, gitea::Gitea, gitlab::GitLab};
 use axum::{
-    Router,
-    http::StatusCode,
-    response::Json,
+    Router, async_trait,
+    body::Bytes,
+    extract::{DefaultBodyLimit, FromRequest, Multipart, Request, State},
+    http::{HeaderMap, StatusCode, header::CONTENT_TYPE},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
 };
+use base64::Engine;
+use chrono::{DateTime, FixedOffset, Local};
+use escpos::errors::{PrinterError, Result as EscposResult};
 use log::{error, info};
 use serde::{Deserialize, Serialize};
+use std::future::IntoFuture;
+use std::sync::Arc;
 use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio::task::JoinSet;
 use tower_http::cors::CorsLayer;
 
 /// Request payload for the print API endpoint.
 ///
 /// This struct represents the JSON payload that clients send to the `/print` endpoint.
 /// All fields except `message` are optional and will use default values if not provided.
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct PrintRequest {
     /// Optional title for the print job.
     pub title: Option<String>,
@@ -30,6 +56,309 @@ pub struct PrintRequest {
     pub port: Option<u16>,
     /// Character encoding codepage for the printer.
     pub codepage: Option<String>,
+    /// Barcode symbology to print the message as (EAN13, EAN8, UPCA, UPCE, CODE39, ITF, CODE128).
+    pub barcode: Option<String>,
+    /// 2D symbology used when `encode` is true (QR, PDF417, DATAMATRIX).
+    pub symbology: Option<String>,
+    /// Whether to print each line of the message as its own slip.
+    pub split_items: Option<bool>,
+    /// Paper type loaded in the printer ("receipt", "gap" or "mark").
+    pub media: Option<String>,
+    /// Label length in millimeters.
+    pub label_length: Option<u16>,
+    /// URL printed as a QR code below the message.
+    pub link: Option<String>,
+    /// Data printed as a Code128 barcode below the message.
+    pub footer_barcode: Option<String>,
+    /// Rendering mode ("frame", "code" or "badge").
+    pub mode: Option<String>,
+    /// Language used to word wrap and hyphenate the message ("de", "en", "fr", ...).
+    pub hyphenate: Option<String>,
+    /// Whether to replace typographic characters with codepage-safe equivalents.
+    pub normalize: Option<bool>,
+    /// Whether to collapse runs of blank lines in the message into one.
+    pub squeeze_blank: Option<bool>,
+    /// Whether to remove whitespace at the end of every message line.
+    pub trim_trailing: Option<bool>,
+    /// Whether to remove the indentation shared by all message lines.
+    pub dedent: Option<bool>,
+    /// RFC 3339 time after which the job is dropped instead of printed.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+    /// Name of the sender, printed below the message and stored with the job.
+    #[serde(alias = "submitted_by")]
+    pub from: Option<String>,
+    /// Identifier of the job in the submitting system, stored with the job but not
+    /// printed, e.g. an order ID to find the job with `GET /jobs?reference=...`.
+    pub reference: Option<String>,
+}
+
+impl PrintRequest {
+    /// Builds a request from a plain text body, reading the metadata from headers.
+    ///
+    /// The body is the message. `X-Title`, `X-Date`, `X-Link`, `X-From` and
+    /// `X-Reference` set the matching fields and `X-Printer` selects the printer, see
+    /// [`split_printer`].
+    ///
+    /// # Arguments
+    ///
+    /// * `headers` - The request headers
+    /// * `message` - The raw request body
+    ///
+    /// # Returns
+    ///
+    /// * `PrintRequest` - The request with all other fields left at their defaults
+    pub fn from_text(headers: &HeaderMap, message: String) -> Self {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let (address, port) = match header("x-printer").as_deref().map(split_printer) {
+            Some((host, port)) => (Some(host), port),
+            None => (None, None),
+        };
+        PrintRequest {
+            title: header("x-title"),
+            message,
+            date: header("x-date"),
+            address,
+            port,
+            link: header("x-link"),
+            from: header("x-from"),
+            reference: header("x-reference"),
+            ..Default::default()
+        }
+    }
+}
+
+/// Splits a printer given as `host`, `host:port`, `[address]` or `[address]:port`.
+///
+/// A bare IPv6 address such as `fe80::1` is taken as host without port, an IPv6 address
+/// with a port must be written in brackets.
+///
+/// # Arguments
+///
+/// * `printer` - The printer of the `X-Printer` header
+///
+/// # Returns
+///
+/// * `(String, Option<u16>)` - The address without brackets and, if given, the port
+fn split_printer(printer: &str) -> (String, Option<u16>) {
+    if let Some(rest) = printer.strip_prefix('[')
+        && let Some((host, rest)) = rest.split_once(']')
+    {
+        let port = rest.strip_prefix(':').and_then(|port| port.parse().ok());
+        return (host.to_string(), port);
+    }
+    match printer.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => {
+            (host.to_string(), port.parse().ok())
+        }
+        _ => (printer.to_string(), None),
+    }
+}
+
+impl PrintRequest {
+    /// Parses a form-encoded request with the same field names as the JSON schema.
+    ///
+    /// HTML forms submit empty inputs as empty values and checked checkboxes as `on`,
+    /// so empty values are treated as missing and `on` enables the boolean fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The raw `application/x-www-form-urlencoded` request body
+    ///
+    /// # Returns
+    ///
+    /// * `Result<PrintRequest, serde_urlencoded::de::Error>` - The parsed request, or an
+    ///   error if a field is missing or has the wrong type
+    pub fn from_form(body: &[u8]) -> Result<Self, serde_urlencoded::de::Error> {
+        let fields: Vec<(String, String)> =
+            serde_urlencoded::from_bytes::<Vec<(String, String)>>(body)?
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(key, value)| match (key.as_str(), value.as_str()) {
+                    (
+                        "encode" | "split_items" | "normalize" | "squeeze_blank" | "trim_trailing"
+                        | "dedent",
+                        "on",
+                    ) => (key, "true".to_string()),
+                    _ => (key, value),
+                })
+                .collect();
+        let normalized = serde_urlencoded::to_string(&fields)
+            .map_err(|e| serde::de::Error::custom(e.to_string()))?;
+        serde_urlencoded::from_str(&normalized)
+    }
+}
+
+/// Print request body extracted according to its content type.
+///
+/// `text/plain` bodies are read with [`PrintRequest::from_text`] and
+/// `application/x-www-form-urlencoded` bodies are parsed with the same field names as
+/// the JSON schema. Everything else is parsed as JSON.
+pub struct PrintPayload(pub PrintRequest);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for PrintPayload {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+
+        if content_type.starts_with("text/plain") {
+            let headers = req.headers().clone();
+            let message = String::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(PrintPayload(PrintRequest::from_text(&headers, message)))
+        } else if content_type.starts_with("application/x-www-form-urlencoded") {
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            PrintRequest::from_form(&body)
+                .map(PrintPayload)
+                .map_err(|e| {
+                    (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("Failed to deserialize form body: {}", e),
+                    )
+                        .into_response()
+                })
+        } else {
+            let Json(payload) = Json::<PrintRequest>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            Ok(PrintPayload(payload))
+        }
+    }
+}
+
+/// Maximum size of an image or PDF upload in bytes.
+const MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+
+/// Options of the image and PDF print endpoints.
+///
+/// Sent as fields of the JSON body, or as the JSON `options` part of a multipart upload.
+#[derive(Deserialize, Default)]
+pub struct ImageOptions {
+    /// Maximum image width in dots, defaults to 384 (58mm paper).
+    pub width: Option<u32>,
+    /// Dithering method ("floyd-steinberg", "atkinson" or "threshold").
+    pub dithering: Option<String>,
+    /// Text printed below the image.
+    pub caption: Option<String>,
+    /// Number of PDF pages to print, starting at the first page (default: 1).
+    pub pages: Option<u32>,
+    /// Number of dot rows sent per chunk, for printers with a small receive buffer.
+    pub chunk_rows: Option<u32>,
+    /// Milliseconds to wait after every chunk.
+    pub chunk_delay: Option<u64>,
+    /// Whether to wait for the printer to finish every chunk before sending the next.
+    pub flow_control: Option<bool>,
+    /// Network address of the target printer.
+    pub address: Option<String>,
+    /// Network port of the target printer.
+    pub port: Option<u16>,
+    /// Character encoding codepage for the printer.
+    pub codepage: Option<String>,
+    /// RFC 3339 time after which the job is dropped instead of printed.
+    pub expires_at: Option<DateTime<FixedOffset>>,
+}
+
+impl ImageOptions {
+    /// Returns how the images are sent to the printer.
+    fn chunking(&self) -> Chunking {
+        Chunking {
+            rows: self.chunk_rows.unwrap_or(0),
+            delay: self.chunk_delay.unwrap_or(0),
+            flow_control: self.flow_control.unwrap_or(false),
+        }
+    }
+}
+
+/// JSON request payload for the image and PDF print endpoints.
+#[derive(Deserialize)]
+pub struct ImageRequest {
+    /// Base64 encoded image file (PNG, JPEG, GIF, BMP or WebP) or PDF document.
+    #[serde(alias = "pdf")]
+    pub image: String,
+    /// Print options.
+    #[serde(flatten)]
+    pub options: ImageOptions,
+}
+
+/// Image or PDF upload extracted according to its content type.
+///
+/// `multipart/form-data` uploads carry the file in an `image` or `pdf` part and the
+/// [`ImageOptions`] as JSON in an optional `options` part. Everything else is parsed
+/// as an [`ImageRequest`] JSON body.
+pub struct UploadPayload {
+    /// The uploaded file.
+    pub file: Vec<u8>,
+    /// Print options.
+    pub options: ImageOptions,
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for UploadPayload {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        let bad_request = |message: String| (StatusCode::BAD_REQUEST, message).into_response();
+
+        if content_type.starts_with("multipart/form-data") {
+            let mut multipart = Multipart::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            let mut file = None;
+            let mut options = ImageOptions::default();
+            while let Some(field) = multipart
+                .next_field()
+                .await
+                .map_err(IntoResponse::into_response)?
+            {
+                match field.name() {
+                    Some("image" | "pdf") => {
+                        file = Some(field.bytes().await.map_err(IntoResponse::into_response)?);
+                    }
+                    Some("options") => {
+                        let bytes = field.bytes().await.map_err(IntoResponse::into_response)?;
+                        options = serde_json::from_slice(&bytes)
+                            .map_err(|e| bad_request(format!("Invalid options part: {}", e)))?;
+                    }
+                    _ => {}
+                }
+            }
+            let file = file.ok_or_else(|| bad_request("Missing file part".to_string()))?;
+            Ok(UploadPayload {
+                file: file.to_vec(),
+                options,
+            })
+        } else {
+            let Json(request) = Json::<ImageRequest>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            let file = base64::engine::general_purpose::STANDARD
+                .decode(request.image.trim())
+                .map_err(|e| bad_request(format!("Invalid base64 file: {}", e)))?;
+            Ok(UploadPayload {
+                file,
+                options: request.options,
+            })
+        }
+    }
 }
 
 /// Response payload for the print API endpoint.
@@ -43,15 +372,28 @@ pub struct PrintResponse {
     pub message: String,
 }
 
+/// Shared state of the API server, available to all handlers.
+pub struct AppState {
+    /// The loaded application configuration.
+    pub config: Config,
+    /// Queue of the jobs submitted to `/print`.
+    pub queue: Arc<JobQueue>,
+    /// GraphQL schema, if the `[graphql]` endpoint is enabled.
+    pub graphql: Option<GraphqlSchema>,
+}
+
 /// Starts the HTTP API server.
 ///
 /// This function creates and starts an HTTP server that provides REST endpoints
 /// for printing tasks. The server includes CORS support and provides both health
-/// check and print functionality.
+/// check and print functionality. One listener is opened for every bind address, all
+/// serving the same routes.
 ///
 /// # Arguments
 ///
 /// * `port` - The port number to bind the server to (e.g., 3000)
+/// * `bind` - The addresses to listen on, e.g. `0.0.0.0`, `127.0.0.1` or `::1`
+/// * `config` - The application configuration used by the integrations
 ///
 /// # Returns
 ///
@@ -63,28 +405,166 @@ pub struct PrintResponse {
 /// - `GET /` - Health check endpoint
 /// - `GET /health` - Health check endpoint  
 /// - `POST /print` - Print a task
+/// - `POST /print/image` - Print an uploaded image
+/// - `POST /print/pdf` - Print the first pages of an uploaded PDF
+/// - `POST /print/{template}` - Print a template defined in the config
+/// - `GET /metrics` - Prometheus metrics per printer
+/// - `GET /jobs` - List queued, running and recently finished jobs
+/// - `GET /jobs/{id}` - Show the status of a job
+/// - `DELETE /jobs/{id}` - Cancel a waiting job
+/// - `GET /queue` - Show whether the queue is paused and how many jobs wait
+/// - `POST /queue/pause` - Hold new jobs until the queue is resumed
+/// - `POST /queue/resume` - Print the held jobs
+/// - `POST /admin/purge` - Remove finished jobs from the history
+/// - `POST /admin/summary` - Print the usage summary of the week so far
+/// - `GET /paper` - Show the estimated paper used and left per printer
+/// - `POST /paper/{printer}/reset` - Start a new paper roll
+/// - `GET /printers/{printer}/capabilities` - Show the features of the printer's model
+/// - `POST /graphql` - Query jobs, printers and stats with GraphQL
+/// - `GET /graphql` - GraphQL Playground
+/// - `GET /graphql/ws` - GraphQL subscriptions to job events over WebSocket
+/// - `GET /counters` - List all counters
+/// - `GET /counters/{name}` - Read a counter
+/// - `POST /counters/{name}` - Increment, set or reset a counter
+/// - `POST /webhook/ci` - Print failed CI runs from GitHub Actions or generic CI webhooks
+/// - `POST /webhook/jira` - Print created and assigned Jira issues
+/// - `POST /webhook/gitlab` - Print GitLab issue and merge request events
+/// - `POST /webhook/gitea` - Print Gitea issue and pull request events
+/// - `POST /webhook/{name}` - Print signed payloads of webhooks defined in the config
+/// - `POST /discord/interactions` - Discord interactions endpoint for the `/print` command
 ///
 /// # Examples
 ///
 /// ```
 /// use taskprinter::api::start_api_server;
+/// use taskprinter::config::Config;
 ///
 /// #[tokio::main]
 /// async fn main() {
-///     start_api_server(3000).await.expect("Server failed");
+///     let bind = ["127.0.0.1".to_string(), "::1".to_string()];
+///     start_api_server(3000, &bind, Config::default()).await.expect("Server failed");
 /// }
 /// ```
-pub async fn start_api_server(port: u16) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn start_api_server(
+    port: u16,
+    bind: &[String],
+    config: Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let queue = JobQueue::start(
+        config.spool.clone(),
+        config.verify.clone(),
+        config.queue.clone().unwrap_or_default(),
+        config.history.clone().unwrap_or_default(),
+    );
+    let graphql = config
+        .graphql
+        .as_ref()
+        .map(|_| graphql::schema(Arc::clone(&queue)));
+    let state = Arc::new(AppState {
+        config,
+        queue,
+        graphql,
+    });
+    if let Some(grpc) = &state.config.grpc {
+        let (port, queue) = (grpc.port, Arc::clone(&state.queue));
+        tokio::spawn(async move {
+            if let Err(e) = start_grpc_server(port, queue).await {
+                error!("gRPC server failed: {}", e);
+            }
+        });
+    }
+
+    if let Some(dbus) = &state.config.dbus {
+        let (config, queue) = (dbus.clone(), Arc::clone(&state.queue));
+        tokio::spawn(async move {
+            if let Err(e) = start_dbus_service(config, queue).await {
+                error!("D-Bus service failed: {}", e);
+            }
+        });
+    }
+    if let Some(mqtt) = &state.config.mqtt {
+        mqtt::start(mqtt.clone(), Arc::clone(&state.queue));
+    }
+    if let Some(summary) = &state.config.summary {
+        summary::start(summary.clone(), Arc::clone(&state.queue))?;
+    }
+    scheduler::start(state.config.schedule.clone(), Arc::clone(&state.queue))?;
+
     let app = Router::new()
         .route("/", get(health_check))
         .route("/health", get(health_check))
         .route("/print", post(print_handler))
-        .layer(CorsLayer::permissive());
+        .route(
+            "/print/image",
+            post(print_image_handler).layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES)),
+        )
+        .route(
+            "/print/pdf",
+            post(print_pdf_handler).layer(DefaultBodyLimit::max(MAX_IMAGE_BYTES)),
+        )
+        .route("/print/:template", post(templates::template_print_handler))
+        .route("/metrics", get(metrics::metrics_handler))
+        .route("/jobs", get(queue::list_jobs_handler))
+        .route(
+            "/jobs/:id",
+            get(queue::get_job_handler).delete(queue::cancel_job_handler),
+        )
+        .route("/queue", get(queue::queue_status_handler))
+        .route("/queue/pause", post(queue::pause_queue_handler))
+        .route("/queue/resume", post(queue::resume_queue_handler))
+        .route("/admin/purge", post(queue::purge_history_handler))
+        .route("/admin/summary", post(summary::print_summary_handler))
+        .route("/paper", get(paper::list_rolls_handler))
+        .route("/paper/:printer/reset", post(paper::reset_roll_handler))
+        .route(
+            "/printers/:printer/capabilities",
+            get(capabilities::capabilities_handler),
+        )
+        .route(
+            "/graphql",
+            get(graphql::graphql_playground_handler).post(graphql::graphql_handler),
+        )
+        .route("/graphql/ws", get(graphql::graphql_ws_handler))
+        .route("/counters", get(counters::list_counters_handler))
+        .route(
+            "/counters/:name",
+            get(counters::get_counter_handler).post(counters::update_counter_handler),
+        )
+        .route("/webhook/ci", post(webhooks::ci::ci_webhook_handler))
+        .route("/webhook/jira", post(webhooks::jira::jira_webhook_handler))
+        .route("/webhook/gitlab", post(forge_webhook_handler::<GitLab>))
+        .route("/webhook/gitea", post(forge_webhook_handler::<Gitea>))
+        .route(
+            "/webhook/:name",
+            post(webhooks::generic::generic_webhook_handler),
+        )
+        .route(
+            "/discord/interactions",
+            post(webhooks::discord::discord_interactions_handler),
+        )
+        .layer(CorsLayer::permissive())
+        .with_state(state);
 
-    let listener = TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
-    info!("API server running on http://0.0.0.0:{}", port);
+    if bind.is_empty() {
+        return Err("no address to bind the API server to".into());
+    }
+    let mut listeners = Vec::new();
+    for address in bind {
+        let host = address.trim_start_matches('[').trim_end_matches(']');
+        let listener = TcpListener::bind((host, port))
+            .await
+            .map_err(|e| format!("cannot listen on {} port {}: {}", address, port, e))?;
+        info!("API server running on http://{}", listener.local_addr()?);
+        listeners.push(listener);
+    }
 
-    axum::serve(listener, app).await?;
+    let mut servers = JoinSet::new();
+    for listener in listeners {
+        servers.spawn(axum::serve(listener, app.clone()).into_future());
+    }
+    while let Some(result) = servers.join_next().await {
+        result??;
+    }
     Ok(())
 }
 
@@ -117,16 +597,24 @@ async fn health_check() -> Json<serde_json::Value> {
 /// Print endpoint handler.
 ///
 /// This function handles POST requests to `/print` endpoint. It accepts a JSON payload
-/// containing print job details, converts it to a `PrintTask`, and attempts to print it.
+/// containing print job details, converts it to a `PrintTask`, queues it and waits
+/// until it is printed. A `text/plain` body is printed as the message, with the title
+/// and printer taken from the `X-Title` and `X-Printer` headers. Form-encoded bodies use
+/// the JSON field names.
 ///
 /// # Arguments
 ///
-/// * `payload` - A `PrintRequest` extracted from the JSON request body
+/// * `state` - The shared state holding the print queue
+/// * `payload` - A `PrintRequest` extracted from the JSON, form or plain text request body
 ///
 /// # Returns
 ///
-/// * `Result<Json<PrintResponse>, StatusCode>` - On success, returns a JSON response
-///   with success status. On failure, returns HTTP 500 Internal Server Error.
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - On success, returns a JSON
+///   response with success status, with HTTP 202 Accepted if the printer is unreachable
+///   and the job was spooled. Invalid input (e.g. malformed barcode data) returns HTTP 400
+///   Bad Request, a full queue returns HTTP 429 Too Many Requests with a `Retry-After`
+///   header, a job that expired or was cancelled while waiting returns HTTP 410 Gone,
+///   any other failure returns HTTP 500 Internal Server Error.
 ///
 /// # Request Format
 ///
@@ -138,7 +626,22 @@ async fn health_check() -> Json<serde_json::Value> {
 ///   "encode": false,
 ///   "address": "printer.local",
 ///   "port": 9100,
-///   "codepage": "PC850"
+///   "codepage": "PC850",
+///   "barcode": null,
+///   "symbology": "QR",
+///   "split_items": false,
+///   "media": "receipt",
+///   "label_length": null,
+///   "link": null,
+///   "footer_barcode": null,
+///   "mode": "frame",
+///   "hyphenate": null,
+///   "normalize": true,
+///   "squeeze_blank": false,
+///   "trim_trailing": false,
+///   "dedent": false,
+///   "expires_at": "2025-08-26T09:55:00+02:00",
+///   "from": "Mom"
 /// }
 /// ```
 ///
@@ -151,9 +654,11 @@ async fn health_check() -> Json<serde_json::Value> {
 /// }
 /// ```
 async fn print_handler(
-    Json(payload): Json<PrintRequest>,
-) -> Result<Json<PrintResponse>, StatusCode> {
+    State(state): State<Arc<AppState>>,
+    PrintPayload(payload): PrintPayload,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
     info!("Received print request");
+    let expires_at = payload.expires_at.map(|at| at.with_timezone(&Local));
     let task = PrintTask {
         title: payload.title,
         message: payload.message,
@@ -162,23 +667,258 @@ async fn print_handler(
         address: payload.address,
         port: payload.port,
         codepage: payload.codepage,
+        barcode: payload.barcode,
+        symbology: payload.symbology,
+        split_items: payload.split_items,
+        media: payload.media,
+        label_length: payload.label_length,
+        link: payload.link,
+        footer_barcode: payload.footer_barcode,
+        mode: payload.mode,
+        hyphenate: payload.hyphenate,
+        normalize: payload.normalize,
+        squeeze_blank: payload.squeeze_blank,
+        trim_trailing: payload.trim_trailing,
+        dedent: payload.dedent,
+        header: None,
+        from: payload.from.filter(|from| !from.trim().is_empty()),
     };
 
-    let result;
-    if task.encode == Some(true) {
-        result = print_qr_code(task);
-    } else {
-        result = print_task(task);
+    queue_task(
+        &state.queue,
+        task,
+        expires_at,
+        payload
+            .reference
+            .filter(|reference| !reference.trim().is_empty()),
+    )
+    .await
+}
+
+/// Queues a task and waits until it is printed, spooled or dropped.
+///
+/// # Arguments
+///
+/// * `queue` - The print queue of the API server
+/// * `task` - The task to print
+/// * `expires_at` - Optional time after which the task must not be printed anymore
+/// * `reference` - Optional identifier of the job in the submitting system
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - The response of the print
+///   endpoint: a success response, HTTP 202 Accepted if the job was spooled, HTTP 400
+///   for invalid input, HTTP 429 with `Retry-After` if the queue is full, HTTP 410 if the
+///   job expired or was cancelled and HTTP 500 for any other failure
+pub(crate) async fn queue_task(
+    queue: &JobQueue,
+    task: PrintTask,
+    expires_at: Option<DateTime<Local>>,
+    reference: Option<String>,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let (id, outcome) = queue
+        .submit(task, expires_at, reference)
+        .map_err(IntoResponse::into_response)?;
+    info!("Queued print job {}", id);
+    job_response(outcome).await
+}
+
+/// Waits for the outcome of a queued job and converts it into an API response, see
+/// [`queue_task`].
+async fn job_response(
+    outcome: oneshot::Receiver<JobOutcome>,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    match outcome.await {
+        Ok(JobOutcome::Printed) => print_response(Ok(()))
+            .map(|json| (StatusCode::OK, json))
+            .map_err(IntoResponse::into_response),
+        Ok(JobOutcome::Failed(e)) => print_response(Err(e))
+            .map(|json| (StatusCode::OK, json))
+            .map_err(IntoResponse::into_response),
+        Ok(JobOutcome::Expired | JobOutcome::Cancelled) => Err(StatusCode::GONE.into_response()),
+        Ok(JobOutcome::Spooled) => Ok((
+            StatusCode::ACCEPTED,
+            Json(PrintResponse {
+                success: true,
+                message: "Printer unreachable, print job spooled".to_string(),
+            }),
+        )),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
     }
+}
 
+/// Image print endpoint handler.
+///
+/// This function handles POST requests to `/print/image`. The image is scaled down to
+/// the requested width, dithered to black and white, queued and printed with an
+/// optional caption below it.
+///
+/// # Arguments
+///
+/// * `state` - The shared state holding the print queue
+/// * `payload` - An `UploadPayload` extracted from a multipart upload or JSON body
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - HTTP 400 Bad Request if the
+///   image cannot be decoded or the options are invalid, otherwise the response of the
+///   print queue, see [`queue_task`]
+///
+/// # Request Format
+///
+/// ```bash
+/// curl -F image=@photo.jpg \
+///      -F 'options={"width": 384, "dithering": "atkinson", "caption": "Hello"}' \
+///      http://localhost:3000/print/image
+/// ```
+async fn print_image_handler(
+    State(state): State<Arc<AppState>>,
+    payload: UploadPayload,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    info!("Received image print request");
+    let UploadPayload { file, options } = payload;
+    let (width, dithering) = (options.width, options.dithering.clone());
+    let bitmap = run_blocking(move || {
+        let dither = Dither::parse(dithering.as_deref())?;
+        Bitmap::decode(&file, width.unwrap_or(DEFAULT_WIDTH), dither)
+    })
+    .await;
+    queue_images(&state.queue, options, bitmap.map(|bitmap| vec![bitmap])).await
+}
+
+/// PDF print endpoint handler.
+///
+/// This function handles POST requests to `/print/pdf`. The first `pages` pages of the
+/// document are rendered at the requested width, dithered, queued and printed one after
+/// another, each followed by a cut. Text-heavy documents such as shipping labels usually
+/// look best with `"dithering": "threshold"`.
+///
+/// # Arguments
+///
+/// * `state` - The shared state holding the print queue
+/// * `payload` - An `UploadPayload` extracted from a multipart upload or JSON body
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - HTTP 400 Bad Request if the
+///   document cannot be rendered or the options are invalid, HTTP 500 Internal Server
+///   Error if `pdftoppm` is missing, otherwise the response of the print queue, see
+///   [`queue_task`]
+///
+/// # Request Format
+///
+/// ```bash
+/// curl -F pdf=@label.pdf -F 'options={"pages": 1, "dithering": "threshold"}' \
+///      http://localhost:3000/print/pdf
+/// ```
+async fn print_pdf_handler(
+    State(state): State<Arc<AppState>>,
+    payload: UploadPayload,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    info!("Received PDF print request");
+    let UploadPayload { file, options } = payload;
+    let (pages, width, dithering) = (options.pages, options.width, options.dithering.clone());
+    let pages = run_blocking(move || {
+        let dither = Dither::parse(dithering.as_deref())?;
+        rasterize_pdf(
+            &file,
+            pages.unwrap_or(1),
+            width.unwrap_or(DEFAULT_WIDTH),
+            dither,
+        )
+    })
+    .await;
+    queue_images(&state.queue, options, pages).await
+}
+
+/// Runs decoding or rendering work on the blocking thread pool, off the async runtime.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> EscposResult<T> + Send + 'static,
+) -> EscposResult<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| Err(PrinterError::Io(e.to_string())))
+}
+
+/// Queues the rendered images of an upload and waits until they are printed, see
+/// [`queue_task`].
+async fn queue_images(
+    queue: &JobQueue,
+    options: ImageOptions,
+    images: EscposResult<Vec<Bitmap>>,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let images = match images {
+        Ok(images) => ImageJob {
+            images,
+            chunking: options.chunking(),
+        },
+        Err(e) => return Err(print_response(Err(e)).into_response()),
+    };
+    let expires_at = options.expires_at.map(|at| at.with_timezone(&Local));
+    let task = PrintTask {
+        message: options.caption.unwrap_or_default(),
+        address: options.address,
+        port: options.port,
+        codepage: options.codepage,
+        ..Default::default()
+    };
+    let (id, outcome) = queue
+        .submit_images(task, images, expires_at, None)
+        .map_err(IntoResponse::into_response)?;
+    info!("Queued image job {}", id);
+    job_response(outcome).await
+}
+
+/// Converts the result of a print operation into an API response.
+///
+/// # Arguments
+///
+/// * `result` - The result returned by one of the printer functions
+///
+/// # Returns
+///
+/// * `Result<Json<PrintResponse>, StatusCode>` - A success response, HTTP 400 Bad Request
+///   for invalid input or HTTP 500 Internal Server Error for any other failure
+pub(crate) fn print_response(result: EscposResult<()>) -> Result<Json<PrintResponse>, StatusCode> {
     match result {
         Ok(()) => Ok(Json(PrintResponse {
             success: true,
             message: "Print job completed successfully".to_string(),
         })),
+        Err(PrinterError::Input(e)) => {
+            error!("Invalid print request: {}", e);
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             error!("Print error: {}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_printer_header() {
+        let cases = [
+            ("printer.local", ("printer.local", None)),
+            ("printer.local:9100", ("printer.local", Some(9100))),
+            ("10.0.0.5:9101", ("10.0.0.5", Some(9101))),
+            ("fe80::1", ("fe80::1", None)),
+            ("2001:db8::9100", ("2001:db8::9100", None)),
+            ("[fe80::1]", ("fe80::1", None)),
+            ("[fe80::1]:9100", ("fe80::1", Some(9100))),
+            ("printer.local:http", ("printer.local:http", None)),
+        ];
+        for (printer, (host, port)) in cases {
+            assert_eq!(
+                split_printer(printer),
+                (host.to_string(), port),
+                "{}",
+                printer
+            );
+        }
+    }
+}
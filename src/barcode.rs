@@ -0,0 +1,363 @@
+use escpos::errors::{PrinterError, Result as EscposResult};
+
+/// Characters accepted by the Code39 symbology (the `*` start/stop character is added by the printer).
+const CODE39_CHARSET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ -.$/+%";
+
+/// Supported one-dimensional barcode symbologies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbology {
+    Ean13,
+    Ean8,
+    UpcA,
+    UpcE,
+    Code39,
+    Itf,
+    Code128,
+}
+
+impl Symbology {
+    /// Parses a symbology name such as `EAN13`, `UPC-A` or `code39`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The symbology name, case-insensitive and with optional dashes
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Symbology>` - The matching symbology, or an input error for unknown names
+    pub fn parse(name: &str) -> EscposResult<Symbology> {
+        match name.to_uppercase().replace(['-', '_'], "").as_str() {
+            "EAN13" => Ok(Symbology::Ean13),
+            "EAN8" => Ok(Symbology::Ean8),
+            "UPCA" => Ok(Symbology::UpcA),
+            "UPCE" => Ok(Symbology::UpcE),
+            "CODE39" => Ok(Symbology::Code39),
+            "ITF" => Ok(Symbology::Itf),
+            "CODE128" => Ok(Symbology::Code128),
+            _ => Err(PrinterError::Input(format!(
+                "unsupported barcode symbology: {}",
+                name
+            ))),
+        }
+    }
+}
+
+/// Supported two-dimensional symbologies for encoded messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code2D {
+    Qr,
+    Pdf417,
+    DataMatrix,
+}
+
+impl Code2D {
+    /// Parses a 2D symbology name such as `QR`, `PDF417` or `DataMatrix`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The symbology name, case-insensitive and with optional dashes
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Code2D>` - The matching symbology, or an input error for unknown names
+    pub fn parse(name: &str) -> EscposResult<Code2D> {
+        match name.to_uppercase().replace(['-', '_'], "").as_str() {
+            "QR" | "QRCODE" => Ok(Code2D::Qr),
+            "PDF417" => Ok(Code2D::Pdf417),
+            "DATAMATRIX" => Ok(Code2D::DataMatrix),
+            _ => Err(PrinterError::Input(format!(
+                "unsupported 2D symbology: {}",
+                name
+            ))),
+        }
+    }
+
+    /// Maximum number of data bytes the symbology can hold in byte mode.
+    fn capacity(self) -> usize {
+        match self {
+            Code2D::Qr => 2953,
+            Code2D::Pdf417 => 1850,
+            Code2D::DataMatrix => 1556,
+        }
+    }
+}
+
+/// Validates that a message fits into the given 2D symbology.
+///
+/// # Arguments
+///
+/// * `code` - The 2D symbology the message will be encoded with
+/// * `data` - The message content
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) if the data fits, or an input error describing the problem
+pub fn validate_2d(code: Code2D, data: &str) -> EscposResult<()> {
+    if data.is_empty() {
+        return Err(PrinterError::Input(format!(
+            "cannot encode an empty message as {:?}",
+            code
+        )));
+    }
+    if data.len() > code.capacity() {
+        return Err(PrinterError::Input(format!(
+            "message of {} bytes exceeds the {:?} capacity of {} bytes",
+            data.len(),
+            code,
+            code.capacity()
+        )));
+    }
+    Ok(())
+}
+
+/// Validates barcode data against the rules of the given symbology.
+///
+/// Validation happens before a printer connection is opened, so malformed input is
+/// rejected with a descriptive error instead of producing a garbled or empty barcode.
+/// When the full code including the check digit is supplied, the check digit is verified.
+///
+/// # Arguments
+///
+/// * `symbology` - The symbology the data will be encoded with
+/// * `data` - The barcode content
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) if the data can be encoded, or an input error describing the problem
+pub fn validate(symbology: Symbology, data: &str) -> EscposResult<()> {
+    let all_digits = !data.is_empty() && data.chars().all(|c| c.is_ascii_digit());
+
+    match symbology {
+        Symbology::Ean13 => validate_gtin("EAN13", data, all_digits, 12),
+        Symbology::Ean8 => validate_gtin("EAN8", data, all_digits, 7),
+        Symbology::UpcA => validate_gtin("UPC-A", data, all_digits, 11),
+        Symbology::UpcE => {
+            if !all_digits || !(6..=8).contains(&data.len()) {
+                return Err(invalid("UPC-E", data, "expected 6, 7 or 8 digits"));
+            }
+            if data.len() >= 7 && !data.starts_with('0') {
+                return Err(invalid("UPC-E", data, "number system must be 0"));
+            }
+            if data.len() == 8 {
+                let body = &data[1..7];
+                let expected = check_digit(&expand_upce(body));
+                if data[7..].parse::<u32>().ok() != Some(expected) {
+                    return Err(invalid("UPC-E", data, "check digit mismatch"));
+                }
+            }
+            Ok(())
+        }
+        Symbology::Code39 => {
+            if data.is_empty() || data.len() > 255 {
+                return Err(invalid("CODE39", data, "expected 1 to 255 characters"));
+            }
+            match data.chars().find(|c| !CODE39_CHARSET.contains(*c)) {
+                Some(c) => Err(invalid(
+                    "CODE39",
+                    data,
+                    &format!(
+                        "character '{}' is not allowed (use 0-9, A-Z, space, - . $ / + %)",
+                        c
+                    ),
+                )),
+                None => Ok(()),
+            }
+        }
+        Symbology::Itf => {
+            if !all_digits || !data.len().is_multiple_of(2) {
+                return Err(invalid("ITF", data, "expected an even number of digits"));
+            }
+            Ok(())
+        }
+        Symbology::Code128 => {
+            // The encoded data, including the code set prefix and doubled braces, is limited to 255 bytes.
+            if data.is_empty() || data.len() + data.matches('{').count() > 253 {
+                return Err(invalid("CODE128", data, "expected 1 to 253 characters"));
+            }
+            match data.chars().find(|c| !(' '..='~').contains(c)) {
+                Some(c) => Err(invalid(
+                    "CODE128",
+                    data,
+                    &format!("character '{}' is not printable ASCII", c),
+                )),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+/// Builds the commands printing a Code128 barcode with the human readable text below.
+///
+/// The escpos crate has no Code128 support, so the `GS k` command (function B, `m = 73`)
+/// is built by hand. Code set B encodes all printable ASCII characters; a literal `{`
+/// starts a code set switch and is therefore doubled. The module width is reduced for
+/// long data so the barcode still fits on 58mm paper.
+///
+/// # Arguments
+///
+/// * `data` - The barcode content, validated with [`validate`]
+///
+/// # Returns
+///
+/// * `Vec<u8>` - The ESC/POS commands
+pub fn code128_command(data: &str) -> Vec<u8> {
+    let mut encoded = b"{B".to_vec();
+    for byte in data.bytes() {
+        encoded.push(byte);
+        if byte == b'{' {
+            encoded.push(b'{');
+        }
+    }
+
+    // 11 modules per character plus start, check and stop symbols, 384 dots on 58mm paper
+    let modules = (data.len() + 3) * 11 + 2;
+    let module_width = if modules * 2 <= 384 { 2 } else { 1 };
+
+    let mut command = vec![0x1D, b'h', 102, 0x1D, b'w', module_width, 0x1D, b'H', 2];
+    command.extend([0x1D, b'k', 73, encoded.len() as u8]);
+    command.extend(encoded);
+    command
+}
+
+/// Validates EAN/UPC-A style data that is either the bare payload or payload plus check digit.
+fn validate_gtin(name: &str, data: &str, all_digits: bool, payload_len: usize) -> EscposResult<()> {
+    if !all_digits || (data.len() != payload_len && data.len() != payload_len + 1) {
+        return Err(invalid(
+            name,
+            data,
+            &format!("expected {} or {} digits", payload_len, payload_len + 1),
+        ));
+    }
+    if data.len() == payload_len + 1 {
+        let expected = check_digit(&data[..payload_len]);
+        if data[payload_len..].parse::<u32>().ok() != Some(expected) {
+            return Err(invalid(name, data, "check digit mismatch"));
+        }
+    }
+    Ok(())
+}
+
+/// Computes the GS1 modulo-10 check digit for a string of digits.
+fn check_digit(payload: &str) -> u32 {
+    let sum: u32 = payload
+        .chars()
+        .rev()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { d })
+        .sum();
+    (10 - sum % 10) % 10
+}
+
+/// Expands a six digit UPC-E body into the eleven digit UPC-A payload it represents.
+fn expand_upce(body: &str) -> String {
+    let d: Vec<char> = body.chars().collect();
+    let middle = match d[5] {
+        '0' | '1' | '2' => format!("{}{}{}0000{}{}{}", d[0], d[1], d[5], d[2], d[3], d[4]),
+        '3' => format!("{}{}{}00000{}{}", d[0], d[1], d[2], d[3], d[4]),
+        '4' => format!("{}{}{}{}00000{}", d[0], d[1], d[2], d[3], d[4]),
+        _ => format!("{}{}{}{}{}0000{}", d[0], d[1], d[2], d[3], d[4], d[5]),
+    };
+    format!("0{}", middle)
+}
+
+fn invalid(name: &str, data: &str, reason: &str) -> PrinterError {
+    PrinterError::Input(format!("invalid {} data '{}': {}", name, data, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepts(symbology: Symbology, data: &[&str]) {
+        for data in data {
+            assert!(
+                validate(symbology, data).is_ok(),
+                "{:?} {}",
+                symbology,
+                data
+            );
+        }
+    }
+
+    fn rejects(symbology: Symbology, data: &[&str]) {
+        for data in data {
+            assert!(
+                matches!(validate(symbology, data), Err(PrinterError::Input(_))),
+                "{:?} {}",
+                symbology,
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn validates_ean_and_upc_a_check_digits() {
+        accepts(Symbology::Ean13, &["400638133393", "4006381333931"]);
+        rejects(
+            Symbology::Ean13,
+            &[
+                "4006381333932",
+                "40063813339",
+                "40063813339311",
+                "40063813339A",
+                "",
+            ],
+        );
+        accepts(Symbology::Ean8, &["9638507", "96385074"]);
+        rejects(Symbology::Ean8, &["96385075", "963850", "963850745"]);
+        accepts(Symbology::UpcA, &["03600029145", "036000291452"]);
+        rejects(
+            Symbology::UpcA,
+            &["036000291453", "0360002914", "0360002914520"],
+        );
+    }
+
+    #[test]
+    fn validates_upc_e() {
+        accepts(Symbology::UpcE, &["425261", "0425261", "04252614"]);
+        rejects(
+            Symbology::UpcE,
+            &[
+                "04252615",
+                "14252614",
+                "1425261",
+                "42526",
+                "042526145",
+                "42526A",
+            ],
+        );
+    }
+
+    #[test]
+    fn expands_upc_e_by_last_digit() {
+        assert_eq!(expand_upce("425261"), "04210000526");
+        assert_eq!(expand_upce("123450"), "01200000345");
+        assert_eq!(expand_upce("123453"), "01230000045");
+        assert_eq!(expand_upce("123454"), "01234000005");
+        assert_eq!(expand_upce("123456"), "01234500006");
+        assert_eq!(check_digit("04210000526"), 4);
+    }
+
+    #[test]
+    fn validates_code39_charset() {
+        accepts(Symbology::Code39, &["ABC-123", "PART 7/8 $5.00+%"]);
+        rejects(Symbology::Code39, &["abc", "A*B", "A_B", ""]);
+        rejects(Symbology::Code39, &[&"A".repeat(256)]);
+    }
+
+    #[test]
+    fn validates_itf_even_length() {
+        accepts(Symbology::Itf, &["12", "12345678"]);
+        rejects(Symbology::Itf, &["123", "12A4", ""]);
+    }
+
+    #[test]
+    fn validates_code128_length_and_charset() {
+        accepts(Symbology::Code128, &["Order {42}", "~ !"]);
+        rejects(Symbology::Code128, &["", "Café", "tab\there"]);
+        accepts(Symbology::Code128, &[&"x".repeat(253)]);
+        // Every `{` is doubled when encoded.
+        rejects(Symbology::Code128, &[&"x".repeat(254), &"{".repeat(127)]);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::config::{Capabilities, Cutter, PrinterProfile};
+use crate::metrics::parse_printer;
+use crate::printer::{is_supported_codepage, profile, resolve_printer};
+use axum::{extract::Path, response::Json};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Printer models from the `[models]` section, keyed by lower case model name.
+static MODELS: OnceLock<HashMap<String, Capabilities>> = OnceLock::new();
+
+/// Returns the capabilities of a bundled printer model.
+///
+/// The generic models describe common 80mm and 58mm printers. Model names are matched
+/// case-insensitively.
+fn bundled(model: &str) -> Option<Capabilities> {
+    match model {
+        "generic-80mm" | "tm-t20" | "tm-t88" => Some(Capabilities::default()),
+        "generic-58mm" => Some(Capabilities {
+            font_a_columns: 32,
+            font_b_columns: 42,
+            cutter: Cutter::None,
+            ..Capabilities::default()
+        }),
+        _ => None,
+    }
+}
+
+/// Registers the printer models of the configuration and checks the models of the
+/// printer profiles.
+///
+/// The models can only be registered once, later calls are ignored. Models defined in
+/// the configuration take precedence over bundled models of the same name.
+///
+/// # Arguments
+///
+/// * `models` - The `[models]` section of the configuration
+/// * `profiles` - The `[printers]` section of the configuration
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for a model with an unsupported
+///   codepage or a printer with an unknown model
+pub fn init(
+    models: &HashMap<String, Capabilities>,
+    profiles: &HashMap<String, PrinterProfile>,
+) -> EscposResult<()> {
+    for (name, model) in models {
+        if let Some(codepage) = model
+            .codepages
+            .iter()
+            .find(|codepage| !is_supported_codepage(codepage))
+        {
+            return Err(PrinterError::Input(format!(
+                "unsupported codepage {} of printer model {}",
+                codepage, name
+            )));
+        }
+    }
+    let _ = MODELS.set(
+        models
+            .iter()
+            .map(|(name, model)| (name.to_lowercase(), model.clone()))
+            .collect(),
+    );
+
+    for (printer, profile) in profiles {
+        if let Some(model) = &profile.model
+            && lookup(model).is_none()
+        {
+            return Err(PrinterError::Input(format!(
+                "unknown model {} of printer {}",
+                model, printer
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the capabilities of a printer model, configured or bundled.
+///
+/// # Arguments
+///
+/// * `model` - The model name, case-insensitive
+///
+/// # Returns
+///
+/// * `Option<Capabilities>` - The capabilities, or None for an unknown model
+pub fn lookup(model: &str) -> Option<Capabilities> {
+    let model = model.to_lowercase();
+    MODELS
+        .get()
+        .and_then(|models| models.get(&model))
+        .cloned()
+        .or_else(|| bundled(&model))
+}
+
+/// Returns the capabilities of the model of a printer profile, or those of a generic
+/// 80mm printer if the profile names no model.
+///
+/// # Arguments
+///
+/// * `profile` - The printer profile
+///
+/// # Returns
+///
+/// * `Capabilities` - The capabilities slips for the printer are rendered with
+pub fn of(profile: &PrinterProfile) -> Capabilities {
+    profile
+        .model
+        .as_deref()
+        .and_then(lookup)
+        .unwrap_or_default()
+}
+
+/// Printer capabilities endpoint handler.
+///
+/// This function handles GET requests to `/printers/{printer}/capabilities`, with the
+/// printer given as `host` or `host:port`.
+///
+/// # Returns
+///
+/// * `Json<Capabilities>` - The capabilities of the model of the printer
+pub async fn capabilities_handler(Path(printer): Path<String>) -> Json<Capabilities> {
+    let (address, port) = parse_printer(&printer);
+    let (address, port) = resolve_printer(Some(&address), port);
+    Json(of(&profile(&address, port)))
+}
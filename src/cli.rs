@@ -1,8 +1,30 @@
-use crate::printer::{PrintTask, print_qr_code, print_task};
-use clap::Parser;
-use escpos::errors::Result as EscposResult;
+use crate::ansi;
+use crate::client;
+use crate::compose::ComposeArgs;
+use crate::fortune::FortuneArgs;
+use crate::git_hook::GitHookArgs;
+use crate::journal::JournalArgs;
+use crate::matrix::MatrixArgs;
+use crate::pdf::rasterize_pdf;
+use crate::pomodoro::PomodoroArgs;
+use crate::printer::{
+    Chunking, PrintTask, RenderMode, RenderOptions, print_barcode, print_code_2d, print_images,
+    print_task, render_task, render_text,
+};
+use crate::puzzle::PuzzleArgs;
+use crate::queue::QueueArgs;
+use crate::raster::{DEFAULT_WIDTH, Dither};
+use crate::replay::ReplayArgs;
+use crate::syslog::SyslogArgs;
+use crate::tail::TailArgs;
+use crate::watch::WatchArgs;
+use crate::weather::WeatherArgs;
+use chrono::{DateTime, FixedOffset, Local};
+use clap::{Parser, Subcommand};
+use escpos::errors::{PrinterError, Result as EscposResult};
 use log::error;
-use std::io::{self, Read};
+use std::fs;
+use std::io::{self, BufRead, Read, Write};
 
 #[derive(Parser)]
 #[command(name = "taskprinter")]
@@ -20,27 +42,165 @@ pub struct Args {
     #[arg(short, long)]
     pub encode: Option<bool>,
 
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub address: Option<String>,
 
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub port: Option<u16>,
 
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub codepage: Option<String>,
 
+    #[arg(short, long)]
+    pub barcode: Option<String>,
+
+    #[arg(short, long)]
+    pub symbology: Option<String>,
+
+    #[arg(long)]
+    pub split_items: bool,
+
+    #[arg(long)]
+    pub media: Option<String>,
+
+    #[arg(long)]
+    pub label_length: Option<u16>,
+
+    #[arg(long)]
+    pub link: Option<String>,
+
+    #[arg(long)]
+    pub footer_barcode: Option<String>,
+
+    #[arg(long)]
+    pub from: Option<String>,
+
+    #[arg(long)]
+    pub mode: Option<String>,
+
+    #[arg(long)]
+    pub hyphenate: Option<String>,
+
+    #[arg(long)]
+    pub no_normalize: bool,
+
+    #[arg(long)]
+    pub squeeze_blank: bool,
+
+    #[arg(long)]
+    pub trim_trailing: bool,
+
+    #[arg(long)]
+    pub dedent: bool,
+
+    #[arg(long)]
+    pub strip_ansi: bool,
+
+    #[arg(long, conflicts_with_all = ["message", "pdf"])]
+    pub clipboard: bool,
+
+    #[arg(long, conflicts_with_all = ["message", "pdf", "clipboard"])]
+    pub stream: bool,
+
+    #[arg(long, requires = "stream")]
+    pub paragraphs: bool,
+
+    #[arg(long)]
+    pub pdf: Option<String>,
+
+    #[arg(long, default_value = "1")]
+    pub pages: u32,
+
+    #[arg(long)]
+    pub width: Option<u32>,
+
+    #[arg(long)]
+    pub dithering: Option<String>,
+
+    #[arg(long, default_value = "0")]
+    pub chunk_rows: u32,
+
+    #[arg(long, default_value = "0")]
+    pub chunk_delay: u64,
+
+    #[arg(long)]
+    pub flow_control: bool,
+
+    #[arg(long, conflicts_with_all = ["server", "pdf"])]
+    pub render: Option<String>,
+
+    #[arg(long, requires = "render")]
+    pub render_text: bool,
+
+    #[arg(long, requires = "render")]
+    pub now: Option<DateTime<FixedOffset>>,
+
+    #[arg(long, global = true, conflicts_with = "server")]
+    pub output: Option<String>,
+
     #[arg(long)]
     pub api: bool,
 
     #[arg(long, default_value = "3000")]
     pub api_port: u16,
+
+    #[arg(long, requires = "api")]
+    pub api_bind: Vec<String>,
+
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+
+    #[arg(long, global = true)]
+    pub server: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands for modes other than printing a single task.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Run a pomodoro timer that prints a slip at every phase transition
+    Pomodoro(PomodoroArgs),
+    /// Print a random quote from the bundled collection or a fortune file
+    Fortune(FortuneArgs),
+    /// Print today's weather forecast for a location
+    Weather(WeatherArgs),
+    /// Print a sudoku or word search puzzle
+    Puzzle(PuzzleArgs),
+    /// Print commit metadata from a git post-commit or post-receive hook
+    GitHook(GitHookArgs),
+    /// Join a Matrix room and print incoming messages
+    Matrix(MatrixArgs),
+    /// Listen for syslog messages and print those passing the configured filters
+    Syslog(SyslogArgs),
+    /// Follow the systemd journal and print matching entries as they appear
+    Journal(JournalArgs),
+    /// Print the matching lines of a file, optionally following it like tail -f
+    Tail(TailArgs),
+    /// Compose and print ad-hoc slips in an interactive terminal screen
+    Compose(ComposeArgs),
+    /// List, cancel or pause the jobs of a running API server
+    Queue(QueueArgs),
+    /// Print every text or image file dropped into a directory
+    Watch(WatchArgs),
+    /// Send printer commands captured with --output to a printer
+    Replay(ReplayArgs),
 }
 
 /// Runs a CLI print job with the provided arguments.
 ///
 /// This function processes command line arguments to create and execute a print task.
-/// If no message is provided via arguments, it reads from stdin. The title defaults
-/// to "INCOMMING MESSAGE" if not specified.
+/// If no message is provided via arguments, it reads from stdin, or from the system
+/// clipboard with `--clipboard`. With `--stream`, every line (or paragraph with
+/// `--paragraphs`) of stdin is printed as its own slip as soon as it arrives. Without a
+/// title, the slip is headed "NOTE" in the configured language. ANSI colors and attributes
+/// in the message are printed as bold, underlined or inverted text unless `--strip-ansi`
+/// is given.
+/// With `--server`, the task is submitted to a remote taskprinter API server, which
+/// queues, spools and prints it. With `--render`, the slip is rendered to a file instead
+/// of printed, see [`render`]. With `--output`, the commands are written to a file as
+/// they would be sent to the printer, see [`set_output`](crate::printer::set_output).
 ///
 /// # Arguments
 ///
@@ -63,35 +223,253 @@ pub struct Args {
 /// run_cli_print(args).expect("Print failed");
 /// ```
 pub fn run_cli_print(args: Args) -> EscposResult<()> {
-    let title = args.title;
-    let message = match args.message {
+    if let Some(path) = &args.pdf {
+        if args.server.is_some() {
+            return Err(PrinterError::Input(
+                "--pdf cannot be printed via --server".to_string(),
+            ));
+        }
+        return run_pdf_print(path, &args);
+    }
+    if args.stream {
+        return run_stream(&args);
+    }
+
+    let message = match args.message.clone() {
         Some(msg) => msg,
         None => {
-            let mut buffer = String::new();
-            io::stdin()
-                .read_to_string(&mut buffer)
-                .map_err(|e| {
-                    error!("Error reading from stdin: {}", e);
-                    std::process::exit(1);
-                })
-                .unwrap();
-            buffer.trim().to_string()
+            let text = if args.clipboard {
+                read_clipboard()?
+            } else {
+                let mut buffer = String::new();
+                io::stdin()
+                    .read_to_string(&mut buffer)
+                    .map_err(|e| {
+                        error!("Error reading from stdin: {}", e);
+                        std::process::exit(1);
+                    })
+                    .unwrap();
+                buffer
+            };
+            if matches!(
+                RenderMode::parse(args.mode.as_deref()),
+                Ok(RenderMode::Code)
+            ) {
+                // Keep the indentation of the first line of code
+                text.trim_end().trim_start_matches(['\r', '\n']).to_string()
+            } else {
+                text.trim().to_string()
+            }
+        }
+    };
+    print(&args, message)
+}
+
+/// Prints every line, or every blank-line-delimited paragraph, of stdin as it arrives.
+///
+/// Stdin is read until EOF, but each slip is printed as soon as its line or paragraph
+/// is complete, so the milestones of a long-running process can be piped to paper. A
+/// failed slip is logged and does not stop the stream.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments, applied to every slip
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - An error if stdin cannot be read or any slip failed to print
+fn run_stream(args: &Args) -> EscposResult<()> {
+    let mut failed = 0;
+    let mut print_slip = |text: &str| {
+        if text.trim().is_empty() {
+            return;
+        }
+        if let Err(e) = print(args, text.trim().to_string()) {
+            error!("Failed to print streamed slip: {:?}", e);
+            failed += 1;
+        }
+    };
+
+    let mut paragraph = String::new();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| PrinterError::Io(format!("cannot read stdin: {}", e)))?;
+        if !args.paragraphs {
+            print_slip(&line);
+        } else if line.trim().is_empty() {
+            print_slip(&paragraph);
+            paragraph.clear();
+        } else {
+            paragraph.push_str(&line);
+            paragraph.push('\n');
         }
+    }
+    print_slip(&paragraph);
+
+    match failed {
+        0 => Ok(()),
+        failed => Err(PrinterError::Io(format!(
+            "{} streamed slips failed to print",
+            failed
+        ))),
+    }
+}
+
+/// Prints a message with the task settings of the command line arguments, or submits it
+/// to the API server given with `--server`.
+fn print(args: &Args, message: String) -> EscposResult<()> {
+    let message = if args.strip_ansi {
+        ansi::strip(&message)
+    } else {
+        message
     };
 
     let task: PrintTask = PrintTask {
-        title: title.clone(),
-        message: message.clone(),
-        date: args.date,
+        title: args.title.clone(),
+        message,
+        date: args.date.clone(),
         encode: args.encode,
-        address: args.address,
+        address: args.address.clone(),
         port: args.port,
-        codepage: args.codepage,
+        codepage: args.codepage.clone(),
+        barcode: args.barcode.clone(),
+        symbology: args.symbology.clone(),
+        split_items: Some(args.split_items),
+        media: args.media.clone(),
+        label_length: args.label_length,
+        link: args.link.clone(),
+        footer_barcode: args.footer_barcode.clone(),
+        mode: args.mode.clone(),
+        hyphenate: args.hyphenate.clone(),
+        normalize: Some(!args.no_normalize),
+        squeeze_blank: Some(args.squeeze_blank),
+        trim_trailing: Some(args.trim_trailing),
+        dedent: Some(args.dedent),
+        header: None,
+        from: args.from.clone(),
     };
 
-    if task.encode == Some(true) {
-        print_qr_code(task)
+    if let Some(path) = &args.render {
+        return render(args, &task, path);
+    }
+
+    if let Some(server) = &args.server {
+        return client::submit(&client::base_url(Some(server)), &task);
+    }
+
+    if task.barcode.is_some() {
+        print_barcode(task)
+    } else if task.encode == Some(true) {
+        print_code_2d(task)
     } else {
         print_task(task)
     }
 }
+
+/// Writes the ESC/POS commands of a task, or its frame as text with `--render-text`, to
+/// a file or stdout instead of printing it.
+///
+/// The date of slips without `--date` is taken from `--now`, so a fixed time renders the
+/// same output every time, e.g. for snapshots of a layout. Placeholders, content
+/// filters, plugins and hooks are not applied, see [`render_task`].
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments, for `--render-text` and `--now`
+/// * `task` - The task to render
+/// * `path` - The output file, or `-` for stdout
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once written, an input error for barcode and 2D code
+///   tasks, or an I/O error if the output cannot be written
+fn render(args: &Args, task: &PrintTask, path: &str) -> EscposResult<()> {
+    if task.barcode.is_some() || task.encode == Some(true) {
+        return Err(PrinterError::Input(
+            "--render does not support barcodes and 2D codes".to_string(),
+        ));
+    }
+    let now = args
+        .now
+        .map_or_else(Local::now, |now| now.with_timezone(&Local));
+    let options = RenderOptions::configured(task);
+    let output = if args.render_text {
+        format!("{}\n", render_text(task, &options, now)).into_bytes()
+    } else {
+        render_task(task, &options, now)?
+    };
+    match path {
+        "-" => io::stdout().write_all(&output),
+        path => fs::write(path, output),
+    }
+    .map_err(|e| PrinterError::Io(format!("cannot write {}: {}", path, e)))
+}
+
+/// Reads the text content of the system clipboard for `--clipboard`.
+///
+/// # Returns
+///
+/// * `EscposResult<String>` - The clipboard text, an input error if the clipboard holds
+///   no text, or an I/O error if the clipboard cannot be accessed
+fn read_clipboard() -> EscposResult<String> {
+    let mut clipboard = arboard::Clipboard::new()
+        .map_err(|e| PrinterError::Io(format!("cannot access the clipboard: {}", e)))?;
+    match clipboard.get_text() {
+        Ok(text) if !text.trim().is_empty() => Ok(text),
+        Ok(_) | Err(arboard::Error::ContentNotAvailable) => Err(PrinterError::Input(
+            "the clipboard contains no text".to_string(),
+        )),
+        Err(e) => Err(PrinterError::Io(format!(
+            "cannot read the clipboard: {}",
+            e
+        ))),
+    }
+}
+
+/// Prints the first pages of a PDF file given with `--pdf`.
+///
+/// The pages are rendered `--width` dots wide (default 384), dithered with `--dithering`
+/// and printed one after another. A `--message` is printed as caption below the last page.
+///
+/// # Arguments
+///
+/// * `path` - Path of the PDF file
+/// * `args` - The parsed command line arguments providing the page count and printer settings
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an error if the file cannot be
+///   read, rendered or printed
+fn run_pdf_print(path: &str, args: &Args) -> EscposResult<()> {
+    let pdf = std::fs::read(path)
+        .map_err(|e| PrinterError::Input(format!("cannot read {}: {}", path, e)))?;
+    let pages = rasterize_pdf(
+        &pdf,
+        args.pages,
+        args.width.unwrap_or(DEFAULT_WIDTH),
+        Dither::parse(args.dithering.as_deref())?,
+    )?;
+
+    print_images(
+        PrintTask {
+            message: args.message.clone().unwrap_or_default(),
+            address: args.address.clone(),
+            port: args.port,
+            codepage: args.codepage.clone(),
+            ..Default::default()
+        },
+        &pages,
+        args.chunking(),
+    )
+}
+
+impl Args {
+    /// Returns how images are sent to the printer, from `--chunk-rows`, `--chunk-delay`
+    /// and `--flow-control`.
+    pub fn chunking(&self) -> Chunking {
+        Chunking {
+            rows: self.chunk_rows,
+            delay: self.chunk_delay,
+            flow_control: self.flow_control,
+        }
+    }
+}
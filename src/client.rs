@@ -0,0 +1,109 @@
+use crate::printer::PrintTask;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+
+/// API server used by the client commands when `--server` is not given.
+const DEFAULT_SERVER: &str = "http://localhost:3000";
+
+/// Returns the base URL of the API server without a trailing slash.
+///
+/// # Arguments
+///
+/// * `server` - The URL given with `--server`, defaults to `http://localhost:3000`
+///
+/// # Returns
+///
+/// * `String` - The base URL to append the endpoint paths to
+pub fn base_url(server: Option<&str>) -> String {
+    server
+        .unwrap_or(DEFAULT_SERVER)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Sends a request to the API server and parses the JSON response.
+///
+/// # Arguments
+///
+/// * `request` - The prepared request
+///
+/// # Returns
+///
+/// * `EscposResult<T>` - The parsed response, an invalid response error for HTTP errors
+///   and unexpected bodies, or an I/O error if the server cannot be reached
+pub fn request<T: DeserializeOwned>(request: ureq::Request) -> EscposResult<T> {
+    parse_response(request.call())
+}
+
+/// Parses the JSON response of a request to the API server, see [`request`].
+pub fn parse_response<T: DeserializeOwned>(
+    response: Result<ureq::Response, ureq::Error>,
+) -> EscposResult<T> {
+    match response {
+        Ok(response) => response
+            .into_json()
+            .map_err(|e| PrinterError::InvalidResponse(format!("invalid server response: {}", e))),
+        Err(ureq::Error::Status(429, response)) => Err(queue_full(&response)),
+        Err(ureq::Error::Status(code, _)) => Err(PrinterError::InvalidResponse(format!(
+            "server responded with HTTP {}",
+            code
+        ))),
+        Err(e) => Err(PrinterError::Io(format!("cannot reach server: {}", e))),
+    }
+}
+
+/// Builds the error of a request rejected with HTTP 429 because the server's queue is
+/// full, including when to retry from the `Retry-After` header.
+fn queue_full(response: &ureq::Response) -> PrinterError {
+    match response.header("Retry-After") {
+        Some(seconds) => PrinterError::Io(format!(
+            "the server's print queue is full, retry in {} seconds",
+            seconds
+        )),
+        None => PrinterError::Io("the server's print queue is full, retry later".to_string()),
+    }
+}
+
+/// Submits a task to the `/print` endpoint of a remote taskprinter server instead of
+/// printing it directly, so the job goes through the server's queue, spool and history.
+///
+/// The call returns once the server printed or spooled the job. Printer address and
+/// port of the task are passed on; without them the server uses its defaults.
+///
+/// # Arguments
+///
+/// * `server` - Base URL of the API server, e.g. `http://printer-host:3000`
+/// * `task` - The task to print
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once the job is printed or spooled, an input error if
+///   the server rejects the task, or an I/O error if it cannot be reached, its queue is
+///   full or printing failed
+pub fn submit(server: &str, task: &PrintTask) -> EscposResult<()> {
+    let url = format!("{}/print", server);
+    let body = serde_json::to_value(task).map_err(|e| PrinterError::Input(e.to_string()))?;
+    match ureq::post(&url).send_json(body) {
+        Ok(response) if response.status() == 202 => {
+            warn!("Printer unreachable, job spooled on {}", server);
+            Ok(())
+        }
+        Ok(_) => {
+            info!("Printed via {}", server);
+            Ok(())
+        }
+        Err(ureq::Error::Status(400 | 422, _)) => Err(PrinterError::Input(
+            "the server rejected the print job".to_string(),
+        )),
+        Err(ureq::Error::Status(429, response)) => Err(queue_full(&response)),
+        Err(ureq::Error::Status(410, _)) => Err(PrinterError::Io(
+            "the job expired or was cancelled on the server".to_string(),
+        )),
+        Err(ureq::Error::Status(code, _)) => Err(PrinterError::Io(format!(
+            "printing via {} failed with HTTP {}",
+            server, code
+        ))),
+        Err(e) => Err(PrinterError::Io(format!("cannot reach server: {}", e))),
+    }
+}
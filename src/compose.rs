@@ -0,0 +1,293 @@
+use crate::cli::Args;
+use crate::client;
+use crate::config::Config;
+use crate::metrics;
+use crate::printer::{PrintTask, preview_frame, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+/// Width of the preview, the printed frame plus the borders of the preview box.
+const PREVIEW_WIDTH: u16 = 26;
+
+/// Options for the interactive compose mode.
+#[derive(ClapArgs)]
+pub struct ComposeArgs {
+    /// Title to start with
+    #[arg(long)]
+    pub title: Option<String>,
+}
+
+/// Input field that has the keyboard focus.
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Title,
+    Message,
+    Printer,
+}
+
+/// State of the compose screen.
+struct Compose<'a> {
+    args: &'a Args,
+    title: String,
+    message: String,
+    printers: Vec<String>,
+    printer: usize,
+    focus: Field,
+    status: String,
+}
+
+/// Runs an interactive terminal screen to compose and print ad-hoc slips.
+///
+/// The screen has fields for title and message, a selector for the printers of the
+/// `[compose]` configuration section and a live preview of the framed slip, showing
+/// exactly where lines are cut on paper. The printer given with `--address` and
+/// `--port` is offered first. `Ctrl+P` prints the slip and clears the message, so
+/// several slips can be printed in a row. With `--server`, slips are submitted to the
+/// API server instead of the selected printer.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `compose` - The compose mode options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - An error if the configuration cannot be loaded or the
+///   terminal cannot be used
+pub fn run_compose(args: &Args, compose: &ComposeArgs) -> EscposResult<()> {
+    let config =
+        Config::load(args.config.as_deref()).map_err(|e| PrinterError::Input(e.to_string()))?;
+    let mut printers = vec![metrics::printer_label(args.address.as_deref(), args.port)];
+    for printer in config
+        .compose
+        .map(|compose| compose.printers)
+        .unwrap_or_default()
+    {
+        let (address, port) = metrics::parse_printer(&printer);
+        let printer = metrics::printer_label(Some(&address), port);
+        if !printers.contains(&printer) {
+            printers.push(printer);
+        }
+    }
+
+    let mut state = Compose {
+        args,
+        title: compose
+            .title
+            .clone()
+            .or(args.title.clone())
+            .unwrap_or_default(),
+        message: String::new(),
+        printers,
+        printer: 0,
+        focus: Field::Message,
+        status: String::new(),
+    };
+    let mut terminal = ratatui::try_init()
+        .map_err(|e| PrinterError::Io(format!("cannot open the terminal: {}", e)))?;
+    let result = state.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Compose<'_> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> EscposResult<()> {
+        let terminal_error = |e: std::io::Error| PrinterError::Io(format!("terminal error: {}", e));
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .map_err(terminal_error)?;
+            let Event::Key(key) = event::read().map_err(terminal_error)? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            let control = key.modifiers.contains(KeyModifiers::CONTROL);
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c' | 'q') if control => return Ok(()),
+                KeyCode::Char('p') if control => {
+                    self.status = "Printing...".to_string();
+                    terminal
+                        .draw(|frame| self.draw(frame))
+                        .map_err(terminal_error)?;
+                    self.print();
+                }
+                KeyCode::Tab => self.focus = self.next_field(),
+                KeyCode::BackTab => {
+                    self.focus = self.next_field();
+                    self.focus = self.next_field();
+                }
+                _ => self.edit(key),
+            }
+        }
+    }
+
+    fn next_field(&self) -> Field {
+        match self.focus {
+            Field::Title => Field::Message,
+            Field::Message => Field::Printer,
+            Field::Printer => Field::Title,
+        }
+    }
+
+    /// Applies a key press to the focused field.
+    fn edit(&mut self, key: KeyEvent) {
+        let text = match self.focus {
+            Field::Title => &mut self.title,
+            Field::Message => &mut self.message,
+            Field::Printer => {
+                let count = self.printers.len();
+                match key.code {
+                    KeyCode::Left | KeyCode::Up => {
+                        self.printer = (self.printer + count - 1) % count
+                    }
+                    KeyCode::Right | KeyCode::Down | KeyCode::Char(' ') => {
+                        self.printer = (self.printer + 1) % count
+                    }
+                    _ => {}
+                }
+                return;
+            }
+        };
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter if self.focus == Field::Message => text.push('\n'),
+            KeyCode::Enter => self.focus = Field::Message,
+            _ => {}
+        }
+    }
+
+    /// Builds the task of the current slip.
+    fn task(&self) -> PrintTask {
+        let (address, port) = metrics::parse_printer(&self.printers[self.printer]);
+        PrintTask {
+            title: Some(self.title.trim().to_string()).filter(|title| !title.is_empty()),
+            message: self.message.trim().to_string(),
+            address: Some(address),
+            port,
+            codepage: self.args.codepage.clone(),
+            hyphenate: self.args.hyphenate.clone(),
+            normalize: Some(!self.args.no_normalize),
+            squeeze_blank: Some(self.args.squeeze_blank),
+            trim_trailing: Some(self.args.trim_trailing),
+            dedent: Some(self.args.dedent),
+            from: self.args.from.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn print(&mut self) {
+        let task = self.task();
+        if task.message.is_empty() {
+            self.status = "Nothing to print, the message is empty".to_string();
+            return;
+        }
+        let printer = self.printers[self.printer].clone();
+        let result = match &self.args.server {
+            Some(server) => client::submit(&client::base_url(Some(server)), &task),
+            None => print_task(task),
+        };
+        self.status = match result {
+            Ok(()) => {
+                self.message.clear();
+                match &self.args.server {
+                    Some(server) => format!("Submitted to {}", server),
+                    None => format!("Printed on {}", printer),
+                }
+            }
+            Err(e) => format!("Failed to print: {:?}", e),
+        };
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [fields, preview] =
+            Layout::horizontal([Constraint::Min(20), Constraint::Length(PREVIEW_WIDTH)])
+                .areas(main);
+        let [title, message, printer] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(3),
+            Constraint::Length(3),
+        ])
+        .areas(fields);
+
+        let block = |name: &str, field: Field| {
+            let block = Block::bordered().title(format!(" {} ", name));
+            if self.focus == field {
+                block.border_style(Style::new().fg(Color::Yellow))
+            } else {
+                block
+            }
+        };
+
+        frame.render_widget(
+            Paragraph::new(self.title.as_str()).block(block("Title", Field::Title)),
+            title,
+        );
+
+        // Keep the end of the message, where the cursor is, in view.
+        let lines = self.message.split('\n').count() as u16;
+        let visible = message.height.saturating_sub(2);
+        let scroll = lines.saturating_sub(visible);
+        frame.render_widget(
+            Paragraph::new(self.message.as_str())
+                .scroll((scroll, 0))
+                .block(block("Message", Field::Message)),
+            message,
+        );
+
+        let selector = if self.printers.len() > 1 {
+            format!("< {} >", self.printers[self.printer])
+        } else {
+            self.printers[self.printer].clone()
+        };
+        frame.render_widget(
+            Paragraph::new(selector).block(block("Printer", Field::Printer)),
+            printer,
+        );
+
+        let slip = preview_frame(&self.task());
+        frame.render_widget(
+            Paragraph::new(slip.lines().map(Line::from).collect::<Vec<_>>()).block(
+                Block::bordered().title(format!(" Preview, {} lines ", slip.lines().count())),
+            ),
+            preview,
+        );
+
+        let help = "Tab next field | Ctrl+P print | Esc quit";
+        let status = if self.status.is_empty() {
+            help.to_string()
+        } else {
+            format!("{} | {}", self.status, help)
+        };
+        frame.render_widget(Paragraph::new(status).dim(), footer);
+
+        match self.focus {
+            Field::Title => set_cursor(frame, title, 0, &self.title),
+            Field::Message => {
+                let last = self.message.rsplit('\n').next().unwrap_or_default();
+                set_cursor(frame, message, lines.min(visible).saturating_sub(1), last);
+            }
+            Field::Printer => {}
+        }
+    }
+}
+
+/// Places the terminal cursor behind the text of a line inside a bordered field.
+fn set_cursor(frame: &mut Frame, area: Rect, line: u16, text: &str) {
+    let column = (text.chars().count() as u16).min(area.width.saturating_sub(3));
+    frame.set_cursor_position((area.x + 1 + column, area.y + 1 + line));
+}
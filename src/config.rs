@@ -0,0 +1,883 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+
+/// Application configuration loaded from a TOML file.
+///
+/// Every section is optional, so an empty file (or no file at all) yields a working
+/// configuration with all integrations disabled.
+///
+/// # Examples
+///
+/// ```toml
+/// language = "de"
+/// plugins = ["/etc/taskprinter/route.lua"]
+///
+/// [labels]
+/// note = "ZETTEL"
+///
+/// [jira]
+/// secret = "change-me"
+/// assignee = "5b10a2844c20165700ede21g"
+///
+/// [gitea]
+/// secret = "change-me"
+/// actions = ["opened", "merged"]
+///
+/// [webhooks.grafana]
+/// secret = "change-me"
+/// title = "$.status"
+/// message = "{$.title}\n{$.message}"
+///
+/// [templates.order]
+/// variables = ["table", "items"]
+/// title = "Table {table}"
+/// message = "{items}"
+///
+/// [spool]
+/// retry_interval = 30
+/// max_age = 86400
+/// max_count = 100
+///
+/// [[filters]]
+/// type = "drop"
+/// pattern = "(?i)healthcheck"
+///
+/// [[filters]]
+/// type = "replace"
+/// pattern = "password=\\S+"
+/// replacement = "password=***"
+///
+/// [[filters]]
+/// type = "max_length"
+/// length = 500
+///
+/// [hooks]
+/// before = "curl -s 'http://plug.local/relay/0?turn=on' && sleep 5"
+/// after = "curl -s 'http://plug.local/relay/0?turn=off'"
+///
+/// [defaults]
+/// address = "192.168.1.50"
+/// codepage = "ISO8859_15"
+///
+/// [printers."kitchen:9100"]
+/// model = "TM-T20"
+/// feed_before_cut = 4
+/// feed_after_cut = 1
+/// roll_length = 80
+///
+/// [printers."kitchen:9100".header]
+/// reverse = false
+/// separator = "="
+///
+/// [models."pocket-58"]
+/// codepages = ["PC437"]
+/// font_a_columns = 32
+/// cutter = "none"
+///
+/// [queue]
+/// max_depth = 50
+///
+/// [history]
+/// max_count = 500
+/// max_age = 604800
+///
+/// [verify]
+/// delay = 2
+/// retransmit = 1
+///
+/// [summary]
+/// weekday = "fri"
+/// time = "16:30"
+///
+/// [[schedule]]
+/// time = "07:30"
+/// weekdays = ["mon", "tue", "wed", "thu", "fri"]
+/// source = "digest"
+/// message = "Standup 9:30"
+/// latitude = 52.52
+/// longitude = 13.41
+///
+/// [api]
+/// bind = ["127.0.0.1", "::1"]
+///
+/// [grpc]
+/// port = 50051
+///
+/// [graphql]
+/// playground = true
+///
+/// [mqtt]
+/// host = "homeassistant.local"
+/// printers = ["taskbob:9100"]
+///
+/// [dbus]
+/// name = "org.taskprinter.Print"
+///
+/// [syslog]
+/// port = 514
+/// severity = "crit"
+/// facilities = ["auth", "daemon"]
+/// patterns = ["(?i)disk|raid"]
+///
+/// [compose]
+/// printers = ["taskbob:9100", "kitchen:9100"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Jira webhook integration.
+    pub jira: Option<JiraConfig>,
+    /// GitLab webhook integration.
+    pub gitlab: Option<ForgeConfig>,
+    /// Gitea webhook integration.
+    pub gitea: Option<ForgeConfig>,
+    /// Discord interactions endpoint.
+    pub discord: Option<DiscordConfig>,
+    /// Matrix bot mode.
+    pub matrix: Option<MatrixConfig>,
+    /// Syslog listener mode.
+    pub syslog: Option<SyslogConfig>,
+    /// Interactive compose mode.
+    pub compose: Option<ComposeConfig>,
+    /// Generic signed webhooks, keyed by the name used in the `/webhook/{name}` URL.
+    pub webhooks: HashMap<String, GenericWebhookConfig>,
+    /// Stored print templates, keyed by the name used in the `/print/{template}` URL.
+    pub templates: HashMap<String, TemplateConfig>,
+    /// Offline spooling of print jobs while the printer is unreachable.
+    pub spool: Option<SpoolConfig>,
+    /// Verification of queued jobs with the error status of the printer.
+    pub verify: Option<VerifyConfig>,
+    /// Weekly usage summary slip printed by the API server.
+    pub summary: Option<SummaryConfig>,
+    /// Slips printed by the API server at fixed times, e.g. a quote every morning.
+    pub schedule: Vec<ScheduleConfig>,
+    /// Limits of the print queue.
+    pub queue: Option<QueueConfig>,
+    /// Retention of finished jobs in the job history.
+    pub history: Option<HistoryConfig>,
+    /// Content filters applied in order to every job before rendering.
+    pub filters: Vec<FilterConfig>,
+    /// Lua scripts whose `on_job` hooks transform or reject every job, called in order.
+    pub plugins: Vec<String>,
+    /// Shell commands run before and after every job.
+    pub hooks: Option<HooksConfig>,
+    /// Printer, codepage and title used when a job does not name them.
+    pub defaults: Option<DefaultsConfig>,
+    /// Per-printer settings, keyed by `host:port` or `host` for any port.
+    pub printers: HashMap<String, PrinterProfile>,
+    /// Capabilities of printer models in addition to the bundled ones, keyed by model.
+    pub models: HashMap<String, Capabilities>,
+    /// Listeners of the HTTP API.
+    pub api: Option<ApiConfig>,
+    /// gRPC service started next to the HTTP API.
+    pub grpc: Option<GrpcConfig>,
+    /// GraphQL endpoint of the HTTP API.
+    pub graphql: Option<GraphqlConfig>,
+    /// Publishing of job and printer status events to an MQTT broker.
+    pub mqtt: Option<MqttConfig>,
+    /// D-Bus service on the session bus.
+    pub dbus: Option<DbusConfig>,
+    /// Language of the built-in labels printed on slips (`en`, `de`, `fr`, `es`).
+    pub language: Option<String>,
+    /// Texts replacing built-in labels, keyed by label name such as `note` or `from`.
+    pub labels: HashMap<String, String>,
+}
+
+/// Settings for the Jira webhook receiver.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct JiraConfig {
+    /// Shared secret configured on the Jira webhook.
+    pub secret: String,
+    /// Account ID (Jira Cloud) or user name (Jira Server) whose assignments are printed.
+    pub assignee: Option<String>,
+}
+
+/// Settings for a forge (GitLab, Gitea) webhook receiver.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ForgeConfig {
+    /// Secret token configured on the webhook.
+    pub secret: String,
+    /// Issue and merge request actions that are printed.
+    pub actions: Vec<String>,
+}
+
+impl Default for ForgeConfig {
+    fn default() -> Self {
+        ForgeConfig {
+            secret: String::new(),
+            actions: vec![
+                "opened".to_string(),
+                "reopened".to_string(),
+                "merged".to_string(),
+            ],
+        }
+    }
+}
+
+/// Settings for the Discord interactions endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DiscordConfig {
+    /// Public key of the Discord application, used to verify interaction signatures.
+    pub public_key: String,
+    /// Role IDs allowed to print. An empty list allows every member.
+    pub allowed_roles: Vec<String>,
+}
+
+/// Settings for a generic HMAC signed webhook.
+///
+/// The `title`, `message`, `date` and `link` mappings are either a single JSONPath
+/// expression such as `$.alerts[0].labels.alertname`, or a template with `{$...}`
+/// placeholders such as `"{$.status}: {$.title}"`. Text without placeholders is
+/// printed as is.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GenericWebhookConfig {
+    /// Shared secret used to sign the request body with HMAC-SHA256.
+    pub secret: String,
+    /// Header carrying the hex encoded signature, optionally prefixed with `sha256=`.
+    pub header: String,
+    /// Mapping of the task title.
+    pub title: Option<String>,
+    /// Mapping of the task message.
+    pub message: String,
+    /// Mapping of the task date.
+    pub date: Option<String>,
+    /// Mapping of the URL printed as QR code.
+    pub link: Option<String>,
+}
+
+impl Default for GenericWebhookConfig {
+    fn default() -> Self {
+        GenericWebhookConfig {
+            secret: String::new(),
+            header: "X-Hub-Signature-256".to_string(),
+            title: None,
+            message: "$.message".to_string(),
+            date: None,
+            link: None,
+        }
+    }
+}
+
+/// Settings for offline spooling.
+///
+/// With a `[spool]` section, jobs posted to `/print` while the printer is unreachable
+/// are written to disk instead of failing, and printed in order once it is back.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SpoolConfig {
+    /// Directory of the spooled jobs. Defaults to `spool` in the state directory.
+    pub dir: Option<String>,
+    /// Seconds between attempts to print the spooled jobs.
+    pub retry_interval: u64,
+    /// Seconds after which a spooled job is dropped as expired.
+    pub max_age: u64,
+    /// Maximum number of spooled jobs. Further jobs fail while the spool is full.
+    pub max_count: usize,
+    /// Passphrase the spooled jobs are encrypted with. Falls back to the
+    /// `TASKPRINTER_SPOOL_KEY` environment variable, without either jobs are stored as
+    /// plain JSON. Only the spool is encrypted, the job history is kept in memory.
+    pub key: Option<String>,
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        SpoolConfig {
+            dir: None,
+            retry_interval: 30,
+            max_age: 24 * 60 * 60,
+            max_count: 100,
+            key: None,
+        }
+    }
+}
+
+/// Limits of the print queue.
+///
+/// With a `max_depth`, new jobs are rejected while that many jobs are waiting, instead of
+/// queueing work the printer cannot keep up with.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct QueueConfig {
+    /// Maximum number of waiting jobs, 0 for no limit.
+    pub max_depth: usize,
+    /// Seconds after which clients of a full queue are asked to try again.
+    pub retry_after: u64,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        QueueConfig {
+            max_depth: 0,
+            retry_after: 30,
+        }
+    }
+}
+
+/// Retention of finished jobs in the job history.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Maximum number of finished jobs kept, the oldest are forgotten first.
+    pub max_count: usize,
+    /// Seconds after which finished jobs are purged, 0 keeps them until `max_count` is
+    /// reached.
+    pub max_age: u64,
+    /// Seconds between purges of jobs older than `max_age`.
+    pub purge_interval: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            max_count: 100,
+            max_age: 0,
+            purge_interval: 60,
+        }
+    }
+}
+
+/// Schedule of the weekly usage summary slip.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Day of the week the summary is printed on, e.g. `mon` or `Friday`.
+    pub weekday: String,
+    /// Local time the summary is printed at, as `HH:MM`.
+    pub time: String,
+    /// Number of senders listed with their job counts.
+    pub top: usize,
+    /// Network address of the printer. Defaults to the default printer.
+    pub address: Option<String>,
+    /// Network port of the printer.
+    pub port: Option<u16>,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        SummaryConfig {
+            weekday: "mon".to_string(),
+            time: "09:00".to_string(),
+            top: 3,
+            address: None,
+            port: None,
+        }
+    }
+}
+
+/// A slip printed by the API server at a fixed time of the day.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    /// Local time the slip is printed at, as `HH:MM`.
+    pub time: String,
+    /// Days of the week the slip is printed on, e.g. `mon` or `Friday`. Empty for every day.
+    pub weekdays: Vec<String>,
+    /// What the slip shows.
+    pub source: ScheduleSource,
+    /// Title of the slip. Defaults to the label of the source, e.g. `FORTUNE`.
+    pub title: Option<String>,
+    /// Text of `message` slips. Placeholders such as `{weekday}` are expanded.
+    pub message: Option<String>,
+    /// Fortune file of `fortune` and `digest` slips. Defaults to the bundled quotes.
+    pub file: Option<String>,
+    /// Latitude of the forecast location of `weather` and `digest` slips.
+    pub latitude: Option<f64>,
+    /// Longitude of the forecast location of `weather` and `digest` slips.
+    pub longitude: Option<f64>,
+    /// Length of a work phase of `pomodoro` sessions in minutes.
+    pub work: u64,
+    /// Length of a break of `pomodoro` sessions in minutes.
+    #[serde(rename = "break")]
+    pub break_minutes: u64,
+    /// Number of work phases of `pomodoro` sessions.
+    pub cycles: u32,
+    /// Network address of the printer. Defaults to the default printer.
+    pub address: Option<String>,
+    /// Network port of the printer.
+    pub port: Option<u16>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        ScheduleConfig {
+            time: "08:00".to_string(),
+            weekdays: Vec::new(),
+            source: ScheduleSource::Message,
+            title: None,
+            message: None,
+            file: None,
+            latitude: None,
+            longitude: None,
+            work: 25,
+            break_minutes: 5,
+            cycles: 4,
+            address: None,
+            port: None,
+        }
+    }
+}
+
+/// Content of a scheduled slip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleSource {
+    /// The configured message.
+    Message,
+    /// A random quote, see `taskprinter fortune`.
+    Fortune,
+    /// Today's forecast, see `taskprinter weather`.
+    Weather,
+    /// The message as agenda, a random quote and today's forecast on one slip.
+    Digest,
+    /// A pomodoro session with a slip per phase, see `taskprinter pomodoro`.
+    Pomodoro,
+}
+
+/// Settings for verifying queued jobs with the error status of the printer.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VerifyConfig {
+    /// Seconds to wait after sending a job before querying the printer, so the slip is
+    /// printed and cut.
+    pub delay: u64,
+    /// How often a job is sent again after the printer reported an error.
+    pub retransmit: u32,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        VerifyConfig {
+            delay: 2,
+            retransmit: 0,
+        }
+    }
+}
+
+/// A content filter applied to every job before rendering.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FilterConfig {
+    /// Replaces every match of a regular expression in the title and message.
+    Replace {
+        /// Regular expression to search for.
+        pattern: String,
+        /// Replacement text, `$1` or `${name}` insert capture groups.
+        #[serde(default)]
+        replacement: String,
+    },
+    /// Cuts the message to a number of characters.
+    MaxLength {
+        /// Maximum number of characters of the message.
+        length: usize,
+    },
+    /// Drops jobs whose title or message matches a regular expression.
+    Drop {
+        /// Regular expression to search for.
+        pattern: String,
+    },
+    /// Converts the title to upper case.
+    UppercaseTitle,
+    /// Masks API tokens, bearer credentials, passwords and card numbers in the title and
+    /// message, also before jobs are stored in the queue, history or spool.
+    RedactSecrets,
+}
+
+/// Shell commands run around every job, e.g. to switch a smart plug powering the printer.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Command run before the job is sent. If it fails, the job is not printed.
+    pub before: Option<String>,
+    /// Command run after the job was printed or failed.
+    pub after: Option<String>,
+}
+
+/// Values used for jobs that do not name a printer, codepage or title.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DefaultsConfig {
+    /// Network address of the printer.
+    pub address: String,
+    /// Network port of the printer.
+    pub port: u16,
+    /// Character encoding codepage, e.g. PC850 or ISO8859_15.
+    pub codepage: String,
+    /// Title of slips without a title. Defaults to the `note` label.
+    pub title: Option<String>,
+}
+
+impl Default for DefaultsConfig {
+    fn default() -> Self {
+        DefaultsConfig {
+            address: "taskbob".to_string(),
+            port: 9100,
+            codepage: "PC850".to_string(),
+            title: None,
+        }
+    }
+}
+
+/// Settings for a single printer mechanism.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct PrinterProfile {
+    /// Printer model, selecting the capabilities slips are rendered for. Bundled models
+    /// or those defined in `[models]`; without a model, a generic 80mm printer is assumed.
+    pub model: Option<String>,
+    /// Lines fed before cutting, so the cutter, which sits above the print head, does
+    /// not cut through the last printed line.
+    pub feed_before_cut: u8,
+    /// Lines fed after cutting, for mechanisms that leave the start of the next slip
+    /// inside the cutter.
+    pub feed_after_cut: u8,
+    /// Styling of the title row of framed slips printed on this printer.
+    pub header: Option<HeaderStyle>,
+    /// Length of a full paper roll in meters, for the remaining paper estimate.
+    pub roll_length: Option<f64>,
+}
+
+impl Default for PrinterProfile {
+    fn default() -> Self {
+        PrinterProfile {
+            model: None,
+            feed_before_cut: 1,
+            feed_after_cut: 0,
+            header: None,
+            roll_length: None,
+        }
+    }
+}
+
+/// Styling of the title row at the top of framed slips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderStyle {
+    /// Whether the title row is printed white on black.
+    pub reverse: bool,
+    /// Whether the title row is printed in bold.
+    pub bold: bool,
+    /// Whether the title row is printed in double height like the message.
+    pub double_height: bool,
+    /// Character of the rule between the title row and the message.
+    pub separator: char,
+}
+
+impl Default for HeaderStyle {
+    fn default() -> Self {
+        HeaderStyle {
+            reverse: false,
+            bold: false,
+            double_height: true,
+            separator: '─',
+        }
+    }
+}
+
+/// Features of a printer model, so slips are rendered with the commands it supports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capabilities {
+    /// Supported codepages, the first is used for tasks with an unsupported codepage.
+    pub codepages: Vec<String>,
+    /// Characters per line in Font A, the font of frames and badges.
+    pub font_a_columns: usize,
+    /// Characters per line in the small Font B, the font of code blocks.
+    pub font_b_columns: usize,
+    /// Whether QR, PDF417 and DataMatrix codes can be printed.
+    pub codes_2d: bool,
+    /// Whether one-dimensional barcodes can be printed.
+    pub barcodes: bool,
+    /// Cuts the printer can perform.
+    pub cutter: Cutter,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities {
+            codepages: ["PC850", "ISO8859_15", "WPC1252", "PC437", "ISO8859_7"]
+                .map(String::from)
+                .to_vec(),
+            font_a_columns: 48,
+            font_b_columns: 64,
+            codes_2d: true,
+            barcodes: true,
+            cutter: Cutter::Partial,
+        }
+    }
+}
+
+/// Cutter of a printer model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Cutter {
+    /// No cutter, slips are torn off.
+    None,
+    /// Full cuts only.
+    Full,
+    /// Full and partial cuts.
+    Partial,
+}
+
+/// Settings for the HTTP API server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Addresses to listen on, one listener each, all on the `--api-port`. IPv6
+    /// addresses are given without brackets, `::` usually also accepts IPv4 connections.
+    pub bind: Vec<String>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            bind: vec!["0.0.0.0".to_string()],
+        }
+    }
+}
+
+/// Settings for the gRPC service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    /// Port the gRPC service listens on.
+    pub port: u16,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        GrpcConfig { port: 50051 }
+    }
+}
+
+/// Settings for the GraphQL endpoint.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GraphqlConfig {
+    /// Whether `GET /graphql` serves the GraphQL Playground to explore the schema.
+    pub playground: bool,
+}
+
+impl Default for GraphqlConfig {
+    fn default() -> Self {
+        GraphqlConfig { playground: true }
+    }
+}
+
+/// Settings for publishing job and printer status events to an MQTT broker.
+///
+/// The status and paper topics may contain a `{printer}` placeholder, replaced by the
+/// printer as `host:port`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// Host name of the MQTT broker.
+    pub host: String,
+    /// Port of the MQTT broker.
+    pub port: u16,
+    /// Client ID used to connect to the broker.
+    pub client_id: String,
+    /// User name, if the broker requires authentication.
+    pub username: Option<String>,
+    /// Password of the user.
+    pub password: Option<String>,
+    /// Topic receiving every job status change as JSON.
+    pub job_topic: String,
+    /// Retained topic with the printer status, `online` or `offline`.
+    pub status_topic: String,
+    /// Retained topic with the paper supply, `ok`, `near_end` or `out`.
+    pub paper_topic: String,
+    /// Printers whose status is checked, as `host:port`. Defaults to the default printer.
+    pub printers: Vec<String>,
+    /// Seconds between printer status checks.
+    pub status_interval: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        MqttConfig {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: "taskprinter".to_string(),
+            username: None,
+            password: None,
+            job_topic: "taskprinter/jobs".to_string(),
+            status_topic: "taskprinter/{printer}/status".to_string(),
+            paper_topic: "taskprinter/{printer}/paper".to_string(),
+            printers: Vec::new(),
+            status_interval: 60,
+        }
+    }
+}
+
+/// Settings for the D-Bus service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DbusConfig {
+    /// Well-known name requested on the session bus.
+    pub name: String,
+}
+
+impl Default for DbusConfig {
+    fn default() -> Self {
+        DbusConfig {
+            name: "org.taskprinter.Print".to_string(),
+        }
+    }
+}
+
+/// A stored print template.
+///
+/// The `title`, `message`, `date` and `link` texts may use every declared variable as
+/// `{name}` placeholder. Print time placeholders such as `{weekday}` are expanded
+/// afterwards, so they can be used as well.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TemplateConfig {
+    /// Names of the variables a request must provide.
+    pub variables: Vec<String>,
+    /// Values of optional variables, used when a request omits them.
+    pub defaults: HashMap<String, String>,
+    /// Template of the task title.
+    pub title: Option<String>,
+    /// Template of the task message.
+    pub message: String,
+    /// Template of the task date.
+    pub date: Option<String>,
+    /// Template of the URL printed as QR code.
+    pub link: Option<String>,
+    /// Rendering mode, `frame`, `code` or `badge`.
+    pub mode: Option<String>,
+    /// Network address of the printer used for this template.
+    pub address: Option<String>,
+    /// Network port of the printer used for this template.
+    pub port: Option<u16>,
+    /// Character encoding codepage for the printer.
+    pub codepage: Option<String>,
+    /// Styling of the title row, overriding the printer profile.
+    pub header: Option<HeaderStyle>,
+}
+
+/// Settings for the Matrix bot mode.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MatrixConfig {
+    /// Base URL of the homeserver, e.g. `https://matrix.example.org`.
+    pub homeserver: String,
+    /// Access token of the bot account.
+    pub access_token: String,
+    /// Room ID or alias to join and print from.
+    pub room: String,
+    /// Only print messages that mention the bot.
+    pub mentions_only: bool,
+}
+
+/// Settings for the syslog listener mode.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SyslogConfig {
+    /// Address the listeners bind to.
+    pub bind: String,
+    /// Port of the UDP and TCP listeners.
+    pub port: u16,
+    /// Whether to receive messages via UDP.
+    pub udp: bool,
+    /// Whether to receive messages via TCP.
+    pub tcp: bool,
+    /// Least severe level that is printed, e.g. `err` also prints `crit`, `alert` and
+    /// `emerg` messages.
+    pub severity: String,
+    /// Facilities that are printed, e.g. `auth` or `local0`. An empty list allows all.
+    pub facilities: Vec<String>,
+    /// Regular expressions of which a message must match at least one. An empty list
+    /// allows all.
+    pub patterns: Vec<String>,
+    /// Regular expressions of messages that are never printed.
+    pub exclude: Vec<String>,
+    /// Maximum number of slips per minute, further messages are dropped. 0 disables
+    /// the limit.
+    pub max_per_minute: u32,
+}
+
+impl Default for SyslogConfig {
+    fn default() -> Self {
+        SyslogConfig {
+            bind: "0.0.0.0".to_string(),
+            port: 514,
+            udp: true,
+            tcp: true,
+            severity: "err".to_string(),
+            facilities: Vec::new(),
+            patterns: Vec::new(),
+            exclude: Vec::new(),
+            max_per_minute: 10,
+        }
+    }
+}
+
+/// Settings for the interactive compose mode.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ComposeConfig {
+    /// Printers offered in the printer selector as `host:port`.
+    pub printers: Vec<String>,
+}
+
+impl Config {
+    /// Loads the configuration from a TOML file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the configuration file, or None for the default configuration
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, Box<dyn Error>>` - The parsed configuration, or an error if the
+    ///   file cannot be read, is not valid TOML or enables a webhook without a secret
+    pub fn load(path: Option<&str>) -> Result<Config, Box<dyn Error>> {
+        match path {
+            Some(path) => {
+                let content = fs::read_to_string(path)
+                    .map_err(|e| format!("cannot read config file {}: {}", path, e))?;
+                let config: Config = toml::from_str(&content)
+                    .map_err(|e| format!("invalid config file {}: {}", path, e))?;
+                config
+                    .validate()
+                    .map_err(|e| format!("invalid config file {}: {}", path, e))?;
+                Ok(config)
+            }
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// Checks settings that parse but cannot work, such as a webhook receiver without a
+    /// secret, which would accept requests signed with an empty key from anyone.
+    fn validate(&self) -> Result<(), String> {
+        if self
+            .jira
+            .as_ref()
+            .is_some_and(|jira| jira.secret.is_empty())
+        {
+            return Err("[jira] requires a non-empty secret".to_string());
+        }
+        for (section, forge) in [("gitlab", &self.gitlab), ("gitea", &self.gitea)] {
+            if forge.as_ref().is_some_and(|forge| forge.secret.is_empty()) {
+                return Err(format!("[{}] requires a non-empty secret", section));
+            }
+        }
+        if let Some(name) = self
+            .webhooks
+            .iter()
+            .find(|(_, webhook)| webhook.secret.is_empty())
+            .map(|(name, _)| name)
+        {
+            return Err(format!("[webhooks.{}] requires a non-empty secret", name));
+        }
+        Ok(())
+    }
+}
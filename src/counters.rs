@@ -0,0 +1,263 @@
+use axum::{body::Bytes, extract::Path, http::StatusCode, response::Json};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Serializes read-modify-write cycles of the counter file within this process. Other
+/// processes, e.g. CLI runs next to the API server, are excluded with a file lock.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Returns the directory persistent state such as counters is stored in.
+///
+/// This is `$TASKPRINTER_STATE_DIR`, `$XDG_STATE_HOME/taskprinter` or
+/// `~/.local/state/taskprinter`, whichever is set first.
+pub(crate) fn state_dir() -> PathBuf {
+    std::env::var_os("TASKPRINTER_STATE_DIR")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("XDG_STATE_HOME").map(|dir| PathBuf::from(dir).join("taskprinter"))
+        })
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".local/state/taskprinter"))
+        })
+        .unwrap_or_else(|| std::env::temp_dir().join("taskprinter"))
+}
+
+/// Returns the path of the file the counters are persisted in.
+fn counters_path() -> PathBuf {
+    state_dir().join("counters.json")
+}
+
+fn read_counters(path: &PathBuf) -> EscposResult<BTreeMap<String, u64>> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| {
+            PrinterError::Io(format!("invalid counter file {}: {}", path.display(), e))
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(e) => Err(PrinterError::Io(format!(
+            "cannot read counter file {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+fn write_counters(path: &PathBuf, counters: &BTreeMap<String, u64>) -> EscposResult<()> {
+    // Write to a temporary file first so a crash never leaves a truncated counter file.
+    let temp = path.with_extension("json.tmp");
+    let content =
+        serde_json::to_string_pretty(counters).map_err(|e| PrinterError::Io(e.to_string()))?;
+    fs::write(&temp, content).map_err(|e| PrinterError::Io(e.to_string()))?;
+    fs::rename(&temp, path).map_err(|e| PrinterError::Io(e.to_string()))
+}
+
+/// Runs a closure with exclusive access to the counter file, writing back the counters
+/// if the closure changed them.
+fn with_counters<T>(update: impl FnOnce(&mut BTreeMap<String, u64>) -> T) -> EscposResult<T> {
+    with_store(counters_path(), update)
+}
+
+/// Runs a closure with exclusive access to a file of named values in the same format as
+/// the counter file, writing back the values if the closure changed them.
+///
+/// # Arguments
+///
+/// * `path` - The JSON file, created on the first change
+/// * `update` - Reads or changes the values
+///
+/// # Returns
+///
+/// * `EscposResult<T>` - The result of the closure, or an I/O error if the file cannot
+///   be locked, read or written
+pub(crate) fn with_store<T>(
+    path: PathBuf,
+    update: impl FnOnce(&mut BTreeMap<String, u64>) -> T,
+) -> EscposResult<T> {
+    let _guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| PrinterError::Io(e.to_string()))?;
+    }
+    let lock =
+        File::create(path.with_extension("lock")).map_err(|e| PrinterError::Io(e.to_string()))?;
+    lock.lock()
+        .map_err(|e| PrinterError::Io(format!("cannot lock counter file: {}", e)))?;
+
+    let mut counters = read_counters(&path)?;
+    let before = counters.clone();
+    let result = update(&mut counters);
+    if counters != before {
+        write_counters(&path, &counters)?;
+    }
+    Ok(result)
+}
+
+/// Increments a named counter and returns its new value.
+///
+/// Counters start at zero, so the first call for a name returns 1. The values are
+/// persisted immediately and survive restarts.
+///
+/// # Arguments
+///
+/// * `name` - The name of the counter
+///
+/// # Returns
+///
+/// * `EscposResult<u64>` - The incremented value, or an I/O error if the counter file
+///   cannot be read or written
+pub fn increment(name: &str) -> EscposResult<u64> {
+    increment_by(name, 1)
+}
+
+/// Increments a named counter by a step and returns its new value.
+///
+/// # Arguments
+///
+/// * `name` - The name of the counter
+/// * `step` - The amount to add, saturating at `u64::MAX`
+///
+/// # Returns
+///
+/// * `EscposResult<u64>` - The incremented value, or an I/O error if the counter file
+///   cannot be read or written
+pub fn increment_by(name: &str, step: u64) -> EscposResult<u64> {
+    with_counters(|counters| {
+        let value = counters.entry(name.to_string()).or_insert(0);
+        *value = value.saturating_add(step);
+        *value
+    })
+}
+
+/// Sets a named counter to a value, e.g. 0 to reset it.
+///
+/// # Arguments
+///
+/// * `name` - The name of the counter
+/// * `value` - The new value; the next increment returns `value + 1`
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once the value is persisted, or an I/O error
+pub fn set(name: &str, value: u64) -> EscposResult<()> {
+    with_counters(|counters| {
+        counters.insert(name.to_string(), value);
+    })
+}
+
+/// Returns the current value of a named counter without changing it.
+///
+/// # Arguments
+///
+/// * `name` - The name of the counter
+///
+/// # Returns
+///
+/// * `EscposResult<u64>` - The value, 0 for counters that were never incremented
+pub fn get(name: &str) -> EscposResult<u64> {
+    with_counters(|counters| counters.get(name).copied().unwrap_or(0))
+}
+
+/// Returns all counters.
+///
+/// # Returns
+///
+/// * `EscposResult<BTreeMap<String, u64>>` - The counters ordered by name
+pub fn all() -> EscposResult<BTreeMap<String, u64>> {
+    with_counters(|counters| counters.clone())
+}
+
+/// Request body of `POST /counters/{name}`.
+///
+/// An empty body increments the counter by one.
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CounterUpdate {
+    /// Amount to increment the counter by.
+    pub by: Option<u64>,
+    /// Value to set the counter to instead of incrementing it, e.g. 0 to reset it.
+    pub value: Option<u64>,
+}
+
+/// Current value of a counter, returned by the counter endpoints.
+#[derive(Serialize)]
+pub struct CounterResponse {
+    /// The name of the counter.
+    pub name: String,
+    /// The value after the request.
+    pub value: u64,
+}
+
+/// Counter list endpoint handler.
+///
+/// This function handles GET requests to `/counters`.
+///
+/// # Returns
+///
+/// * `Result<Json<BTreeMap<String, u64>>, StatusCode>` - All counters by name, or HTTP
+///   500 if the counter file cannot be read
+pub async fn list_counters_handler() -> Result<Json<BTreeMap<String, u64>>, StatusCode> {
+    all().map(Json).map_err(counter_error)
+}
+
+/// Counter read endpoint handler.
+///
+/// This function handles GET requests to `/counters/{name}`. Counters that were never
+/// incremented have the value 0.
+///
+/// # Returns
+///
+/// * `Result<Json<CounterResponse>, StatusCode>` - The current value, or HTTP 500 if the
+///   counter file cannot be read
+pub async fn get_counter_handler(
+    Path(name): Path<String>,
+) -> Result<Json<CounterResponse>, StatusCode> {
+    let value = get(&name).map_err(counter_error)?;
+    Ok(Json(CounterResponse { name, value }))
+}
+
+/// Counter update endpoint handler.
+///
+/// This function handles POST requests to `/counters/{name}`. The counter is
+/// incremented atomically, by one or by `by`, or set to `value`:
+///
+/// ```json
+/// { "by": 10 }
+/// { "value": 0 }
+/// ```
+///
+/// # Returns
+///
+/// * `Result<Json<CounterResponse>, StatusCode>` - The value after the update, HTTP 400
+///   for an invalid body or HTTP 500 if the counter file cannot be written
+pub async fn update_counter_handler(
+    Path(name): Path<String>,
+    body: Bytes,
+) -> Result<Json<CounterResponse>, StatusCode> {
+    let update: CounterUpdate = if body.iter().all(u8::is_ascii_whitespace) {
+        CounterUpdate::default()
+    } else {
+        serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST)?
+    };
+
+    let value = match update {
+        CounterUpdate {
+            value: Some(value),
+            by: None,
+        } => set(&name, value).map(|()| value),
+        CounterUpdate { value: None, by } => increment_by(&name, by.unwrap_or(1)),
+        _ => return Err(StatusCode::BAD_REQUEST),
+    }
+    .map_err(counter_error)?;
+
+    info!("Counter {} is now {}", name, value);
+    Ok(Json(CounterResponse { name, value }))
+}
+
+fn counter_error(e: PrinterError) -> StatusCode {
+    error!("Counter error: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
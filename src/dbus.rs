@@ -0,0 +1,158 @@
+use crate::config::DbusConfig;
+use crate::printer::PrintTask;
+use crate::queue::JobQueue;
+use chrono::{DateTime, Local};
+use log::{info, warn};
+use serde_json::{Map, Number, Value as JsonValue};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio_stream::StreamExt;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::{OwnedValue, Value};
+use zbus::{connection, fdo, interface};
+
+/// Object path of the print service.
+const OBJECT_PATH: &str = "/org/taskprinter/Print";
+
+/// D-Bus interface submitting jobs to the print queue shared with the HTTP API.
+pub struct PrintService {
+    queue: Arc<JobQueue>,
+}
+
+#[interface(name = "org.taskprinter.Print")]
+impl PrintService {
+    /// Queues a slip and returns its job ID without waiting for the printer.
+    ///
+    /// The options take the other fields of the `/print` JSON request, e.g. `from`,
+    /// `address` or `expires_at`.
+    #[zbus(out_args("id"))]
+    async fn print(
+        &self,
+        title: &str,
+        message: &str,
+        mut options: HashMap<String, OwnedValue>,
+    ) -> fdo::Result<u64> {
+        let reference =
+            to_reference(options.remove("reference")).map_err(fdo::Error::InvalidArgs)?;
+        let (task, expires_at) =
+            to_task(title, message, options).map_err(fdo::Error::InvalidArgs)?;
+        let (id, _) = self
+            .queue
+            .submit(task, expires_at, reference)
+            .map_err(|e| fdo::Error::LimitsExceeded(e.to_string()))?;
+        info!("Queued D-Bus print job {}", id);
+        Ok(id)
+    }
+
+    /// Sent when a job is printed, failed, expired or was cancelled. The error is empty
+    /// unless the job failed.
+    #[zbus(signal)]
+    async fn job_completed(
+        emitter: &SignalEmitter<'_>,
+        id: u64,
+        status: &str,
+        error: &str,
+    ) -> zbus::Result<()>;
+}
+
+/// Reads the `reference` option of a `Print` call, ignoring empty references.
+fn to_reference(value: Option<OwnedValue>) -> Result<Option<String>, String> {
+    match value.as_deref().map(to_json) {
+        Some(Some(JsonValue::String(reference))) if !reference.trim().is_empty() => {
+            Ok(Some(reference))
+        }
+        Some(Some(JsonValue::String(_))) | None => Ok(None),
+        Some(_) => Err("reference must be a string".to_string()),
+    }
+}
+
+/// Builds the task of a `Print` call from its arguments.
+fn to_task(
+    title: &str,
+    message: &str,
+    options: HashMap<String, OwnedValue>,
+) -> Result<(PrintTask, Option<DateTime<Local>>), String> {
+    let mut fields = Map::new();
+    for (name, value) in options {
+        let value = to_json(&value).ok_or_else(|| format!("unsupported type of {}", name))?;
+        fields.insert(name, value);
+    }
+    let expires_at = match fields.remove("expires_at") {
+        Some(JsonValue::String(at)) => Some(
+            DateTime::parse_from_rfc3339(&at)
+                .map_err(|e| format!("invalid expires_at: {}", e))?
+                .with_timezone(&Local),
+        ),
+        Some(_) => return Err("expires_at must be an RFC 3339 string".to_string()),
+        None => None,
+    };
+    if !title.trim().is_empty() {
+        fields.insert("title".to_string(), title.into());
+    }
+    fields.insert("message".to_string(), message.into());
+
+    let task: PrintTask =
+        serde_json::from_value(JsonValue::Object(fields)).map_err(|e| e.to_string())?;
+    let task = PrintTask {
+        from: task.from.filter(|from| !from.trim().is_empty()),
+        ..task
+    };
+    Ok((task, expires_at))
+}
+
+/// Converts a basic D-Bus value to JSON, or None for containers and other types.
+fn to_json(value: &Value) -> Option<JsonValue> {
+    Some(match value {
+        Value::Bool(value) => JsonValue::Bool(*value),
+        Value::U8(value) => (*value).into(),
+        Value::I16(value) => (*value).into(),
+        Value::U16(value) => (*value).into(),
+        Value::I32(value) => (*value).into(),
+        Value::U32(value) => (*value).into(),
+        Value::I64(value) => (*value).into(),
+        Value::U64(value) => (*value).into(),
+        Value::F64(value) => JsonValue::Number(Number::from_f64(*value)?),
+        Value::Str(value) => JsonValue::String(value.to_string()),
+        Value::Value(value) => to_json(value)?,
+        _ => return None,
+    })
+}
+
+/// Starts the D-Bus service on the session bus and signals the completed jobs.
+///
+/// # Arguments
+///
+/// * `config` - The D-Bus settings
+/// * `queue` - The print queue shared with the HTTP API
+///
+/// # Returns
+///
+/// * `zbus::Result<()>` - Runs until the connection to the bus fails
+pub async fn start_dbus_service(config: DbusConfig, queue: Arc<JobQueue>) -> zbus::Result<()> {
+    let service = PrintService {
+        queue: Arc::clone(&queue),
+    };
+    let connection = connection::Builder::session()?
+        .name(config.name.as_str())?
+        .serve_at(OBJECT_PATH, service)?
+        .build()
+        .await?;
+    info!("D-Bus service {} running on the session bus", config.name);
+
+    let emitter = SignalEmitter::new(&connection, OBJECT_PATH)?;
+    loop {
+        // The event stream ends when the service falls behind, so subscribe again.
+        let mut events = queue.events(None);
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(job) if job.status.is_finished() => {
+                    let error = job.error.as_deref().unwrap_or_default();
+                    PrintService::job_completed(&emitter, job.id, job.status.as_str(), error)
+                        .await?;
+                }
+                Ok(_) => {}
+                Err(missed) => warn!("D-Bus service missed {} job events", missed),
+            }
+        }
+    }
+}
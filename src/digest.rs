@@ -0,0 +1,77 @@
+use crate::config::ScheduleConfig;
+use crate::fortune::random_fortune;
+use crate::i18n::{self, Label};
+use crate::weather;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::warn;
+
+/// Builds the message of a morning digest: the agenda, a random quote and today's forecast.
+///
+/// The agenda is the configured message, the forecast is included if a location is
+/// configured. Sections that cannot be built, e.g. because the weather service is
+/// unreachable, are left out with a warning, so the rest of the digest is still printed.
+///
+/// # Arguments
+///
+/// * `config` - The schedule entry providing the message, fortune file and location
+///
+/// # Returns
+///
+/// * `EscposResult<String>` - The sections separated by blank lines, or an I/O error if
+///   none of them could be built
+pub fn digest_message(config: &ScheduleConfig) -> EscposResult<String> {
+    let mut sections = Vec::new();
+    match random_fortune(config.file.as_deref()) {
+        Ok(quote) => sections.push((Label::Fortune, quote)),
+        Err(e) => warn!("Digest printed without quote: {:?}", e),
+    }
+    if let (Some(latitude), Some(longitude)) = (config.latitude, config.longitude) {
+        match weather::today(latitude, longitude) {
+            Ok(forecast) => sections.push((Label::Weather, forecast)),
+            Err(e) => warn!("Digest printed without weather: {:?}", e),
+        }
+    }
+    join_sections(config.message.as_deref(), sections)
+        .ok_or_else(|| PrinterError::Io("the digest has no content".to_string()))
+}
+
+/// Joins the agenda and the labeled sections, or returns None if there are none.
+fn join_sections(agenda: Option<&str>, sections: Vec<(Label, String)>) -> Option<String> {
+    let parts: Vec<String> = agenda
+        .map(str::to_string)
+        .into_iter()
+        .chain(
+            sections
+                .into_iter()
+                .map(|(label, text)| format!("{}\n{}", i18n::text(label), text)),
+        )
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_agenda_and_sections() {
+        let message = join_sections(
+            Some("Standup 9:30"),
+            vec![
+                (Label::Fortune, "Carpe diem".to_string()),
+                (Label::Weather, "Rain chance 80%".to_string()),
+            ],
+        );
+        assert_eq!(
+            message.as_deref(),
+            Some("Standup 9:30\n\nFORTUNE\nCarpe diem\n\nWEATHER\nRain chance 80%")
+        );
+    }
+
+    #[test]
+    fn leaves_out_missing_sections() {
+        let message = join_sections(None, vec![(Label::Fortune, "Carpe diem".to_string())]);
+        assert_eq!(message.as_deref(), Some("FORTUNE\nCarpe diem"));
+        assert_eq!(join_sections(None, Vec::new()), None);
+    }
+}
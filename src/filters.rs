@@ -0,0 +1,179 @@
+use crate::config::FilterConfig;
+use crate::printer::PrintTask;
+use crate::redact;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Compiled filters from the configuration, see [`init`].
+static FILTERS: OnceLock<Vec<Filter>> = OnceLock::new();
+
+/// A compiled content filter, see [`FilterConfig`].
+enum Filter {
+    Replace { pattern: Regex, replacement: String },
+    MaxLength(usize),
+    Drop(Regex),
+    UppercaseTitle,
+    RedactSecrets,
+}
+
+/// Compiles the content filters of the configuration.
+///
+/// The filters can only be set once, later calls are ignored. Without a call, jobs are
+/// printed unchanged.
+///
+/// # Arguments
+///
+/// * `configs` - The filters in the order they are applied
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for an invalid regular expression
+pub fn init(configs: &[FilterConfig]) -> EscposResult<()> {
+    let filters = configs
+        .iter()
+        .map(|config| {
+            Ok(match config {
+                FilterConfig::Replace {
+                    pattern,
+                    replacement,
+                } => Filter::Replace {
+                    pattern: compile(pattern)?,
+                    replacement: replacement.clone(),
+                },
+                FilterConfig::MaxLength { length } => Filter::MaxLength(*length),
+                FilterConfig::Drop { pattern } => Filter::Drop(compile(pattern)?),
+                FilterConfig::UppercaseTitle => Filter::UppercaseTitle,
+                FilterConfig::RedactSecrets => Filter::RedactSecrets,
+            })
+        })
+        .collect::<EscposResult<Vec<_>>>()?;
+    let _ = FILTERS.set(filters);
+    Ok(())
+}
+
+/// Applies the configured filters in order to a task.
+///
+/// # Arguments
+///
+/// * `task` - The task to filter
+///
+/// # Returns
+///
+/// * `Option<PrintTask>` - The filtered task, or None if a drop filter matched
+pub fn apply(task: PrintTask) -> Option<PrintTask> {
+    let Some(filters) = FILTERS.get() else {
+        return Some(task);
+    };
+    filters.iter().try_fold(task, |task, filter| match filter {
+        Filter::Replace {
+            pattern,
+            replacement,
+        } => Some(PrintTask {
+            title: task
+                .title
+                .map(|title| pattern.replace_all(&title, replacement).into_owned()),
+            message: pattern.replace_all(&task.message, replacement).into_owned(),
+            ..task
+        }),
+        Filter::MaxLength(length) => Some(PrintTask {
+            message: truncate(&task.message, *length),
+            ..task
+        }),
+        Filter::Drop(pattern) => {
+            let matches = task.title.as_deref().is_some_and(|t| pattern.is_match(t))
+                || pattern.is_match(&task.message);
+            (!matches).then_some(task)
+        }
+        Filter::UppercaseTitle => Some(PrintTask {
+            title: task.title.map(|title| title.to_uppercase()),
+            ..task
+        }),
+        Filter::RedactSecrets => Some(redact_secrets(task)),
+    })
+}
+
+/// Masks secrets in a task if a `redact_secrets` filter is configured, before the task
+/// is stored in the queue, history or spool. The other filters run when it is printed.
+/// Only the human-readable title, message and sender are masked. The data of barcodes
+/// and 2D codes is left alone, as masked digits would no longer scan or pass the check
+/// digit validation.
+///
+/// # Arguments
+///
+/// * `task` - The task to scrub
+///
+/// # Returns
+///
+/// * `PrintTask` - The task with secrets in title, message and sender masked
+pub fn redact(task: PrintTask) -> PrintTask {
+    let enabled = FILTERS
+        .get()
+        .is_some_and(|filters| filters.iter().any(|f| matches!(f, Filter::RedactSecrets)));
+    if enabled { redact_secrets(task) } else { task }
+}
+
+fn redact_secrets(task: PrintTask) -> PrintTask {
+    let encoded = task.barcode.is_some() || task.encode.unwrap_or(false);
+    PrintTask {
+        title: task.title.map(|title| redact::secrets(&title)),
+        message: if encoded {
+            task.message
+        } else {
+            redact::secrets(&task.message)
+        },
+        from: task.from.map(|from| redact::secrets(&from)),
+        ..task
+    }
+}
+
+/// Cuts a text to at most `length` characters, marking the cut with "...".
+fn truncate(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(length.saturating_sub(3)).collect();
+    format!("{}...", kept.trim_end())
+}
+
+/// Compiles the regular expression of a filter.
+fn compile(pattern: &str) -> EscposResult<Regex> {
+    Regex::new(pattern)
+        .map_err(|e| PrinterError::Input(format!("invalid filter pattern {}: {}", pattern, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_secrets_in_text() {
+        let task = redact_secrets(PrintTask {
+            title: Some("Deploy".to_string()),
+            message: "Authorization: Bearer abcdef123456\ncard 4111 1111 1111 1111".to_string(),
+            from: Some("ci token=hunter2hunter2".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(task.title.as_deref(), Some("Deploy"));
+        assert_eq!(task.message, "Authorization: Bearer ****\ncard **** 1111");
+        assert_eq!(task.from.as_deref(), Some("ci token=****"));
+    }
+
+    #[test]
+    fn keeps_barcode_and_code_data() {
+        // A valid EAN-13 that also passes the Luhn check of card numbers.
+        let barcode = redact_secrets(PrintTask {
+            message: "4006381333924".to_string(),
+            barcode: Some("EAN13".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(barcode.message, "4006381333924");
+
+        let code = redact_secrets(PrintTask {
+            message: "4111111111111111".to_string(),
+            encode: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(code.message, "4111111111111111");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::cli::Args;
+use crate::i18n::{self, Label};
+use crate::printer::{PrintTask, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use rand::seq::IndexedRandom;
+use std::fs;
+
+/// Quotes shipped with the binary, in classic `fortune` format.
+const BUNDLED_FORTUNES: &str = include_str!("assets/fortunes.txt");
+
+/// Number of characters that fit into the body of the printed frame.
+const LINE_WIDTH: usize = 20;
+
+/// Options for the fortune mode.
+#[derive(ClapArgs)]
+pub struct FortuneArgs {
+    /// Fortune file to pick from instead of the bundled quotes. Entries are separated by
+    /// lines containing only `%`; files without separators use one entry per line.
+    #[arg(short, long)]
+    pub file: Option<String>,
+}
+
+/// Prints a random quote from the bundled collection or a user-supplied fortune file.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `fortune` - The fortune options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn run_fortune(args: &Args, fortune: &FortuneArgs) -> EscposResult<()> {
+    print_task(PrintTask {
+        title: Some(i18n::text(Label::Fortune)),
+        message: random_fortune(fortune.file.as_deref())?,
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        media: args.media.clone(),
+        label_length: args.label_length,
+        ..Default::default()
+    })
+}
+
+/// Picks a random quote and wraps it to the width of a framed slip.
+///
+/// # Arguments
+///
+/// * `file` - Fortune file to pick from, or None for the bundled quotes
+///
+/// # Returns
+///
+/// * `EscposResult<String>` - The wrapped quote, or an input error if the file cannot
+///   be read or contains no entries
+pub fn random_fortune(file: Option<&str>) -> EscposResult<String> {
+    let source = match file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| PrinterError::Input(format!("cannot read {}: {}", path, e)))?,
+        None => BUNDLED_FORTUNES.to_string(),
+    };
+
+    let entries = parse_fortunes(&source);
+    let quote = entries
+        .choose(&mut rand::rng())
+        .ok_or_else(|| PrinterError::Input("fortune file contains no entries".to_string()))?;
+    Ok(wrap_words(quote, LINE_WIDTH))
+}
+
+/// Splits fortune file content into entries.
+///
+/// # Arguments
+///
+/// * `source` - The file content
+///
+/// # Returns
+///
+/// * `Vec<String>` - The non-empty entries with surrounding whitespace removed
+pub fn parse_fortunes(source: &str) -> Vec<String> {
+    let separated = source.lines().any(|line| line.trim() == "%");
+    let entries: Vec<String> = if separated {
+        source
+            .split('\n')
+            .collect::<Vec<_>>()
+            .split(|line| line.trim() == "%")
+            .map(|lines| lines.join("\n"))
+            .collect()
+    } else {
+        source.lines().map(str::to_string).collect()
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Wraps text at word boundaries so that no line exceeds `width` characters.
+fn wrap_words(text: &str, width: usize) -> String {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut current = String::new();
+        for word in paragraph.split_whitespace() {
+            let needed = current.chars().count() + word.chars().count() + 1;
+            if !current.is_empty() && needed > width {
+                lines.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        lines.push(current);
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,165 @@
+use crate::cli::Args;
+use crate::printer::{PrintTask, print_task};
+use clap::{Args as ClapArgs, ValueEnum};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::info;
+use std::io::{self, Read};
+use std::path::Path;
+use std::process::Command;
+
+/// Object name git uses for a ref that did not exist before or was deleted.
+const ZERO_OID: &str = "0000000000000000000000000000000000000000";
+
+/// Format of a single commit: short hash, author and subject separated by unit separators.
+const LOG_FORMAT: &str = "--format=%h%x1f%an%x1f%s";
+
+/// Git hook the command is invoked from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Hook {
+    /// Prints the commit that was just created
+    PostCommit,
+    /// Prints all commits of a push, reading `<old> <new> <ref>` lines from stdin
+    PostReceive,
+}
+
+/// Options for the git hook mode.
+#[derive(ClapArgs)]
+pub struct GitHookArgs {
+    /// Hook the command is called from
+    #[arg(value_enum, default_value = "post-commit")]
+    pub hook: Hook,
+
+    /// Title printed in the header, defaults to the repository name
+    #[arg(long)]
+    pub repo: Option<String>,
+}
+
+/// A commit as printed on the ticker.
+struct Commit {
+    hash: String,
+    author: String,
+    subject: String,
+}
+
+/// Prints commit metadata from a git hook as a compact "commit ticker" slip.
+///
+/// For `post-commit` the latest commit of the current repository is printed. For
+/// `post-receive` every pushed ref is read from stdin and all new commits are printed
+/// with the branch name, oldest first. Deleted refs are ignored.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `hook` - The git hook options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an error if git could not be queried
+pub fn run_git_hook(args: &Args, hook: &GitHookArgs) -> EscposResult<()> {
+    let mut sections = Vec::new();
+
+    match hook.hook {
+        Hook::PostCommit => {
+            for commit in git_log(&["-1"])? {
+                sections.push(format_commit(&commit));
+            }
+        }
+        Hook::PostReceive => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| PrinterError::Io(format!("cannot read hook input: {}", e)))?;
+
+            for line in input.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let [old, new, reference] = fields[..] else {
+                    continue;
+                };
+                if new == ZERO_OID {
+                    continue;
+                }
+                let branch = reference.trim_start_matches("refs/heads/");
+                // A newly created ref would list the whole history, only its tip is printed.
+                let commits = if old == ZERO_OID {
+                    git_log(&["-1", new])?
+                } else {
+                    git_log(&["--reverse", &format!("{}..{}", old, new)])?
+                };
+                for commit in commits {
+                    sections.push(format!("[{}]\n{}", branch, format_commit(&commit)));
+                }
+            }
+        }
+    }
+
+    if sections.is_empty() {
+        info!("No commits to print");
+        return Ok(());
+    }
+
+    print_task(PrintTask {
+        title: Some(hook.repo.clone().unwrap_or_else(repository_name)),
+        message: sections.join("\n\n"),
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        media: args.media.clone(),
+        label_length: args.label_length,
+        ..Default::default()
+    })
+}
+
+fn format_commit(commit: &Commit) -> String {
+    format!("{} {}\n{}", commit.hash, commit.author, commit.subject)
+}
+
+/// Runs `git log` with the given arguments and parses the commits.
+fn git_log(extra: &[&str]) -> EscposResult<Vec<Commit>> {
+    let output = Command::new("git")
+        .arg("log")
+        .arg(LOG_FORMAT)
+        .args(extra)
+        .output()
+        .map_err(|e| PrinterError::Io(format!("cannot run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(PrinterError::Io(format!(
+            "git log failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(3, '\u{1f}');
+            Some(Commit {
+                hash: parts.next()?.to_string(),
+                author: parts.next()?.to_string(),
+                subject: parts.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Derives the repository name from the work tree, or from the git directory of bare repositories.
+fn repository_name() -> String {
+    let query = |flag: &str| {
+        Command::new("git")
+            .args(["rev-parse", flag])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|path| !path.is_empty())
+    };
+
+    query("--show-toplevel")
+        .or_else(|| query("--absolute-git-dir"))
+        .and_then(|path| {
+            Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().trim_end_matches(".git").to_string())
+        })
+        .unwrap_or_else(|| "GIT".to_string())
+        .to_uppercase()
+}
@@ -0,0 +1,229 @@
+use crate::api::AppState;
+use crate::metrics::{self, PrinterStats};
+use crate::queue::{JobInfo, JobQueue, JobStatus, QueueStatus};
+use async_graphql::http::{
+    GraphQLPlaygroundConfig, WebSocket as GraphqlWebSocket, WebSocketProtocols, WsMessage,
+    playground_source,
+};
+use async_graphql::{EmptyMutation, Object, Schema, SimpleObject, Subscription};
+use axum::{
+    Json,
+    extract::State,
+    extract::ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+    http::{HeaderMap, StatusCode, header::SEC_WEBSOCKET_PROTOCOL},
+    response::{Html, IntoResponse},
+};
+use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
+use tokio_stream::Stream;
+
+/// GraphQL schema of the API server.
+pub type GraphqlSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
+
+/// Builds the GraphQL schema on top of the print queue.
+///
+/// # Arguments
+///
+/// * `queue` - The print queue shared with `/print` and `/jobs`
+///
+/// # Returns
+///
+/// * `GraphqlSchema` - The schema answering the `/graphql` requests
+pub fn schema(queue: Arc<JobQueue>) -> GraphqlSchema {
+    Schema::build(
+        QueryRoot {
+            queue: Arc::clone(&queue),
+        },
+        EmptyMutation,
+        SubscriptionRoot { queue },
+    )
+    .finish()
+}
+
+/// Totals of all printers since the server started.
+#[derive(SimpleObject)]
+pub struct Stats {
+    /// Queued jobs printed successfully.
+    pub printed: u64,
+    /// Queued jobs that failed to print.
+    pub failed: u64,
+    /// Failed connection attempts.
+    pub connect_errors: u64,
+}
+
+/// Queries of the GraphQL schema.
+pub struct QueryRoot {
+    queue: Arc<JobQueue>,
+}
+
+#[Object]
+impl QueryRoot {
+    /// The job being printed, the waiting jobs and the last finished jobs, ordered by ID,
+    /// optionally only those with a status or submitted with a reference.
+    async fn jobs(&self, status: Option<JobStatus>, reference: Option<String>) -> Vec<JobInfo> {
+        self.queue
+            .jobs()
+            .into_iter()
+            .filter(|job| status.is_none_or(|status| job.status == status))
+            .filter(|job| {
+                reference
+                    .as_ref()
+                    .is_none_or(|reference| job.reference.as_ref() == Some(reference))
+            })
+            .collect()
+    }
+
+    /// A single job, or null if it is unknown or no longer in the history.
+    async fn job(&self, id: u64) -> Option<JobInfo> {
+        self.queue.job(id)
+    }
+
+    /// Whether the queue is paused and how many jobs are waiting.
+    async fn queue(&self) -> QueueStatus {
+        self.queue.status()
+    }
+
+    /// The printers used since the server started.
+    async fn printers(&self) -> Vec<PrinterStats> {
+        metrics::printers()
+    }
+
+    /// Totals of all printers since the server started.
+    async fn stats(&self) -> Stats {
+        metrics::printers().iter().fold(
+            Stats {
+                printed: 0,
+                failed: 0,
+                connect_errors: 0,
+            },
+            |stats, printer| Stats {
+                printed: stats.printed + printer.printed,
+                failed: stats.failed + printer.failed,
+                connect_errors: stats.connect_errors + printer.connect_errors,
+            },
+        )
+    }
+}
+
+/// Subscriptions of the GraphQL schema.
+pub struct SubscriptionRoot {
+    queue: Arc<JobQueue>,
+}
+
+#[Subscription]
+impl SubscriptionRoot {
+    /// Status changes of all jobs, or of a single job. A client that reads too slowly
+    /// receives an error, after which the subscription ends.
+    async fn job_events(
+        &self,
+        job_id: Option<u64>,
+    ) -> impl Stream<Item = async_graphql::Result<JobInfo>> {
+        self.queue.events(job_id).map(|event| {
+            event.map_err(|missed| format!("client too slow, {} events dropped", missed).into())
+        })
+    }
+}
+
+/// GraphQL endpoint handler.
+///
+/// This function handles POST requests to `/graphql` with a JSON encoded GraphQL query.
+///
+/// # Arguments
+///
+/// * `state` - The shared server state holding the schema
+/// * `request` - The GraphQL request with query, operation name and variables
+///
+/// # Returns
+///
+/// * `Result<Json<async_graphql::Response>, StatusCode>` - The GraphQL response, or
+///   404 Not Found if no `[graphql]` section is configured
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Result<Json<async_graphql::Response>, StatusCode> {
+    let schema = state.graphql.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(schema.execute(request).await))
+}
+
+/// GraphQL Playground handler.
+///
+/// This function handles GET requests to `/graphql` and serves the GraphQL Playground
+/// to explore the schema in the browser.
+///
+/// # Returns
+///
+/// * `Result<Html<String>, StatusCode>` - The playground page, or 404 Not Found if the
+///   endpoint or the playground is disabled
+pub async fn graphql_playground_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, StatusCode> {
+    match &state.config.graphql {
+        Some(config) if config.playground => Ok(Html(playground_source(
+            GraphQLPlaygroundConfig::new("/graphql").subscription_endpoint("/graphql/ws"),
+        ))),
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// GraphQL subscription handler.
+///
+/// This function handles WebSocket upgrades on `/graphql/ws` for subscriptions, speaking
+/// the `graphql-transport-ws` or the older `graphql-ws` protocol as requested by the
+/// client.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, StatusCode>` - The upgrade response, 400 Bad Request if
+///   the client requests no supported protocol, or 404 Not Found if the endpoint is
+///   disabled
+pub async fn graphql_ws_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Result<impl IntoResponse, StatusCode> {
+    let schema = state.graphql.clone().ok_or(StatusCode::NOT_FOUND)?;
+    let protocol = headers
+        .get(SEC_WEBSOCKET_PROTOCOL)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            value
+                .split(',')
+                .find_map(|protocol| protocol.trim().parse::<WebSocketProtocols>().ok())
+        })
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    Ok(upgrade
+        .protocols([protocol.sec_websocket_protocol()])
+        .on_upgrade(move |socket| serve_subscriptions(socket, schema, protocol)))
+}
+
+/// Relays the messages between a WebSocket and the GraphQL subscription protocol.
+async fn serve_subscriptions(
+    socket: WebSocket,
+    schema: GraphqlSchema,
+    protocol: WebSocketProtocols,
+) {
+    let (mut sink, stream) = socket.split();
+    let input = stream
+        .take_while(|message| futures_util::future::ready(message.is_ok()))
+        .filter_map(|message| {
+            futures_util::future::ready(match message {
+                Ok(Message::Text(text)) => Some(text.into_bytes()),
+                Ok(Message::Binary(data)) => Some(data),
+                _ => None,
+            })
+        });
+
+    let mut output = GraphqlWebSocket::new(schema, input, protocol);
+    while let Some(message) = output.next().await {
+        let message = match message {
+            WsMessage::Text(text) => Message::Text(text),
+            WsMessage::Close(code, reason) => Message::Close(Some(CloseFrame {
+                code,
+                reason: reason.into(),
+            })),
+        };
+        if sink.send(message).await.is_err() {
+            break;
+        }
+    }
+}
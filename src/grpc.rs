@@ -0,0 +1,150 @@
+use crate::printer::PrintTask;
+use crate::queue::{JobInfo, JobQueue};
+use chrono::{DateTime, Local};
+use log::info;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+
+/// Code generated from `proto/taskprinter.proto`.
+pub mod proto {
+    tonic::include_proto!("taskprinter");
+}
+
+use proto::task_printer_server::{TaskPrinter, TaskPrinterServer};
+use proto::{GetJobRequest, Job, StreamEventsRequest, SubmitJobRequest};
+
+/// gRPC service submitting jobs to the print queue shared with the HTTP API.
+pub struct TaskPrinterService {
+    queue: Arc<JobQueue>,
+}
+
+#[tonic::async_trait]
+impl TaskPrinter for TaskPrinterService {
+    async fn submit_job(
+        &self,
+        request: Request<SubmitJobRequest>,
+    ) -> Result<Response<Job>, Status> {
+        let request = request.into_inner();
+        let expires_at = match request.expires_at.as_deref() {
+            Some(at) => Some(
+                DateTime::parse_from_rfc3339(at)
+                    .map_err(|e| Status::invalid_argument(format!("invalid expires_at: {}", e)))?
+                    .with_timezone(&Local),
+            ),
+            None => None,
+        };
+        let task = PrintTask {
+            title: request.title,
+            message: request.message,
+            date: request.date,
+            encode: request.encode,
+            address: request.address,
+            port: request
+                .port
+                .map(u16::try_from)
+                .transpose()
+                .map_err(|_| Status::invalid_argument("port out of range"))?,
+            codepage: request.codepage,
+            barcode: request.barcode,
+            symbology: request.symbology,
+            split_items: request.split_items,
+            media: request.media,
+            label_length: request
+                .label_length
+                .map(u16::try_from)
+                .transpose()
+                .map_err(|_| Status::invalid_argument("label_length out of range"))?,
+            link: request.link,
+            footer_barcode: request.footer_barcode,
+            mode: request.mode,
+            hyphenate: request.hyphenate,
+            normalize: request.normalize,
+            squeeze_blank: request.squeeze_blank,
+            trim_trailing: request.trim_trailing,
+            dedent: request.dedent,
+            header: None,
+            from: request.from.filter(|from| !from.trim().is_empty()),
+        };
+
+        // The outcome is followed with GetJob or StreamEvents instead.
+        let (id, _) = self
+            .queue
+            .submit(
+                task,
+                expires_at,
+                request
+                    .reference
+                    .filter(|reference| !reference.trim().is_empty()),
+            )
+            .map_err(|e| Status::resource_exhausted(e.to_string()))?;
+        info!("Queued gRPC print job {}", id);
+        self.queue
+            .job(id)
+            .map(|job| Response::new(to_proto(job)))
+            .ok_or_else(|| Status::internal("submitted job not found"))
+    }
+
+    async fn get_job(&self, request: Request<GetJobRequest>) -> Result<Response<Job>, Status> {
+        let id = request.into_inner().id;
+        self.queue
+            .job(id)
+            .map(|job| Response::new(to_proto(job)))
+            .ok_or_else(|| Status::not_found(format!("job {} not found", id)))
+    }
+
+    type StreamEventsStream = Pin<Box<dyn Stream<Item = Result<Job, Status>> + Send>>;
+
+    // The stream items are results with the large `Status` error, as tonic requires.
+    #[allow(clippy::result_large_err)]
+    async fn stream_events(
+        &self,
+        request: Request<StreamEventsRequest>,
+    ) -> Result<Response<Self::StreamEventsStream>, Status> {
+        let events = self.queue.events(request.into_inner().job_id).map(|event| {
+            event.map(to_proto).map_err(|missed| {
+                Status::resource_exhausted(format!("client too slow, {} events dropped", missed))
+            })
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+}
+
+/// Converts a job to its protobuf representation.
+fn to_proto(job: JobInfo) -> Job {
+    Job {
+        id: job.id,
+        status: job.status.as_str().to_string(),
+        title: job.title,
+        from: job.from,
+        submitted_at: job.submitted_at.to_rfc3339(),
+        expires_at: job.expires_at.map(|at| at.to_rfc3339()),
+        finished_at: job.finished_at.map(|at| at.to_rfc3339()),
+        error: job.error,
+        reference: job.reference,
+    }
+}
+
+/// Starts the gRPC service on all interfaces.
+///
+/// # Arguments
+///
+/// * `port` - The port to listen on
+/// * `queue` - The print queue shared with the HTTP API
+///
+/// # Returns
+///
+/// * `Result<(), tonic::transport::Error>` - Runs until the server fails
+pub async fn start_grpc_server(
+    port: u16,
+    queue: Arc<JobQueue>,
+) -> Result<(), tonic::transport::Error> {
+    let address = SocketAddr::from((Ipv4Addr::UNSPECIFIED, port));
+    info!("gRPC server running on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(TaskPrinterServer::new(TaskPrinterService { queue }))
+        .serve(address)
+        .await
+}
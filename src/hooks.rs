@@ -0,0 +1,97 @@
+use crate::config::HooksConfig;
+use crate::metrics;
+use crate::printer::{PrintTask, resolve_printer};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::warn;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Commands of the configuration, see [`init`].
+static HOOKS: OnceLock<HooksConfig> = OnceLock::new();
+
+/// Registers the commands run before and after every job.
+///
+/// The hooks can only be registered once, later calls are ignored.
+///
+/// # Arguments
+///
+/// * `hooks` - The commands of the `[hooks]` section
+pub fn init(hooks: HooksConfig) {
+    let _ = HOOKS.set(hooks);
+}
+
+/// Prints a job between the `before` and `after` commands.
+///
+/// The commands are run with `sh -c` and receive the job in environment variables:
+/// `TASKPRINTER_PRINTER` (`host:port`), `TASKPRINTER_ADDRESS`, `TASKPRINTER_PORT`,
+/// `TASKPRINTER_TITLE`, `TASKPRINTER_FROM` and `TASKPRINTER_MODE`. The `after` command
+/// also gets `TASKPRINTER_STATUS` (`printed` or `failed`) and, for failed jobs,
+/// `TASKPRINTER_ERROR`.
+///
+/// # Arguments
+///
+/// * `task` - The job, used for the environment variables
+/// * `print` - Prints the job
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - The result of `print`, or an IO error if the `before` command
+///   failed, in which case the job is not printed. A failing `after` command is only
+///   logged.
+pub fn run(task: &PrintTask, print: impl FnOnce() -> EscposResult<()>) -> EscposResult<()> {
+    let Some(hooks) = HOOKS.get() else {
+        return print();
+    };
+    if let Some(before) = &hooks.before {
+        execute(before, task, &[])
+            .map_err(|e| PrinterError::Io(format!("before hook failed, job not printed: {}", e)))?;
+    }
+    let result = print();
+    if let Some(after) = &hooks.after {
+        let status = match &result {
+            Ok(()) => vec![("TASKPRINTER_STATUS", "printed".to_string())],
+            Err(e) => vec![
+                ("TASKPRINTER_STATUS", "failed".to_string()),
+                ("TASKPRINTER_ERROR", e.to_string()),
+            ],
+        };
+        if let Err(e) = execute(after, task, &status) {
+            warn!("After hook failed: {}", e);
+        }
+    }
+    result
+}
+
+/// Runs a hook command and waits for it to exit.
+fn execute(command: &str, task: &PrintTask, extra: &[(&str, String)]) -> Result<(), String> {
+    let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env(
+            "TASKPRINTER_PRINTER",
+            metrics::printer_label(Some(&address), Some(port)),
+        )
+        .env("TASKPRINTER_ADDRESS", address)
+        .env("TASKPRINTER_PORT", port.to_string())
+        .env(
+            "TASKPRINTER_TITLE",
+            task.title.as_deref().unwrap_or_default(),
+        )
+        .env("TASKPRINTER_FROM", task.from.as_deref().unwrap_or_default())
+        .env("TASKPRINTER_MODE", task.mode.as_deref().unwrap_or("frame"))
+        .envs(extra.iter().cloned())
+        .output()
+        .map_err(|e| format!("cannot run {}: {}", command, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match stderr.trim() {
+        "" => Err(format!("{} exited with {}", command, output.status)),
+        stderr => Err(format!(
+            "{} exited with {}: {}",
+            command, output.status, stderr
+        )),
+    }
+}
@@ -0,0 +1,107 @@
+use escpos::errors::{PrinterError, Result as EscposResult};
+use hyphenation::{Hyphenator, Language, Load, Standard};
+use std::ops::Range;
+
+/// A wrapped line: the range of characters it shows and whether a hyphen is appended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedLine {
+    /// Range of characters of the wrapped text shown on this line.
+    pub range: Range<usize>,
+    /// Whether the line ends inside a hyphenated word.
+    pub hyphen: bool,
+}
+
+/// Loads the embedded hyphenation dictionary of a language.
+///
+/// # Arguments
+///
+/// * `code` - The language code, e.g. `de`, `en`, `en-gb`, `fr` or `de-ch-1901`.
+///   `de` selects the 1996 German orthography and `en` American English.
+///
+/// # Returns
+///
+/// * `EscposResult<Standard>` - The dictionary, or an input error for unknown languages
+pub fn load(code: &str) -> EscposResult<Standard> {
+    let code = code.to_lowercase();
+    let language = match code.as_str() {
+        "de" => Some(Language::German1996),
+        "en" => Some(Language::EnglishUS),
+        other => Language::try_from_code(other),
+    }
+    .ok_or_else(|| PrinterError::Input(format!("unsupported hyphenation language: {}", code)))?;
+
+    Standard::from_embedded(language)
+        .map_err(|e| PrinterError::Input(format!("cannot load hyphenation dictionary: {}", e)))
+}
+
+/// Word wraps a single line of text, hyphenating words that do not fit.
+///
+/// Lines are broken at spaces where possible. A word crossing the end of a line is
+/// hyphenated at the last break point that leaves room for the hyphen. Words without
+/// a suitable break point move to the next line, or are cut if they are longer than
+/// a whole line.
+///
+/// # Arguments
+///
+/// * `chars` - The characters of the line, without line breaks
+/// * `width` - The maximum number of characters per wrapped line, including the hyphen
+/// * `dictionary` - The hyphenation dictionary
+///
+/// # Returns
+///
+/// * `Vec<WrappedLine>` - The wrapped lines, at least one for an empty line
+pub fn wrap(chars: &[char], width: usize, dictionary: &Standard) -> Vec<WrappedLine> {
+    let mut lines = Vec::new();
+    let mut pos = 0;
+
+    while chars.len() - pos > width {
+        let limit = pos + width;
+        let last_space = chars[pos..=limit]
+            .iter()
+            .rposition(|c| *c == ' ')
+            .map(|i| pos + i);
+        let word_start = last_space.map_or(pos, |space| space + 1);
+        let word_end = chars[word_start..]
+            .iter()
+            .position(|c| *c == ' ')
+            .map_or(chars.len(), |i| word_start + i);
+
+        let room = limit.saturating_sub(word_start + 1);
+        let word: String = chars[word_start..word_end].iter().collect();
+        let hyphenated = dictionary.hyphenate(&word);
+        let split = hyphenated
+            .breaks
+            .iter()
+            .map(|byte| word[..*byte].chars().count())
+            .filter(|split| *split > 0 && *split <= room)
+            .max();
+
+        let (end, next, hyphen) = match (split, last_space) {
+            (Some(split), _) => (word_start + split, word_start + split, true),
+            (None, Some(space)) if space > pos => (space, space + 1, false),
+            _ => (limit, limit, false),
+        };
+
+        let mut trimmed = end;
+        while trimmed > pos && chars[trimmed - 1] == ' ' {
+            trimmed -= 1;
+        }
+        lines.push(WrappedLine {
+            range: pos..trimmed,
+            hyphen,
+        });
+
+        pos = next;
+        while pos < chars.len() && chars[pos] == ' ' {
+            pos += 1;
+        }
+    }
+
+    if pos < chars.len() || lines.is_empty() {
+        lines.push(WrappedLine {
+            range: pos..chars.len(),
+            hyphen: false,
+        });
+    }
+    lines
+}
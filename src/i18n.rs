@@ -0,0 +1,385 @@
+use escpos::errors::{PrinterError, Result as EscposResult};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Language and label overrides from the configuration, see [`init`].
+static LABELS: OnceLock<Labels> = OnceLock::new();
+
+/// Language of the built-in labels and the overrides replacing them, as configured.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels {
+    pub language: Language,
+    /// Texts replacing built-in labels, keyed by [`Label::key`].
+    pub overrides: HashMap<String, String>,
+}
+
+impl Labels {
+    /// Returns the text of a label, the override or the built-in text of the language.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to look up
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The override, or the built-in text
+    pub fn text(&self, label: Label) -> String {
+        self.overrides
+            .get(label.key())
+            .cloned()
+            .unwrap_or_else(|| label.builtin(self.language).to_string())
+    }
+
+    /// Returns the text of a label with its placeholders filled in.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - The label to look up
+    /// * `values` - Placeholder names and their values, e.g. `("count", "3")` for `{count}`
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The label text with every `{name}` replaced by its value
+    pub fn format(&self, label: Label, values: &[(&str, &str)]) -> String {
+        values.iter().fold(self.text(label), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+    }
+}
+
+/// Language of the built-in labels printed on slips.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+impl Language {
+    /// Parses a language code (`en`, `de`, `fr`, `es`), defaulting to English.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The optional language code, case-insensitive. Region suffixes such as
+    ///   `de-AT` are ignored.
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Language>` - The matching language, or an input error for
+    ///   unsupported languages
+    pub fn parse(code: Option<&str>) -> EscposResult<Language> {
+        let code = code.map(|code| code.to_lowercase().replace('_', "-"));
+        match code
+            .as_deref()
+            .map(|code| code.split('-').next().unwrap_or(code))
+        {
+            None | Some("en") => Ok(Language::English),
+            Some("de") => Ok(Language::German),
+            Some("fr") => Ok(Language::French),
+            Some("es") => Ok(Language::Spanish),
+            Some(other) => Err(PrinterError::Input(format!(
+                "unsupported language: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// A built-in label printed on slips.
+///
+/// Labels containing values use named placeholders such as `{count}`, which are filled
+/// in by [`format`]. Overrides in the configuration may use the same placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Label {
+    /// Title of slips without a title.
+    Note,
+    /// Attribution below the message, `{name}` is the sender.
+    From,
+    /// Note below a cut off batch of lines, `{count}` is the number of lines left out.
+    MoreLines,
+    Fortune,
+    Pomodoro,
+    Weather,
+    Sudoku,
+    WordSearch,
+    Solution,
+    Shopping,
+    Receipt,
+    /// Receipt line of the amount without tax when prices include tax.
+    Net,
+    /// Receipt line of the amount before tax.
+    Subtotal,
+    /// Receipt line of a tax rate, `{letter}` marks the items and `{rate}` is the percentage.
+    Tax,
+    Total,
+    PackingSlip,
+    /// `{number}` is the order number.
+    Order,
+    ShipTo,
+    Items,
+    /// Column header of the packing slip item list.
+    QtyItem,
+    /// `{count}` is the total quantity.
+    ItemCount,
+    Coupon,
+    /// `{date}` is the expiry date.
+    ValidUntil,
+    /// `{code}` is the coupon code.
+    Code,
+    CiFailed,
+    CiPassed,
+    WeeklySummary,
+    /// `{count}` is the number of printed jobs.
+    Printed,
+    /// `{count}` is the number of failed jobs.
+    Failed,
+    /// `{length}` is the estimated paper length, e.g. `2.4 m`.
+    Paper,
+    TopSenders,
+    /// Sender of jobs without a `from`.
+    Anonymous,
+    /// Title of the morning digest.
+    Digest,
+}
+
+impl Label {
+    /// Every label, in the order of [`Label::key`].
+    pub const ALL: [Label; 33] = [
+        Label::Note,
+        Label::From,
+        Label::MoreLines,
+        Label::Fortune,
+        Label::Pomodoro,
+        Label::Weather,
+        Label::Sudoku,
+        Label::WordSearch,
+        Label::Solution,
+        Label::Shopping,
+        Label::Receipt,
+        Label::Net,
+        Label::Subtotal,
+        Label::Tax,
+        Label::Total,
+        Label::PackingSlip,
+        Label::Order,
+        Label::ShipTo,
+        Label::Items,
+        Label::QtyItem,
+        Label::ItemCount,
+        Label::Coupon,
+        Label::ValidUntil,
+        Label::Code,
+        Label::CiFailed,
+        Label::CiPassed,
+        Label::WeeklySummary,
+        Label::Printed,
+        Label::Failed,
+        Label::Paper,
+        Label::TopSenders,
+        Label::Anonymous,
+        Label::Digest,
+    ];
+
+    /// Returns the name of the label in the `[labels]` section of the configuration.
+    pub fn key(self) -> &'static str {
+        match self {
+            Label::Note => "note",
+            Label::From => "from",
+            Label::MoreLines => "more_lines",
+            Label::Fortune => "fortune",
+            Label::Pomodoro => "pomodoro",
+            Label::Weather => "weather",
+            Label::Sudoku => "sudoku",
+            Label::WordSearch => "word_search",
+            Label::Solution => "solution",
+            Label::Shopping => "shopping",
+            Label::Receipt => "receipt",
+            Label::Net => "net",
+            Label::Subtotal => "subtotal",
+            Label::Tax => "tax",
+            Label::Total => "total",
+            Label::PackingSlip => "packing_slip",
+            Label::Order => "order",
+            Label::ShipTo => "ship_to",
+            Label::Items => "items",
+            Label::QtyItem => "qty_item",
+            Label::ItemCount => "item_count",
+            Label::Coupon => "coupon",
+            Label::ValidUntil => "valid_until",
+            Label::Code => "code",
+            Label::CiFailed => "ci_failed",
+            Label::CiPassed => "ci_passed",
+            Label::WeeklySummary => "weekly_summary",
+            Label::Printed => "printed",
+            Label::Failed => "failed",
+            Label::Paper => "paper",
+            Label::TopSenders => "top_senders",
+            Label::Anonymous => "anonymous",
+            Label::Digest => "digest",
+        }
+    }
+
+    /// Returns the built-in text of the label in a language.
+    fn builtin(self, language: Language) -> &'static str {
+        // English, German, French, Spanish
+        let texts = match self {
+            Label::Note => ["NOTE", "NOTIZ", "NOTE", "NOTA"],
+            Label::From => ["From: {name}", "Von: {name}", "De : {name}", "De: {name}"],
+            Label::MoreLines => [
+                "({count} more lines)",
+                "({count} weitere Zeilen)",
+                "({count} lignes de plus)",
+                "({count} líneas más)",
+            ],
+            Label::Fortune => ["FORTUNE", "SPRUCH", "CITATION", "FRASE"],
+            Label::Pomodoro => ["POMODORO", "POMODORO", "POMODORO", "POMODORO"],
+            Label::Weather => ["WEATHER", "WETTER", "MÉTÉO", "TIEMPO"],
+            Label::Sudoku => ["SUDOKU", "SUDOKU", "SUDOKU", "SUDOKU"],
+            Label::WordSearch => ["WORD SEARCH", "SUCHRÄTSEL", "MOTS MÊLÉS", "SOPA DE LETRAS"],
+            Label::Solution => ["SOLUTION", "LÖSUNG", "SOLUTION", "SOLUCIÓN"],
+            Label::Shopping => ["SHOPPING", "EINKAUF", "COURSES", "COMPRA"],
+            Label::Receipt => ["RECEIPT", "QUITTUNG", "REÇU", "RECIBO"],
+            Label::Net => ["Net", "Netto", "HT", "Neto"],
+            Label::Subtotal => ["Subtotal", "Zwischensumme", "Sous-total", "Subtotal"],
+            Label::Tax => [
+                "Tax {letter} {rate}%",
+                "MwSt {letter} {rate}%",
+                "TVA {letter} {rate}%",
+                "IVA {letter} {rate}%",
+            ],
+            Label::Total => ["TOTAL", "SUMME", "TOTAL", "TOTAL"],
+            Label::PackingSlip => [
+                "PACKING SLIP",
+                "LIEFERSCHEIN",
+                "BON DE LIVRAISON",
+                "ALBARÁN",
+            ],
+            Label::Order => [
+                "ORDER #{number}",
+                "BESTELLUNG #{number}",
+                "COMMANDE #{number}",
+                "PEDIDO #{number}",
+            ],
+            Label::ShipTo => ["Ship to", "Lieferadresse", "Livrer à", "Enviar a"],
+            Label::Items => ["Items", "Artikel", "Articles", "Artículos"],
+            Label::QtyItem => ["QTY ITEM", "ANZ ARTIKEL", "QTÉ ARTICLE", "CNT ARTÍCULO"],
+            Label::ItemCount => [
+                "{count} items",
+                "{count} Artikel",
+                "{count} articles",
+                "{count} artículos",
+            ],
+            Label::Coupon => ["COUPON", "GUTSCHEIN", "BON", "CUPÓN"],
+            Label::ValidUntil => [
+                "Valid until {date}",
+                "Gültig bis {date}",
+                "Valable jusqu'au {date}",
+                "Válido hasta {date}",
+            ],
+            Label::Code => [
+                "Code: {code}",
+                "Code: {code}",
+                "Code : {code}",
+                "Código: {code}",
+            ],
+            Label::CiFailed => ["CI FAILED", "CI FEHLER", "CI ÉCHEC", "CI FALLO"],
+            Label::CiPassed => ["CI PASSED", "CI OK", "CI RÉUSSI", "CI CORRECTO"],
+            Label::WeeklySummary => [
+                "WEEKLY SUMMARY",
+                "WOCHENBERICHT",
+                "BILAN SEMAINE",
+                "RESUMEN SEMANA",
+            ],
+            Label::Printed => [
+                "{count} printed",
+                "{count} gedruckt",
+                "{count} imprimés",
+                "{count} impresos",
+            ],
+            Label::Failed => [
+                "{count} failed",
+                "{count} fehlgeschlagen",
+                "{count} échoués",
+                "{count} fallidos",
+            ],
+            Label::Paper => [
+                "Paper: ~{length}",
+                "Papier: ~{length}",
+                "Papier : ~{length}",
+                "Papel: ~{length}",
+            ],
+            Label::TopSenders => [
+                "TOP SENDERS",
+                "TOP ABSENDER",
+                "TOP EXPÉDITEURS",
+                "TOP REMITENTES",
+            ],
+            Label::Anonymous => ["anonymous", "anonym", "anonyme", "anónimo"],
+            Label::Digest => ["GOOD MORNING", "GUTEN MORGEN", "BONJOUR", "BUENOS DÍAS"],
+        };
+        texts[language as usize]
+    }
+}
+
+/// Sets the language of the built-in labels and the overrides from the configuration.
+///
+/// The labels can only be set once, later calls are ignored. Without a call, the English
+/// labels are used.
+///
+/// # Arguments
+///
+/// * `language` - The optional language code, see [`Language::parse`]
+/// * `overrides` - Texts replacing built-in labels, keyed by [`Label::key`]
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for an unsupported language or an
+///   unknown label name
+pub fn init(language: Option<&str>, overrides: &HashMap<String, String>) -> EscposResult<()> {
+    let language = Language::parse(language)?;
+    if let Some(key) = overrides
+        .keys()
+        .find(|key| !Label::ALL.iter().any(|label| label.key() == key.as_str()))
+    {
+        return Err(PrinterError::Input(format!("unknown label: {}", key)));
+    }
+    let _ = LABELS.set(Labels {
+        language,
+        overrides: overrides.clone(),
+    });
+    Ok(())
+}
+
+/// Returns the text of a label in the configured language.
+///
+/// # Arguments
+///
+/// * `label` - The label to look up
+///
+/// # Returns
+///
+/// * `String` - The override from the configuration, or the built-in text
+pub fn text(label: Label) -> String {
+    configured().text(label)
+}
+
+/// Returns the text of a label with its placeholders filled in.
+///
+/// # Arguments
+///
+/// * `label` - The label to look up
+/// * `values` - Placeholder names and their values, e.g. `("count", "3")` for `{count}`
+///
+/// # Returns
+///
+/// * `String` - The label text with every `{name}` replaced by its value
+pub fn format(label: Label, values: &[(&str, &str)]) -> String {
+    configured().format(label, values)
+}
+
+/// Returns the labels set by [`init`], or the English labels without a call.
+pub fn configured() -> Labels {
+    LABELS.get().cloned().unwrap_or_default()
+}
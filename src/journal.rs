@@ -0,0 +1,147 @@
+use crate::cli::Args;
+use crate::printer::{PrintTask, print_task};
+use crate::syslog::{RateLimit, SEVERITIES};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use regex::Regex;
+use serde_json::Value;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+
+/// Options for the journal follow mode.
+#[derive(ClapArgs)]
+pub struct JournalArgs {
+    /// Only print entries of this systemd unit, can be repeated
+    #[arg(short, long)]
+    pub unit: Vec<String>,
+
+    /// Least severe priority that is printed, or a range such as "crit..err"
+    #[arg(long, default_value = "err")]
+    pub priority: String,
+
+    /// Only print entries whose message matches this regular expression
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    /// Maximum number of slips per minute, further entries are dropped (0 for no limit)
+    #[arg(long, default_value = "10")]
+    pub max_per_minute: u32,
+}
+
+/// Follows the systemd journal and prints every matching entry as it appears.
+///
+/// The entries are read from `journalctl --follow`, so unit and priority filtering and
+/// the permissions to read the journal are those of `journalctl`. Entries written
+/// before the command started are not printed. Every entry is printed as a slip with
+/// priority and unit as title and the syslog identifier with the message as message.
+/// At most `max_per_minute` slips are printed, further entries are dropped and counted
+/// on the next slip. The function runs until the process is terminated.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `journal` - The journal mode options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - An error if the pattern is invalid, `journalctl` cannot be
+///   started or exits, print errors are logged
+pub fn run_journal(args: &Args, journal: &JournalArgs) -> EscposResult<()> {
+    let pattern = journal
+        .grep
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| PrinterError::Input(format!("invalid pattern: {}", e)))?;
+
+    let mut command = Command::new("journalctl");
+    command
+        .args(["--follow", "--lines=0", "--output=json"])
+        .arg(format!("--priority={}", journal.priority))
+        .stdout(Stdio::piped());
+    for unit in &journal.unit {
+        command.arg(format!("--unit={}", unit));
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| PrinterError::Io(format!("cannot run journalctl: {}", e)))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| PrinterError::Io("cannot read journalctl output".to_string()))?;
+    info!("Following the journal");
+
+    let mut limit = RateLimit::new(journal.max_per_minute);
+    for line in BufReader::new(stdout).lines() {
+        let line = line.map_err(|e| PrinterError::Io(format!("cannot read journal: {}", e)))?;
+        let Ok(entry) = serde_json::from_str::<Value>(&line) else {
+            warn!("Skipping invalid journal entry");
+            continue;
+        };
+        let Some(message) = field(&entry, "MESSAGE") else {
+            continue;
+        };
+        if pattern
+            .as_ref()
+            .is_some_and(|pattern| !pattern.is_match(&message))
+        {
+            continue;
+        }
+        if !limit.allow() {
+            warn!(
+                "Dropped journal entry, more than {} per minute",
+                journal.max_per_minute
+            );
+            continue;
+        }
+
+        let priority = field(&entry, "PRIORITY")
+            .and_then(|priority| priority.parse::<usize>().ok())
+            .and_then(|priority| SEVERITIES.get(priority))
+            .map_or("LOG".to_string(), |name| name.to_uppercase());
+        let title = match field(&entry, "_SYSTEMD_UNIT") {
+            Some(unit) => format!("{} {}", priority, unit.trim_end_matches(".service")),
+            None => priority,
+        };
+        let message = match field(&entry, "SYSLOG_IDENTIFIER") {
+            Some(identifier) => format!("{}: {}", identifier, message.trim()),
+            None => message.trim().to_string(),
+        };
+        let result = print_task(PrintTask {
+            title: Some(title),
+            message: limit.annotate(message),
+            address: args.address.clone(),
+            port: args.port,
+            codepage: args.codepage.clone(),
+            ..Default::default()
+        });
+        if let Err(e) = result {
+            error!("Failed to print journal entry: {:?}", e);
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| PrinterError::Io(format!("cannot wait for journalctl: {}", e)))?;
+    Err(PrinterError::Io(format!(
+        "journalctl exited with {}",
+        status
+    )))
+}
+
+/// Reads a field of a journal entry. Fields with binary content are exported as an
+/// array of bytes and decoded lossily.
+fn field(entry: &Value, name: &str) -> Option<String> {
+    match entry.get(name)? {
+        Value::String(value) => Some(value.clone()),
+        Value::Array(bytes) => {
+            let bytes: Vec<u8> = bytes
+                .iter()
+                .filter_map(|byte| byte.as_u64().map(|byte| byte as u8))
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        }
+        _ => None,
+    }
+}
@@ -1,18 +1,79 @@
 use clap::Parser;
 use escpos::errors::Result as EscposResult;
+use log::error;
 
+mod ansi;
 mod api;
+mod barcode;
+mod capabilities;
 mod cli;
+mod client;
+mod compose;
+mod config;
+mod counters;
+mod dbus;
+mod digest;
+mod filters;
+mod fortune;
+mod git_hook;
+mod graphql;
+mod grpc;
+mod hooks;
+mod hyphenate;
+mod i18n;
+mod journal;
+mod matrix;
+mod metrics;
+mod mqtt;
+mod paper;
+mod pdf;
+mod placeholders;
+mod plugins;
+mod pomodoro;
 mod printer;
+mod puzzle;
+mod queue;
+mod quiet_hours;
+mod raster;
+mod redact;
+mod replay;
+mod scheduler;
+mod spool;
+mod summary;
+mod syslog;
+mod table;
+mod tail;
+mod templates;
+mod typography;
+mod watch;
+mod weather;
+mod webhooks;
 
 use api::start_api_server;
-use cli::{Args, run_cli_print};
+use cli::{Args, Command, run_cli_print};
+use compose::run_compose;
+use config::Config;
+use fortune::run_fortune;
+use git_hook::run_git_hook;
+use journal::run_journal;
+use matrix::run_matrix;
+use pomodoro::run_pomodoro;
+use printer::{set_defaults, set_output, set_profiles};
+use puzzle::run_puzzle;
+use queue::run_queue;
+use replay::run_replay;
+use syslog::run_syslog;
+use tail::run_tail;
+use watch::run_watch;
+use weather::run_weather;
 
 /// Main entry point of the application.
 ///
-/// Parses command line arguments and either starts an API server or runs a CLI print job.
-/// When the `--api` flag is provided, starts an HTTP server on the specified port.
-/// Otherwise, executes a single print job with the provided arguments.
+/// Parses command line arguments, registers the defaults, printer profiles, hooks,
+/// labels, content filters and plugins of the configuration and either starts an API
+/// server, runs a subcommand or runs a CLI print job. When the `--api` flag is provided,
+/// starts an HTTP server on the specified port. When a subcommand is given, runs that
+/// mode. Otherwise, executes a single print job with the provided arguments.
 ///
 /// # Returns
 ///
@@ -20,16 +81,57 @@ use cli::{Args, run_cli_print};
 fn main() -> EscposResult<()> {
     env_logger::builder()
         .filter_level(log::LevelFilter::Info)
+        // zbus logs every D-Bus message and handshake step at info level.
+        .filter_module("zbus", log::LevelFilter::Warn)
+        .filter_module("tracing::span", log::LevelFilter::Warn)
         .init();
     let args = Args::parse();
+    let config = Config::load(args.config.as_deref()).unwrap_or_else(|e| {
+        error!("{}", e);
+        std::process::exit(1);
+    });
+    set_profiles(config.printers.clone());
+    hooks::init(config.hooks.clone().unwrap_or_default());
+    if let Err(e) = set_defaults(config.defaults.clone().unwrap_or_default())
+        .and_then(|()| i18n::init(config.language.as_deref(), &config.labels))
+        .and_then(|()| filters::init(&config.filters))
+        .and_then(|()| plugins::init(&config.plugins))
+        .and_then(|()| capabilities::init(&config.models, &config.printers))
+        .and_then(|()| args.output.as_deref().map_or(Ok(()), set_output))
+    {
+        error!("{}", e);
+        std::process::exit(1);
+    }
 
     if args.api {
         let rt = tokio::runtime::Runtime::new().unwrap();
+        let bind = if args.api_bind.is_empty() {
+            config.api.clone().unwrap_or_default().bind
+        } else {
+            args.api_bind.clone()
+        };
         rt.block_on(async {
-            start_api_server(args.api_port).await.unwrap();
+            start_api_server(args.api_port, &bind, config)
+                .await
+                .unwrap();
         });
         Ok(())
     } else {
-        run_cli_print(args)
+        match args.command {
+            Some(Command::Pomodoro(ref pomodoro)) => run_pomodoro(&args, pomodoro),
+            Some(Command::Fortune(ref fortune)) => run_fortune(&args, fortune),
+            Some(Command::Weather(ref weather)) => run_weather(&args, weather),
+            Some(Command::Puzzle(ref puzzle)) => run_puzzle(&args, puzzle),
+            Some(Command::GitHook(ref hook)) => run_git_hook(&args, hook),
+            Some(Command::Matrix(ref matrix)) => run_matrix(&args, matrix),
+            Some(Command::Syslog(ref syslog)) => run_syslog(&args, syslog),
+            Some(Command::Journal(ref journal)) => run_journal(&args, journal),
+            Some(Command::Tail(ref tail)) => run_tail(&args, tail, &config),
+            Some(Command::Compose(ref compose)) => run_compose(&args, compose),
+            Some(Command::Queue(ref queue)) => run_queue(&args, queue),
+            Some(Command::Watch(ref watch)) => run_watch(&args, watch),
+            Some(Command::Replay(ref replay)) => run_replay(&args, replay),
+            None => run_cli_print(args),
+        }
     }
 }
@@ -0,0 +1,262 @@
+use crate::cli::Args;
+use crate::config::{Config, MatrixConfig};
+use crate::printer::{PrintTask, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+
+/// How long the homeserver may hold a sync request open, in milliseconds.
+const SYNC_TIMEOUT_MS: u64 = 30_000;
+
+/// Delay before retrying after a failed sync request.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Options for the Matrix bot mode.
+#[derive(ClapArgs)]
+pub struct MatrixArgs {
+    /// Only print messages that mention the bot, overriding the configuration
+    #[arg(long)]
+    pub mentions_only: bool,
+}
+
+#[derive(Deserialize)]
+struct WhoAmI {
+    user_id: String,
+}
+
+#[derive(Deserialize)]
+struct JoinedRoom {
+    room_id: String,
+}
+
+#[derive(Deserialize)]
+struct SyncResponse {
+    next_batch: String,
+    #[serde(default)]
+    rooms: SyncRooms,
+}
+
+#[derive(Default, Deserialize)]
+struct SyncRooms {
+    #[serde(default)]
+    join: HashMap<String, RoomTimeline>,
+}
+
+#[derive(Deserialize)]
+struct RoomTimeline {
+    #[serde(default)]
+    timeline: Timeline,
+}
+
+#[derive(Default, Deserialize)]
+struct Timeline {
+    #[serde(default)]
+    events: Vec<RoomEvent>,
+}
+
+#[derive(Deserialize)]
+struct RoomEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    sender: String,
+    #[serde(default)]
+    content: Value,
+}
+
+/// Runs a Matrix client that joins the configured room and prints incoming messages.
+///
+/// The bot logs in with the access token from the `[matrix]` configuration section,
+/// joins the room and then long-polls the homeserver's sync endpoint. Messages already
+/// in the room when the bot starts are skipped, as are the bot's own messages. With
+/// `mentions_only` set, only messages mentioning the bot's user ID or localpart are
+/// printed. The function runs until the process is terminated.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `matrix` - The Matrix mode options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Only returns on a configuration or login error, since the
+///   sync loop retries network failures and logs print errors
+pub fn run_matrix(args: &Args, matrix: &MatrixArgs) -> EscposResult<()> {
+    let config = Config::load(args.config.as_deref())
+        .map_err(|e| PrinterError::Input(e.to_string()))?
+        .matrix
+        .ok_or_else(|| {
+            PrinterError::Input("Matrix mode requires a [matrix] config section".to_string())
+        })?;
+    let mentions_only = matrix.mentions_only || config.mentions_only;
+
+    let client = Client::new(&config);
+    let user_id = client.whoami()?;
+    let room_id = client.join(&config.room)?;
+    info!("Joined {} as {}", room_id, user_id);
+
+    let mut since = client.sync(None, &room_id)?.next_batch;
+    loop {
+        let response = match client.sync(Some(&since), &room_id) {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("{}, retrying in {}s", e, RETRY_DELAY.as_secs());
+                thread::sleep(RETRY_DELAY);
+                continue;
+            }
+        };
+        since = response.next_batch;
+
+        let Some(room) = response.rooms.join.get(&room_id) else {
+            continue;
+        };
+        for event in &room.timeline.events {
+            let Some(body) = message_body(event, &user_id) else {
+                continue;
+            };
+            if mentions_only && !mentions(event, body, &user_id) {
+                continue;
+            }
+            let result = print_task(PrintTask {
+                title: Some(localpart(&event.sender).to_uppercase()),
+                message: body.to_string(),
+                address: args.address.clone(),
+                port: args.port,
+                codepage: args.codepage.clone(),
+                ..Default::default()
+            });
+            if let Err(e) = result {
+                error!("Failed to print message from {}: {:?}", event.sender, e);
+            }
+        }
+    }
+}
+
+/// Minimal client for the parts of the Matrix client-server API the bot needs.
+struct Client {
+    agent: ureq::Agent,
+    homeserver: String,
+    authorization: String,
+}
+
+impl Client {
+    fn new(config: &MatrixConfig) -> Self {
+        Client {
+            agent: ureq::AgentBuilder::new()
+                .timeout_read(Duration::from_millis(SYNC_TIMEOUT_MS + 30_000))
+                .build(),
+            homeserver: config.homeserver.trim_end_matches('/').to_string(),
+            authorization: format!("Bearer {}", config.access_token),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/_matrix/client/v3{}", self.homeserver, path)
+    }
+
+    fn whoami(&self) -> EscposResult<String> {
+        let response: WhoAmI = self
+            .agent
+            .get(&self.url("/account/whoami"))
+            .set("Authorization", &self.authorization)
+            .call()
+            .map_err(|e| PrinterError::Io(format!("Matrix login failed: {}", e)))?
+            .into_json()
+            .map_err(|e| {
+                PrinterError::InvalidResponse(format!("invalid whoami response: {}", e))
+            })?;
+        Ok(response.user_id)
+    }
+
+    /// Joins a room by ID or alias and returns the room ID. Joining a room the bot is
+    /// already a member of is a no-op on the homeserver.
+    fn join(&self, room: &str) -> EscposResult<String> {
+        let response: JoinedRoom = self
+            .agent
+            .post(&self.url(&format!("/join/{}", encode_path_segment(room))))
+            .set("Authorization", &self.authorization)
+            .send_json(serde_json::json!({}))
+            .map_err(|e| PrinterError::Io(format!("cannot join Matrix room {}: {}", room, e)))?
+            .into_json()
+            .map_err(|e| PrinterError::InvalidResponse(format!("invalid join response: {}", e)))?;
+        Ok(response.room_id)
+    }
+
+    /// Fetches new message events of a single room. Without a `since` token the call
+    /// returns immediately and only serves to obtain the starting position.
+    fn sync(&self, since: Option<&str>, room_id: &str) -> EscposResult<SyncResponse> {
+        let filter = serde_json::json!({
+            "presence": { "types": [] },
+            "account_data": { "types": [] },
+            "room": {
+                "rooms": [room_id],
+                "state": { "types": [] },
+                "ephemeral": { "types": [] },
+                "account_data": { "types": [] },
+                "timeline": { "types": ["m.room.message"], "limit": 50 },
+            },
+        });
+        let mut request = self
+            .agent
+            .get(&self.url("/sync"))
+            .set("Authorization", &self.authorization)
+            .query("filter", &filter.to_string());
+        if let Some(since) = since {
+            request = request
+                .query("since", since)
+                .query("timeout", &SYNC_TIMEOUT_MS.to_string());
+        }
+        request
+            .call()
+            .map_err(|e| PrinterError::Io(format!("Matrix sync failed: {}", e)))?
+            .into_json()
+            .map_err(|e| PrinterError::InvalidResponse(format!("invalid sync response: {}", e)))
+    }
+}
+
+/// Returns the text of a message event, skipping the bot's own messages and
+/// non-text message types such as images or reactions.
+fn message_body<'a>(event: &'a RoomEvent, user_id: &str) -> Option<&'a str> {
+    if event.kind != "m.room.message" || event.sender == user_id {
+        return None;
+    }
+    match event.content["msgtype"].as_str() {
+        Some("m.text" | "m.notice" | "m.emote") => event.content["body"].as_str(),
+        _ => None,
+    }
+}
+
+/// Checks whether a message is addressed to the bot, either through the intentional
+/// mentions metadata or a plain text mention of its user ID or localpart.
+fn mentions(event: &RoomEvent, body: &str, user_id: &str) -> bool {
+    let tagged = event.content["m.mentions"]["user_ids"]
+        .as_array()
+        .is_some_and(|ids| ids.iter().any(|id| id.as_str() == Some(user_id)));
+    let body = body.to_lowercase();
+    tagged
+        || body.contains(&user_id.to_lowercase())
+        || body.contains(&localpart(user_id).to_lowercase())
+}
+
+/// Extracts the localpart of a user ID, e.g. `alice` from `@alice:example.org`.
+fn localpart(user_id: &str) -> &str {
+    let name = user_id.trim_start_matches('@');
+    name.split(':').next().unwrap_or(name)
+}
+
+/// Percent-encodes a room ID or alias for use as a URL path segment.
+fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,318 @@
+use crate::paper;
+use crate::printer::resolve_printer;
+use async_graphql::SimpleObject;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds in seconds of the latency histogram buckets.
+const BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Metrics per printer, keyed by `host:port`.
+static METRICS: Mutex<BTreeMap<String, PrinterMetrics>> = Mutex::new(BTreeMap::new());
+
+/// Jobs rejected because the queue was full.
+static REJECTED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Default)]
+struct PrinterMetrics {
+    connect: Histogram,
+    connect_errors: u64,
+    print: Histogram,
+    printed: u64,
+    failed: u64,
+    paper_mm: u64,
+}
+
+/// Cumulative latency histogram in the Prometheus format.
+#[derive(Default)]
+struct Histogram {
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        for (count, bound) in self.counts.iter_mut().zip(BUCKETS) {
+            if seconds <= bound {
+                *count += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn write(&self, out: &mut String, name: &str, printer: &str) {
+        for (count, bound) in self.counts.iter().zip(BUCKETS) {
+            let _ = writeln!(
+                out,
+                "{}_bucket{{printer=\"{}\",le=\"{}\"}} {}",
+                name, printer, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{printer=\"{}\",le=\"+Inf\"}} {}",
+            name, printer, self.count
+        );
+        let _ = writeln!(out, "{}_sum{{printer=\"{}\"}} {}", name, printer, self.sum);
+        let _ = writeln!(
+            out,
+            "{}_count{{printer=\"{}\"}} {}",
+            name, printer, self.count
+        );
+    }
+}
+
+fn update(printer: &str, change: impl FnOnce(&mut PrinterMetrics)) {
+    let mut metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    change(metrics.entry(printer.to_string()).or_default());
+}
+
+/// Returns the label identifying a printer, with the same defaults as the print functions.
+///
+/// # Arguments
+///
+/// * `address` - The printer address, defaults to the configured printer
+/// * `port` - The printer port, defaults to the configured port
+///
+/// # Returns
+///
+/// * `String` - The printer as `host:port`
+pub fn printer_label(address: Option<&str>, port: Option<u16>) -> String {
+    let (address, port) = resolve_printer(address, port);
+    format!("{}:{}", address, port)
+}
+
+/// Splits a printer given as `host:port` into address and port, the inverse of
+/// [`printer_label`]. Without a port, the default port is used.
+///
+/// # Arguments
+///
+/// * `printer` - The printer as `host` or `host:port`
+///
+/// # Returns
+///
+/// * `(String, Option<u16>)` - The address and, if given, the port
+pub fn parse_printer(printer: &str) -> (String, Option<u16>) {
+    match printer.rsplit_once(':') {
+        Some((host, port)) if port.parse::<u16>().is_ok() => (host.to_string(), port.parse().ok()),
+        _ => (printer.to_string(), None),
+    }
+}
+
+/// Records an attempt to connect to a printer.
+///
+/// # Arguments
+///
+/// * `printer` - The printer label, see [`printer_label`]
+/// * `duration` - Time until the connection was established or failed
+/// * `success` - Whether the connection was established
+pub fn record_connect(printer: &str, duration: Duration, success: bool) {
+    update(printer, |metrics| {
+        if success {
+            metrics.connect.observe(duration);
+        } else {
+            metrics.connect_errors += 1;
+        }
+    });
+}
+
+/// Records a print job sent to a printer.
+///
+/// # Arguments
+///
+/// * `printer` - The printer label, see [`printer_label`]
+/// * `duration` - Time from connecting until the job was printed or failed
+/// * `success` - Whether the job was printed
+pub fn record_print(printer: &str, duration: Duration, success: bool) {
+    update(printer, |metrics| {
+        if success {
+            metrics.print.observe(duration);
+            metrics.printed += 1;
+        } else {
+            metrics.failed += 1;
+        }
+    });
+}
+
+/// Records the estimated paper of a printed job.
+///
+/// # Arguments
+///
+/// * `printer` - The printer label, see [`printer_label`]
+/// * `mm` - The paper length in millimeters
+pub fn record_paper(printer: &str, mm: u64) {
+    update(printer, |metrics| metrics.paper_mm += mm);
+}
+
+/// Returns the estimated paper printed on all printers since the server started.
+///
+/// # Returns
+///
+/// * `u64` - The paper length in millimeters
+pub fn paper_total_mm() -> u64 {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    metrics.values().map(|metrics| metrics.paper_mm).sum()
+}
+
+/// Records a job rejected because the queue was full.
+pub fn record_rejected() {
+    REJECTED.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Totals of a printer since the server started.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct PrinterStats {
+    /// The printer as `host:port`.
+    pub printer: String,
+    /// Queued jobs printed successfully.
+    pub printed: u64,
+    /// Queued jobs that failed to print.
+    pub failed: u64,
+    /// Failed connection attempts.
+    pub connect_errors: u64,
+    /// Average time to print a job in seconds, including the connect.
+    pub average_print_seconds: Option<f64>,
+    /// Estimated paper printed in millimeters.
+    pub paper_mm: u64,
+}
+
+/// Returns the totals of every printer used since the server started.
+///
+/// # Returns
+///
+/// * `Vec<PrinterStats>` - The totals, ordered by printer
+pub fn printers() -> Vec<PrinterStats> {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    metrics
+        .iter()
+        .map(|(printer, metrics)| PrinterStats {
+            printer: printer.clone(),
+            printed: metrics.printed,
+            failed: metrics.failed,
+            connect_errors: metrics.connect_errors,
+            average_print_seconds: (metrics.print.count > 0)
+                .then(|| metrics.print.sum / metrics.print.count as f64),
+            paper_mm: metrics.paper_mm,
+        })
+        .collect()
+}
+
+/// Renders all metrics in the Prometheus text exposition format.
+///
+/// # Returns
+///
+/// * `String` - The metrics, one series per printer and the rejected jobs
+pub fn render() -> String {
+    let metrics = METRICS.lock().unwrap_or_else(|e| e.into_inner());
+    let printers: Vec<(String, &PrinterMetrics)> = metrics
+        .iter()
+        .map(|(printer, metrics)| (escape(printer), metrics))
+        .collect();
+    let mut out = String::new();
+
+    out.push_str("# HELP taskprinter_print_jobs_total Print jobs sent to the printer by result.\n");
+    out.push_str("# TYPE taskprinter_print_jobs_total counter\n");
+    for (printer, metrics) in &printers {
+        for (result, count) in [("printed", metrics.printed), ("failed", metrics.failed)] {
+            let _ = writeln!(
+                out,
+                "taskprinter_print_jobs_total{{printer=\"{}\",result=\"{}\"}} {}",
+                printer, result, count
+            );
+        }
+    }
+
+    out.push_str("# HELP taskprinter_connect_errors_total Failed connection attempts.\n");
+    out.push_str("# TYPE taskprinter_connect_errors_total counter\n");
+    for (printer, metrics) in &printers {
+        let _ = writeln!(
+            out,
+            "taskprinter_connect_errors_total{{printer=\"{}\"}} {}",
+            printer, metrics.connect_errors
+        );
+    }
+
+    out.push_str("# HELP taskprinter_connect_duration_seconds Time to connect to the printer.\n");
+    out.push_str("# TYPE taskprinter_connect_duration_seconds histogram\n");
+    for (printer, metrics) in &printers {
+        metrics
+            .connect
+            .write(&mut out, "taskprinter_connect_duration_seconds", printer);
+    }
+
+    out.push_str("# HELP taskprinter_print_duration_seconds Time to print a job.\n");
+    out.push_str("# TYPE taskprinter_print_duration_seconds histogram\n");
+    for (printer, metrics) in &printers {
+        metrics
+            .print
+            .write(&mut out, "taskprinter_print_duration_seconds", printer);
+    }
+
+    out.push_str("# HELP taskprinter_paper_used_millimeters_total Estimated paper printed.\n");
+    out.push_str("# TYPE taskprinter_paper_used_millimeters_total counter\n");
+    for (printer, metrics) in &printers {
+        let _ = writeln!(
+            out,
+            "taskprinter_paper_used_millimeters_total{{printer=\"{}\"}} {}",
+            printer, metrics.paper_mm
+        );
+    }
+
+    out.push_str(
+        "# HELP taskprinter_paper_roll_remaining_millimeters Estimated paper left on the roll.\n",
+    );
+    out.push_str("# TYPE taskprinter_paper_roll_remaining_millimeters gauge\n");
+    for roll in paper::rolls().unwrap_or_default() {
+        if let Some(remaining) = roll.remaining_mm {
+            let _ = writeln!(
+                out,
+                "taskprinter_paper_roll_remaining_millimeters{{printer=\"{}\"}} {}",
+                escape(&roll.printer),
+                remaining
+            );
+        }
+    }
+
+    out.push_str(
+        "# HELP taskprinter_rejected_jobs_total Jobs rejected because the queue was full.\n",
+    );
+    out.push_str("# TYPE taskprinter_rejected_jobs_total counter\n");
+    let _ = writeln!(
+        out,
+        "taskprinter_rejected_jobs_total {}",
+        REJECTED.load(Ordering::Relaxed)
+    );
+    out
+}
+
+/// Escapes a label value for the Prometheus text format.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Metrics endpoint handler.
+///
+/// This function handles GET requests to `/metrics` for scraping by Prometheus.
+///
+/// # Returns
+///
+/// * `impl IntoResponse` - The metrics in the Prometheus text format
+pub async fn metrics_handler() -> impl IntoResponse {
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        render(),
+    )
+}
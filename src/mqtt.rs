@@ -0,0 +1,147 @@
+use crate::config::MqttConfig;
+use crate::metrics;
+use crate::printer::{PaperStatus, PrinterStatus, query_status};
+use crate::queue::JobQueue;
+use log::{debug, info, warn};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_stream::StreamExt;
+
+/// Number of messages buffered while the broker is unreachable.
+const CAPACITY: usize = 64;
+
+/// Seconds to wait before reconnecting to the broker.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Starts publishing job and printer status events to an MQTT broker.
+///
+/// Every job status change is published as JSON to the job topic. The status of the
+/// configured printers is checked between jobs and published as retained messages
+/// whenever it changes, so Home Assistant can show e.g. "printer out of paper" as an
+/// entity. A printer that cannot be reached or does not answer the status query is
+/// reported `offline`.
+///
+/// Messages are dropped while the broker is unreachable and the buffer is full, the
+/// connection is retried in the background.
+///
+/// # Arguments
+///
+/// * `config` - The broker and topic settings
+/// * `queue` - The print queue of the API server
+pub fn start(config: MqttConfig, queue: Arc<JobQueue>) {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    if let Some(username) = &config.username {
+        options.set_credentials(username, config.password.clone().unwrap_or_default());
+    }
+    let (client, mut events) = AsyncClient::new(options, CAPACITY);
+
+    let broker = format!("{}:{}", config.host, config.port);
+    tokio::spawn(async move {
+        let mut connected = false;
+        loop {
+            match events.poll().await {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker {}", broker);
+                    connected = true;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    if connected {
+                        warn!("Lost connection to MQTT broker {}: {}", broker, e);
+                    } else {
+                        debug!("Cannot connect to MQTT broker {}: {}", broker, e);
+                    }
+                    connected = false;
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            }
+        }
+    });
+
+    tokio::spawn(publish_jobs(
+        client.clone(),
+        config.job_topic.clone(),
+        Arc::clone(&queue),
+    ));
+    tokio::spawn(publish_printer_status(client, config, queue));
+}
+
+/// Publishes every job status change to the job topic.
+async fn publish_jobs(client: AsyncClient, topic: String, queue: Arc<JobQueue>) {
+    loop {
+        // The event stream ends when this publisher falls behind, so subscribe again.
+        let mut events = queue.events(None);
+        while let Some(event) = events.next().await {
+            match event {
+                Ok(job) => match serde_json::to_vec(&job) {
+                    Ok(payload) => publish(&client, &topic, false, payload),
+                    Err(e) => warn!("Cannot encode job {}: {}", job.id, e),
+                },
+                Err(missed) => warn!("MQTT publisher missed {} job events", missed),
+            }
+        }
+    }
+}
+
+/// Checks the status of the configured printers and publishes it when it changes.
+async fn publish_printer_status(client: AsyncClient, config: MqttConfig, queue: Arc<JobQueue>) {
+    let mut last: HashMap<String, Option<PrinterStatus>> = HashMap::new();
+    let printers = match config.printers.as_slice() {
+        [] => vec![metrics::printer_label(None, None)],
+        printers => printers.to_vec(),
+    };
+    let mut interval = tokio::time::interval(Duration::from_secs(config.status_interval.max(1)));
+    loop {
+        interval.tick().await;
+        for printer in &printers {
+            let (address, port) = metrics::parse_printer(printer);
+            let label = metrics::printer_label(Some(&address), port);
+            let queue = Arc::clone(&queue);
+            let status = tokio::task::spawn_blocking(move || {
+                queue.between_jobs(|| query_status(Some(&address), port))
+            })
+            .await;
+            let status = match status {
+                Ok(Ok(status)) => Some(status),
+                Ok(Err(e)) => {
+                    debug!("Status query of printer {} failed: {}", label, e);
+                    None
+                }
+                Err(e) => {
+                    warn!("Status query of printer {} panicked: {}", label, e);
+                    None
+                }
+            };
+            if last.get(&label) == Some(&status) {
+                continue;
+            }
+
+            let online = status.is_some_and(|status| status.online);
+            let topic = config.status_topic.replace("{printer}", &label);
+            publish(
+                &client,
+                &topic,
+                true,
+                if online { "online" } else { "offline" },
+            );
+            if let Some(status) = status {
+                if status.paper == PaperStatus::Out {
+                    warn!("Printer {} is out of paper", label);
+                }
+                let topic = config.paper_topic.replace("{printer}", &label);
+                publish(&client, &topic, true, status.paper.as_str());
+            }
+            last.insert(label, status);
+        }
+    }
+}
+
+/// Queues a message for the broker, dropping it if the buffer is full.
+fn publish(client: &AsyncClient, topic: &str, retain: bool, payload: impl Into<Vec<u8>>) {
+    if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, retain, payload) {
+        debug!("Dropped MQTT message to {}: {}", topic, e);
+    }
+}
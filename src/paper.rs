@@ -0,0 +1,130 @@
+use crate::counters::{self, with_store};
+use crate::metrics::{self, parse_printer, printer_label};
+use crate::printer::{profile, resolve_printer};
+use axum::{extract::Path, http::StatusCode, response::Json};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Returns the path of the file the paper used since the last roll change is persisted
+/// in, keyed by printer label.
+fn paper_path() -> PathBuf {
+    counters::state_dir().join("paper.json")
+}
+
+/// Records the estimated paper of a printed job.
+///
+/// The length is added to the paper metrics of the server and to the paper used since
+/// the roll of the printer was changed, which survives restarts.
+///
+/// # Arguments
+///
+/// * `printer` - The printer label, see [`printer_label`]
+/// * `mm` - The estimated paper length in millimeters
+pub fn record(printer: &str, mm: u64) {
+    metrics::record_paper(printer, mm);
+    let result = with_store(paper_path(), |used| {
+        let value = used.entry(printer.to_string()).or_insert(0);
+        *value = value.saturating_add(mm);
+    });
+    if let Err(e) = result {
+        warn!("Cannot record paper usage of {}: {}", printer, e);
+    }
+}
+
+/// Paper used on the current roll of a printer.
+#[derive(Debug, Clone, Serialize)]
+pub struct RollStatus {
+    /// The printer as `host:port`.
+    pub printer: String,
+    /// Estimated paper used since the roll was changed, in millimeters.
+    pub used_mm: u64,
+    /// Length of a full roll in millimeters, from the `roll_length` of the printer profile.
+    pub roll_length_mm: Option<u64>,
+    /// Estimated paper left on the roll in millimeters, if the roll length is known.
+    pub remaining_mm: Option<u64>,
+}
+
+impl RollStatus {
+    fn new(printer: String, used_mm: u64) -> RollStatus {
+        let (address, port) = parse_printer(&printer);
+        let (address, port) = resolve_printer(Some(&address), port);
+        let roll_length_mm = profile(&address, port)
+            .roll_length
+            .map(|meters| (meters * 1000.0).round() as u64);
+        RollStatus {
+            printer,
+            used_mm,
+            roll_length_mm,
+            remaining_mm: roll_length_mm.map(|length| length.saturating_sub(used_mm)),
+        }
+    }
+}
+
+/// Returns the paper used on the current roll of every printer that printed since its
+/// roll was changed.
+///
+/// # Returns
+///
+/// * `EscposResult<Vec<RollStatus>>` - The rolls ordered by printer, or an I/O error if
+///   the paper file cannot be read
+pub fn rolls() -> EscposResult<Vec<RollStatus>> {
+    let used = with_store(paper_path(), |used| used.clone())?;
+    Ok(used
+        .into_iter()
+        .map(|(printer, used_mm)| RollStatus::new(printer, used_mm))
+        .collect())
+}
+
+/// Starts counting the paper of a printer from zero, after its roll was changed.
+///
+/// # Arguments
+///
+/// * `printer` - The printer as `host` or `host:port`
+///
+/// # Returns
+///
+/// * `EscposResult<RollStatus>` - The status of the new roll, or an I/O error if the
+///   paper file cannot be written
+pub fn reset(printer: &str) -> EscposResult<RollStatus> {
+    let (address, port) = parse_printer(printer);
+    let printer = printer_label(Some(&address), port);
+    with_store(paper_path(), |used| {
+        used.insert(printer.clone(), 0);
+    })?;
+    info!("Paper roll of {} changed", printer);
+    Ok(RollStatus::new(printer, 0))
+}
+
+/// Paper roll list endpoint handler.
+///
+/// This function handles GET requests to `/paper`.
+///
+/// # Returns
+///
+/// * `Result<Json<Vec<RollStatus>>, StatusCode>` - The paper used and left per printer,
+///   or HTTP 500 if the paper file cannot be read
+pub async fn list_rolls_handler() -> Result<Json<Vec<RollStatus>>, StatusCode> {
+    rolls().map(Json).map_err(paper_error)
+}
+
+/// Paper roll reset endpoint handler.
+///
+/// This function handles POST requests to `/paper/{printer}/reset`, to be called after
+/// the roll of the printer was changed.
+///
+/// # Returns
+///
+/// * `Result<Json<RollStatus>, StatusCode>` - The status of the new roll, or HTTP 500 if
+///   the paper file cannot be written
+pub async fn reset_roll_handler(
+    Path(printer): Path<String>,
+) -> Result<Json<RollStatus>, StatusCode> {
+    reset(&printer).map(Json).map_err(paper_error)
+}
+
+fn paper_error(e: PrinterError) -> StatusCode {
+    error!("Paper error: {}", e);
+    StatusCode::INTERNAL_SERVER_ERROR
+}
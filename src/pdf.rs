@@ -0,0 +1,93 @@
+use crate::raster::{Bitmap, Dither};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Rasterizes the first pages of a PDF document for printing.
+///
+/// Pages are rendered in grayscale with `pdftoppm` from poppler-utils, scaled to the
+/// given width in dots and dithered. The document is written to a temporary directory
+/// that is removed again afterwards.
+///
+/// # Arguments
+///
+/// * `pdf` - The PDF document
+/// * `pages` - Number of pages to render, starting at the first page
+/// * `width` - Width of the rendered pages in dots
+/// * `dither` - The dithering method
+///
+/// # Returns
+///
+/// * `EscposResult<Vec<Bitmap>>` - One bitmap per rendered page, an input error if the
+///   document cannot be rendered, or an I/O error if `pdftoppm` is not installed
+pub fn rasterize_pdf(
+    pdf: &[u8],
+    pages: u32,
+    width: u32,
+    dither: Dither,
+) -> EscposResult<Vec<Bitmap>> {
+    if !pdf.starts_with(b"%PDF") {
+        return Err(PrinterError::Input("not a PDF document".to_string()));
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let dir =
+        std::env::temp_dir().join(format!("taskprinter-pdf-{}-{}", std::process::id(), nanos));
+    fs::create_dir_all(&dir).map_err(|e| PrinterError::Io(e.to_string()))?;
+
+    let result = render_pages(&dir, pdf, pages.max(1), width, dither);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn render_pages(
+    dir: &Path,
+    pdf: &[u8],
+    pages: u32,
+    width: u32,
+    dither: Dither,
+) -> EscposResult<Vec<Bitmap>> {
+    let input = dir.join("input.pdf");
+    fs::write(&input, pdf).map_err(|e| PrinterError::Io(e.to_string()))?;
+
+    let output = Command::new("pdftoppm")
+        .args(["-gray", "-png", "-f", "1", "-l", &pages.to_string()])
+        .args(["-scale-to-x", &width.to_string(), "-scale-to-y", "-1"])
+        .arg(&input)
+        .arg(dir.join("page"))
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => {
+                PrinterError::Io("pdftoppm not found, install poppler-utils".to_string())
+            }
+            _ => PrinterError::Io(format!("cannot run pdftoppm: {}", e)),
+        })?;
+    if !output.status.success() {
+        return Err(PrinterError::Input(format!(
+            "cannot render PDF: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    // pdftoppm zero-pads the page numbers to the same width, so the names sort by page.
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| PrinterError::Io(e.to_string()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    files.sort();
+
+    files
+        .iter()
+        .map(|file| {
+            let bytes = fs::read(file).map_err(|e| PrinterError::Io(e.to_string()))?;
+            Bitmap::decode(&bytes, width, dither)
+        })
+        .collect()
+}
@@ -0,0 +1,114 @@
+use crate::counters;
+use chrono::Local;
+use escpos::errors::Result as EscposResult;
+use std::collections::HashMap;
+
+/// Expands placeholder variables at print time.
+///
+/// Supported placeholders are `{date}`, `{time}`, `{weekday}`, `{hostname}` and
+/// `{counter:name}`. A counter is incremented once per expander, so the same counter
+/// used in several fields of one task shows the same number. Unknown placeholders and
+/// other braces are kept as they are.
+pub struct Expander {
+    now: chrono::DateTime<Local>,
+    counters: HashMap<String, u64>,
+}
+
+impl Expander {
+    /// Creates an expander that uses the current local time for all fields.
+    pub fn new() -> Self {
+        Expander {
+            now: Local::now(),
+            counters: HashMap::new(),
+        }
+    }
+
+    /// Expands all placeholders in a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text containing placeholders
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<String>` - The expanded text, or an I/O error if a counter cannot
+    ///   be persisted
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use taskprinter::placeholders::Expander;
+    ///
+    /// let text = Expander::new().expand("Plan for {weekday} {date}")?;
+    /// ```
+    pub fn expand(&mut self, text: &str) -> EscposResult<String> {
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+
+        while let Some(start) = rest.find('{') {
+            output.push_str(&rest[..start]);
+            let candidate = &rest[start + 1..];
+            let value = match candidate.find(['}', '{']) {
+                Some(end) if candidate[end..].starts_with('}') => {
+                    self.value(&candidate[..end])?.map(|value| (value, end))
+                }
+                _ => None,
+            };
+            match value {
+                Some((value, end)) => {
+                    output.push_str(&value);
+                    rest = &candidate[end + 1..];
+                }
+                None => {
+                    output.push('{');
+                    rest = candidate;
+                }
+            }
+        }
+        output.push_str(rest);
+
+        Ok(output)
+    }
+
+    /// Returns the value of a placeholder name, or `None` if it is unknown.
+    fn value(&mut self, name: &str) -> EscposResult<Option<String>> {
+        let value = match name.trim() {
+            "date" => self.now.format("%d/%m/%Y").to_string(),
+            "time" => self.now.format("%H:%M").to_string(),
+            "weekday" => self.now.format("%A").to_string(),
+            "hostname" => hostname(),
+            name => match name.strip_prefix("counter:").map(str::trim) {
+                Some(counter) if !counter.is_empty() => {
+                    let value = match self.counters.get(counter) {
+                        Some(value) => *value,
+                        None => {
+                            let value = counters::increment(counter)?;
+                            self.counters.insert(counter.to_string(), value);
+                            value
+                        }
+                    };
+                    value.to_string()
+                }
+                _ => return Ok(None),
+            },
+        };
+        Ok(Some(value))
+    }
+}
+
+impl Default for Expander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the host name of the machine running the printer service.
+fn hostname() -> String {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "localhost".to_string())
+}
@@ -0,0 +1,107 @@
+use crate::printer::PrintTask;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::info;
+use mlua::{Function, Lua, LuaSerdeExt, SerializeOptions, Value};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Loaded plugins from the configuration, see [`init`].
+static PLUGINS: OnceLock<Mutex<Vec<Plugin>>> = OnceLock::new();
+
+/// A Lua script with its own interpreter, so globals kept between jobs, such as counters,
+/// do not leak into other plugins.
+struct Plugin {
+    name: String,
+    lua: Lua,
+    hook: Function,
+}
+
+/// Loads the plugin scripts of the configuration.
+///
+/// Every script must define a global function `on_job(job)`. It receives the job as a
+/// table with the fields of [`PrintTask`] and returns the job to print, which may be
+/// changed or routed to another printer by setting `address` and `port`. Returning `nil`
+/// or `false`, optionally followed by a reason, rejects the job.
+///
+/// The plugins can only be loaded once, later calls are ignored.
+///
+/// # Arguments
+///
+/// * `paths` - Paths of the Lua scripts, in the order their hooks are called
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error if a script cannot be read, fails to
+///   run or defines no `on_job` function
+pub fn init(paths: &[String]) -> EscposResult<()> {
+    let plugins = paths
+        .iter()
+        .map(|path| load(path))
+        .collect::<EscposResult<Vec<_>>>()?;
+    let _ = PLUGINS.set(Mutex::new(plugins));
+    Ok(())
+}
+
+/// Runs a plugin script and looks up its hook.
+fn load(path: &str) -> EscposResult<Plugin> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| PrinterError::Input(format!("cannot read plugin {}: {}", path, e)))?;
+    let name = Path::new(path).file_stem().map_or_else(
+        || path.to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    let lua = Lua::new();
+    lua.load(source)
+        .set_name(format!("@{}", path))
+        .exec()
+        .map_err(|e| PrinterError::Input(format!("cannot load plugin {}: {}", path, e)))?;
+    let hook = lua
+        .globals()
+        .get::<Function>("on_job")
+        .map_err(|_| PrinterError::Input(format!("plugin {} defines no on_job function", path)))?;
+    info!("Loaded plugin {}", name);
+    Ok(Plugin { name, lua, hook })
+}
+
+/// Passes a task through the `on_job` hooks of all plugins in order.
+///
+/// # Arguments
+///
+/// * `task` - The task to transform
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task returned by the last plugin, or an input error
+///   if a plugin rejected the job or failed
+pub fn apply(task: PrintTask) -> EscposResult<PrintTask> {
+    let Some(plugins) = PLUGINS.get() else {
+        return Ok(task);
+    };
+    let plugins = plugins.lock().unwrap_or_else(|e| e.into_inner());
+    plugins
+        .iter()
+        .try_fold(task, |task, plugin| plugin.call(task))
+}
+
+impl Plugin {
+    fn call(&self, task: PrintTask) -> EscposResult<PrintTask> {
+        let failed =
+            |e: mlua::Error| PrinterError::Input(format!("plugin {} failed: {}", self.name, e));
+        let options = SerializeOptions::new()
+            .serialize_none_to_null(false)
+            .serialize_unit_to_null(false);
+        let job = self.lua.to_value_with(&task, options).map_err(failed)?;
+        let (result, reason): (Value, Option<String>) = self.hook.call(job).map_err(failed)?;
+        match result {
+            Value::Nil | Value::Boolean(false) => Err(PrinterError::Input(format!(
+                "rejected by plugin {}{}",
+                self.name,
+                reason
+                    .map(|reason| format!(": {}", reason))
+                    .unwrap_or_default()
+            ))),
+            result => self.lua.from_value(result).map_err(failed),
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use crate::cli::Args;
+use crate::i18n::{self, Label};
+use crate::printer::{PrintTask, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::Result as EscposResult;
+use log::info;
+use std::thread;
+use std::time::Duration;
+
+/// Options for the pomodoro timer mode.
+#[derive(ClapArgs)]
+pub struct PomodoroArgs {
+    /// Length of a work phase in minutes
+    #[arg(long, default_value = "25")]
+    pub work: u64,
+
+    /// Length of a break in minutes
+    #[arg(long = "break", default_value = "5")]
+    pub break_minutes: u64,
+
+    /// Number of work phases before the session ends
+    #[arg(long, default_value = "4")]
+    pub cycles: u32,
+}
+
+/// Runs a pomodoro session, printing a slip at every phase transition.
+///
+/// Each cycle consists of a work phase followed by a break. A slip is printed when a
+/// work phase starts, when a break starts and once the whole session is finished.
+/// The function blocks for the full duration of the session.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `pomodoro` - The pomodoro timing options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once the session is complete, or an ESC/POS error on failure
+pub fn run_pomodoro(args: &Args, pomodoro: &PomodoroArgs) -> EscposResult<()> {
+    for phase in phases(pomodoro.work, pomodoro.break_minutes, pomodoro.cycles) {
+        print_phase(args, &phase.message)?;
+        sleep_minutes(phase.minutes);
+    }
+    Ok(())
+}
+
+/// A slip of a pomodoro session and the minutes until the next one.
+pub struct Phase {
+    pub message: String,
+    pub minutes: u64,
+}
+
+/// Returns the slips of a pomodoro session in order.
+///
+/// # Arguments
+///
+/// * `work` - Length of a work phase in minutes
+/// * `break_minutes` - Length of a break in minutes
+/// * `cycles` - Number of work phases
+///
+/// # Returns
+///
+/// * `Vec<Phase>` - A slip per work phase and break, followed by the slip ending the session
+pub fn phases(work: u64, break_minutes: u64, cycles: u32) -> Vec<Phase> {
+    let mut phases = Vec::new();
+    for cycle in 1..=cycles {
+        phases.push(Phase {
+            message: format!("Focus time - {} min\nCycle {} of {}", work, cycle, cycles),
+            minutes: work,
+        });
+        if cycle < cycles {
+            phases.push(Phase {
+                message: format!(
+                    "Break time - stretch\n{} min, then cycle {}",
+                    break_minutes,
+                    cycle + 1
+                ),
+                minutes: break_minutes,
+            });
+        }
+    }
+    phases.push(Phase {
+        message: format!("Session complete\n{} cycles done\nWell earned rest", cycles),
+        minutes: 0,
+    });
+    phases
+}
+
+/// Prints a single pomodoro slip with the printer settings from the command line.
+fn print_phase(args: &Args, message: &str) -> EscposResult<()> {
+    info!("Pomodoro: {}", message.replace('\n', " / "));
+    print_task(PrintTask {
+        title: Some(i18n::text(Label::Pomodoro)),
+        message: message.to_string(),
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        media: args.media.clone(),
+        label_length: args.label_length,
+        ..Default::default()
+    })
+}
+
+fn sleep_minutes(minutes: u64) {
+    thread::sleep(Duration::from_secs(minutes * 60));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternates_work_and_breaks() {
+        let phases = phases(25, 5, 2);
+        let minutes: Vec<u64> = phases.iter().map(|phase| phase.minutes).collect();
+        assert_eq!(minutes, [25, 5, 25, 0]);
+        assert_eq!(phases[0].message, "Focus time - 25 min\nCycle 1 of 2");
+        assert_eq!(
+            phases[1].message,
+            "Break time - stretch\n5 min, then cycle 2"
+        );
+        assert_eq!(
+            phases[3].message,
+            "Session complete\n2 cycles done\nWell earned rest"
+        );
+    }
+}
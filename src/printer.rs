@@ -1,29 +1,278 @@
-use chrono::Local;
+use crate::ansi::{self, Style};
+use crate::barcode::{self, Code2D, Symbology};
+use crate::capabilities;
+use crate::config::{Capabilities, Cutter, DefaultsConfig, HeaderStyle, PrinterProfile};
+use crate::filters;
+use crate::hooks;
+use crate::hyphenate::{self, WrappedLine};
+use crate::i18n::{self, Label, Labels};
+use crate::metrics;
+use crate::paper;
+use crate::placeholders::Expander;
+use crate::plugins;
+use crate::raster::Bitmap;
+use crate::typography;
+use chrono::{DateTime, Local};
 use escpos::printer::Printer;
 use escpos::printer_options::PrinterOptions;
 use escpos::utils::*;
-use escpos::{driver::*, errors::Result as EscposResult};
+use escpos::{
+    driver::*,
+    errors::{PrinterError, Result as EscposResult},
+};
+use hyphenation::Standard;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `GS FF`: feeds label or black mark paper to the print start position of the next label.
+const GS_FEED_TO_LABEL_START: [u8; 2] = [0x1D, 0x0C];
+
+/// Vertical resolution of common 80mm/58mm thermal printers (203 dpi).
+const DOTS_PER_MM: u32 = 8;
+
+/// Line pitch in dots used for double height text in label mode.
+const LABEL_LINE_DOTS: u8 = 48;
+
+/// Height of a line of double height text in dots, for the paper estimate.
+const DOUBLE_LINE_DOTS: u32 = 48;
+
+/// Height of a line of normal text and of a fed line in dots, the default line spacing.
+const LINE_DOTS: u32 = 30;
+
+/// Rough height of a QR code or barcode in dots, for the paper estimate.
+const CODE_DOTS: u32 = 200;
+
+/// Paper between the print head and the cutter in dots, which every cut slip starts with.
+const CUTTER_DOTS: u32 = 96;
+
+/// Number of spaces a tab expands to in code mode.
+const CODE_TAB_WIDTH: usize = 4;
+
+/// `ESC M n`: selects Font A (n = 0) or the smaller Font B (n = 1).
+const ESC_SELECT_FONT: [u8; 2] = [0x1B, 0x4D];
+
+/// `GS r 1`: transmits the paper sensor status. Unlike the real-time `DLE EOT` requests it
+/// is processed in order, so the answer arrives once everything sent before is printed.
+const GS_TRANSMIT_STATUS: [u8; 3] = [0x1D, 0x72, 0x01];
+
+/// Printer profiles from the configuration, keyed by printer label or address.
+static PROFILES: OnceLock<HashMap<String, PrinterProfile>> = OnceLock::new();
+
+/// Printer, codepage and title for tasks without them, see [`set_defaults`].
+static DEFAULTS: OnceLock<DefaultsConfig> = OnceLock::new();
+
+/// File capturing the printer commands of all print jobs, see [`set_output`].
+static OUTPUT: OnceLock<PathBuf> = OnceLock::new();
+
+/// How the message of a task is laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Large text in a box drawing frame with title and date.
+    Frame,
+    /// Small Font B text without wrapping, for stack traces and config snippets.
+    Code,
+    /// Centered name badge: the title in very large letters above the message.
+    Badge,
+}
+
+impl RenderMode {
+    /// Parses a rendering mode name (`frame`, `code`, `badge`), defaulting to the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The optional mode name, case-insensitive
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<RenderMode>` - The matching mode, or an input error for unknown
+    ///   names
+    pub fn parse(name: Option<&str>) -> EscposResult<RenderMode> {
+        match name.map(str::to_lowercase).as_deref() {
+            None | Some("frame") => Ok(RenderMode::Frame),
+            Some("code") => Ok(RenderMode::Code),
+            Some("badge") => Ok(RenderMode::Badge),
+            Some(other) => Err(PrinterError::Input(format!(
+                "unsupported rendering mode: {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Settings the rendering of a task takes from the configuration, see [`render_task`].
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptions {
+    /// Title of tasks without a title. Without one, such tasks are framed as notes.
+    pub title: Option<String>,
+    /// Codepage of tasks without a supported codepage. Defaults to PC850.
+    pub codepage: Option<String>,
+    /// Feed, cut and header settings of the printer.
+    pub profile: PrinterProfile,
+    /// Features of the printer model, see [`capabilities::of`].
+    pub capabilities: Capabilities,
+    /// Language and overrides of the labels printed on slips, e.g. the note title.
+    pub labels: Labels,
+}
+
+impl RenderOptions {
+    /// Returns the options the configured defaults and the profile of its printer give
+    /// a task, as used when printing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task, whose printer selects the profile
+    ///
+    /// # Returns
+    ///
+    /// * `RenderOptions` - The default title and codepage, the printer profile, the
+    ///   capabilities of its model and the configured labels
+    pub fn configured(task: &PrintTask) -> RenderOptions {
+        let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+        let profile = profile(&address, port);
+        RenderOptions {
+            title: defaults().title.clone(),
+            codepage: Some(defaults().codepage.clone()),
+            capabilities: capabilities::of(&profile),
+            profile,
+            labels: i18n::configured(),
+        }
+    }
+}
+
+/// Driver collecting the printer commands in memory instead of sending them, see
+/// [`render_task`].
+#[derive(Clone, Default)]
+struct MemoryDriver(Rc<RefCell<Vec<u8>>>);
+
+impl Driver for MemoryDriver {
+    fn name(&self) -> String {
+        "memory".to_string()
+    }
+
+    fn write(&self, data: &[u8]) -> EscposResult<()> {
+        self.0.borrow_mut().extend_from_slice(data);
+        Ok(())
+    }
+
+    fn read(&self, _buf: &mut [u8]) -> EscposResult<usize> {
+        Ok(0)
+    }
+
+    fn flush(&self) -> EscposResult<()> {
+        Ok(())
+    }
+}
+
+/// Paper type loaded in the printer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Media {
+    /// Continuous receipt paper, every slip is cut off.
+    Receipt,
+    /// Die-cut labels separated by gaps, detected by the printer's gap sensor.
+    Gap,
+    /// Paper with black marks on the back, detected by the printer's mark sensor.
+    BlackMark,
+}
+
+impl Media {
+    /// Parses a media name (`receipt`, `gap`, `mark`/`black-mark`), defaulting to receipt
+    /// paper.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The optional media name, case-insensitive
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Media>` - The matching media type, or an input error for unknown
+    ///   names
+    pub fn parse(name: Option<&str>) -> EscposResult<Media> {
+        match name.map(|n| n.to_lowercase().replace('_', "-")).as_deref() {
+            None | Some("receipt") => Ok(Media::Receipt),
+            Some("gap") | Some("label") => Ok(Media::Gap),
+            Some("mark") | Some("black-mark") | Some("blackmark") => Ok(Media::BlackMark),
+            Some(other) => Err(PrinterError::Input(format!(
+                "unsupported media type: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Whether the media consists of individual labels.
+    pub fn is_label(self) -> bool {
+        self != Media::Receipt
+    }
+}
 
 /// Represents a print task with all necessary configuration options.
 ///
 /// This struct contains all the information needed to print a message to an ESC/POS printer,
 /// including optional formatting, network settings, and encoding preferences.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PrintTask {
-    /// Optional title for the print job. Defaults to "INCOMMING MESSAGE" if not provided.
+    /// Optional title for the print job. Defaults to "NOTE" in the configured language if
+    /// not provided.
     pub title: Option<String>,
     /// The main message content to be printed.
     pub message: String,
     /// Optional date string. If not provided, uses current date in DD/MM/YYYY format.
     pub date: Option<String>,
-    /// Whether to encode the message as a QR code instead of plain text.
+    /// Whether to encode the message as a 2D code (see `symbology`) instead of plain text.
     pub encode: Option<bool>,
-    /// Network address of the printer. Defaults to "taskbob" if not provided.
+    /// Network address of the printer. Defaults to the configured printer, "taskbob".
     pub address: Option<String>,
-    /// Network port of the printer. Defaults to 9100 if not provided.
+    /// Network port of the printer. Defaults to the configured port, 9100.
     pub port: Option<u16>,
     /// Character encoding codepage. Supports PC850, ISO8859_15, WPC1252, PC437, ISO8859_7.
     pub codepage: Option<String>,
+    /// Optional barcode symbology (EAN13, EAN8, UPCA, UPCE, CODE39, ITF, CODE128). When
+    /// set, the message is printed as a barcode of that symbology.
+    pub barcode: Option<String>,
+    /// 2D symbology used when `encode` is true (QR, PDF417, DATAMATRIX). Defaults to QR.
+    pub symbology: Option<String>,
+    /// Whether to print every non-empty line of the message as its own slip with header
+    /// and cut.
+    pub split_items: Option<bool>,
+    /// Paper type: "receipt" (default), "gap" or "mark". Label media prints one label per
+    /// item.
+    pub media: Option<String>,
+    /// Label length in millimeters. Each slip is padded with paper feed to exactly this
+    /// length.
+    pub label_length: Option<u16>,
+    /// Optional URL printed as a QR code below the message of every slip.
+    pub link: Option<String>,
+    /// Optional data printed as a Code128 barcode below the message of every slip,
+    /// e.g. an order number.
+    pub footer_barcode: Option<String>,
+    /// Rendering mode: "frame" (default), "code" for stack traces and config snippets or
+    /// "badge" for name badges.
+    pub mode: Option<String>,
+    /// Language code used to word wrap and hyphenate the message, e.g. "de" or "en".
+    /// Without a language, lines are cut at the frame border.
+    pub hyphenate: Option<String>,
+    /// Whether to replace curly quotes, dashes, ellipses and similar characters with
+    /// codepage-safe equivalents before printing. Defaults to true.
+    pub normalize: Option<bool>,
+    /// Whether to collapse runs of blank lines in the message into one. Defaults to false.
+    pub squeeze_blank: Option<bool>,
+    /// Whether to remove whitespace at the end of every message line. Defaults to false.
+    pub trim_trailing: Option<bool>,
+    /// Whether to remove the indentation shared by all message lines. Defaults to false.
+    pub dedent: Option<bool>,
+    /// Styling of the title row of framed slips. Defaults to the header of the printer
+    /// profile, or the plain double height title row.
+    #[serde(default)]
+    pub header: Option<HeaderStyle>,
+    /// Optional name of the sender, printed as "From: ..." below the message.
+    pub from: Option<String>,
 }
 
 /// Prints a task to an ESC/POS printer over the network.
@@ -42,11 +291,28 @@ pub struct PrintTask {
 ///
 /// # Behavior
 ///
+/// - Applies the content filters of the configuration, skipping jobs a drop filter matches
+/// - Passes the task through the `on_job` hooks of the configured Lua plugins, failing if
+///   a plugin rejects it
+/// - Runs the configured `before` and `after` commands around the job, see [`hooks::run`]
+/// - Normalizes typographic characters of title and message unless `normalize` is false
+/// - Removes the common indentation, trailing whitespace and repeated blank lines of the
+///   message if `dedent`, `trim_trailing` and `squeeze_blank` are set
+/// - Prints the message with [`print_code`] instead if `mode` is "code", or with
+///   [`print_badge`] if it is "badge"
 /// - Sets up printer with specified codepage and formatting options
-/// - Prints a header with title and date in reverse/bold text
-/// - If `encode` is true, prints the message as a QR code
+/// - Prints a header with title and date, reversed, bold or in single height as set in
+///   `header` or the printer profile
 /// - If `encode` is false or None, prints the message as large text
-/// - Performs a cut operation after printing
+/// - Word wraps and hyphenates the message if `hyphenate` names a language
+/// - If `split_items` is true or label media is used, prints each line of the message
+///   as a separate slip
+/// - Prints the sender given in `from` right-aligned below the message if set
+/// - Prints `link` as a QR code below the message if set
+/// - Prints `footer_barcode` as a Code128 barcode below the message if set
+/// - Pads each slip to `label_length` millimeters if set
+/// - Performs a cut operation after each slip on receipt paper, or feeds to the start
+///   of the next label on gap/black mark media
 ///
 /// # Examples
 ///
@@ -61,34 +327,133 @@ pub struct PrintTask {
 ///     address: Some("192.168.1.100".to_string()),
 ///     port: Some(9100),
 ///     codepage: Some("PC850".to_string()),
+///     header: None, // Header style of the printer profile
+///     ..Default::default()
 /// };
 ///
 /// print_task(task).expect("Failed to print");
 /// ```
 pub fn print_task(task: PrintTask) -> EscposResult<()> {
-    let address = task
-        .address
-        .clone()
-        .unwrap_or_else(|| "taskbob".to_string());
-    let port = task.port.unwrap_or(9100);
-    let codepage = match task.codepage.as_deref() {
-        Some("PC850") => PageCode::PC850,
-        Some("ISO8859_15") => PageCode::ISO8859_15,
-        Some("WPC1252") => PageCode::WPC1252,
-        Some("PC437") => PageCode::PC437,
-        Some("ISO8859_7") => PageCode::ISO8859_7,
-        _ => PageCode::PC850,
+    let Some(task) = filters::apply(expand_placeholders(task)?) else {
+        info!("Dropped a job matching a content filter");
+        return Ok(());
     };
+    let task = compress_whitespace(normalize_typography(plugins::apply(task)?));
+    if let Some(data) = &task.footer_barcode {
+        barcode::validate(Symbology::Code128, data)?;
+    }
+    let mode = RenderMode::parse(task.mode.as_deref())?;
+    hooks::run(&task.clone(), || match mode {
+        RenderMode::Frame => send(&task, write_frame),
+        RenderMode::Code => print_code(task),
+        RenderMode::Badge => print_badge(task),
+    })
+}
 
-    let driver = NetworkDriver::open(&address, port, None)?;
-    let mut binding = Printer::new(driver, Protocol::default(), Some(PrinterOptions::default()));
-    let message;
-
-    if task.title.is_some() {
-        message = generate_task_string(&task);
-    } else {
-        message = generate_note_string(&task);
+/// Renders a task into the ESC/POS commands `print_task` sends to the printer.
+///
+/// The rendering is pure: the configuration is taken from `options` and the date of
+/// slips without one from `now`, so the same task always renders to the same bytes,
+/// e.g. for snapshot tests of layouts. Typography and whitespace are normalized like when
+/// printing, but placeholders, content filters, plugins and hooks are left out.
+///
+/// # Arguments
+///
+/// * `task` - The task to render, in the mode set in `mode`
+/// * `options` - The configuration to render with, see [`RenderOptions::configured`]
+/// * `now` - The time used for the date of slips without a `date`
+///
+/// # Returns
+///
+/// * `EscposResult<Vec<u8>>` - The printer commands, or an input error for an invalid
+///   mode, media or footer barcode
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{Local, TimeZone};
+/// use taskprinter::printer::{PrintTask, RenderOptions, render_task};
+///
+/// let task = PrintTask {
+///     title: Some("Alert".to_string()),
+///     message: "System backup completed".to_string(),
+///     ..Default::default()
+/// };
+/// let now = Local.with_ymd_and_hms(2025, 3, 14, 9, 0, 0).unwrap();
+/// let bytes = render_task(&task, &RenderOptions::default(), now).expect("Failed to render");
+/// assert_eq!(bytes, render_task(&task, &RenderOptions::default(), now).unwrap());
+/// ```
+pub fn render_task(
+    task: &PrintTask,
+    options: &RenderOptions,
+    now: DateTime<Local>,
+) -> EscposResult<Vec<u8>> {
+    let task = compress_whitespace(normalize_typography(task.clone()));
+    if let Some(data) = &task.footer_barcode {
+        barcode::validate(Symbology::Code128, data)?;
     }
+    let driver = MemoryDriver::default();
+    let mut printer = Printer::new(
+        driver.clone(),
+        Protocol::default(),
+        Some(PrinterOptions::default()),
+    );
+    match RenderMode::parse(task.mode.as_deref())? {
+        RenderMode::Frame => write_frame(&mut printer, &task, options, now)?,
+        RenderMode::Code => write_code(&mut printer, &task, options, now)?,
+        RenderMode::Badge => write_badge(&mut printer, &task, options, now)?,
+    };
+    printer.print()?;
+    Ok(driver.0.take())
+}
+
+/// Renders a task with one of the `write_*` functions, sends it to its printer and
+/// records the paper used.
+fn send(
+    task: &PrintTask,
+    write: fn(
+        &mut Printer<Connection>,
+        &PrintTask,
+        &RenderOptions,
+        DateTime<Local>,
+    ) -> EscposResult<u32>,
+) -> EscposResult<()> {
+    let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+    let options = RenderOptions::configured(task);
+    let driver = open(&address, port)?;
+    let mut printer = Printer::new(
+        driver.clone(),
+        Protocol::default(),
+        Some(PrinterOptions::default()),
+    );
+    let dots = write(&mut printer, task, &options, Local::now())?;
+    printer.print()?;
+    record_paper(&driver, &address, port, dots);
+    Ok(())
+}
+
+/// Writes the message in a box drawing frame with title and date, see [`print_task`].
+///
+/// Returns the estimated paper of the slips in dots.
+fn write_frame<D: Driver>(
+    binding: &mut Printer<D>,
+    task: &PrintTask,
+    options: &RenderOptions,
+    now: DateTime<Local>,
+) -> EscposResult<u32> {
+    let codepage = resolve_codepage(task, options);
+    let media = Media::parse(task.media.as_deref())?;
+    let dictionary = task.hyphenate.as_deref().map(hyphenate::load).transpose()?;
+    let scale = frame_scale(&options.capabilities);
+
+    let slips: Vec<(String, String, Vec<Style>)> = split_task(task)
+        .into_iter()
+        .map(|slip| {
+            let (message, styles) = ansi::parse(&slip.message);
+            let slip = PrintTask { message, ..slip };
+            (generate_frame(&slip, options, now), slip.message, styles)
+        })
+        .collect();
 
     let printer = binding
         .debug_mode(Some(DebugMode::Dec))
@@ -97,92 +462,1132 @@ pub fn print_task(task: PrintTask) -> EscposResult<()> {
         .smoothing(true)?
         .justify(JustifyMode::LEFT)?
         .reverse(false)?
-        .size(2, 2)?
-        .writeln(&message)?
-        .feed()?;
+        .size(scale, 2)?;
 
-    printer.print_cut()?;
-    Ok(())
+    if task.label_length.is_some() {
+        printer.line_spacing(LABEL_LINE_DOTS)?;
+    }
+
+    let header = header_style(task, options);
+    let mut dots = 0;
+    for (message, text, styles) in &slips {
+        write_styled_frame(
+            printer,
+            message,
+            text,
+            styles,
+            header,
+            scale,
+            dictionary.as_ref(),
+        )?;
+
+        if let Some(from) = &task.from {
+            printer
+                .justify(JustifyMode::RIGHT)?
+                .writeln(&sender_line(from, &options.labels))?
+                .justify(JustifyMode::LEFT)?;
+        }
+
+        write_codes(printer, task, &options.capabilities)?;
+
+        if let Some(length) = task.label_length {
+            let printed = (message.lines().count() as u32) * LABEL_LINE_DOTS as u32;
+            feed_dots(
+                printer,
+                (length as u32 * DOTS_PER_MM).saturating_sub(printed),
+            )?;
+        }
+
+        let mut slip_dots =
+            message.lines().count() as u32 * DOUBLE_LINE_DOTS + footer_dots(task, DOUBLE_LINE_DOTS);
+        if !header.double_height {
+            slip_dots -= DOUBLE_LINE_DOTS - LINE_DOTS;
+        }
+        if let Some(length) = task.label_length {
+            slip_dots = slip_dots.max(length as u32 * DOTS_PER_MM);
+        }
+        if media.is_label() {
+            printer.custom(&GS_FEED_TO_LABEL_START)?;
+        } else {
+            feed_and_cut(printer, options, false)?;
+            slip_dots += cut_dots(&options.profile);
+        }
+        dots += slip_dots;
+    }
+    Ok(dots)
 }
 
-/// Prints a QR code representation of the message to an ESC/POS printer over the network.
-/// This function connects to a network printer and prints the provided task as a QR code.
-/// It handles character encoding and establishes the printer connection using TCP/IP.
-/// 
+/// Prints the message as a code block.
+///
+/// The message is printed in the small Font B, one source line per printed line with
+/// leading whitespace preserved and tabs expanded. Lines longer than the Font B columns
+/// of the printer model are not word wrapped but cut, with a `\` marking every cut, so
+/// indentation and alignment stay intact. The title, if set, is printed in bold above the
+/// code.
+///
 /// # Arguments
-/// 
+///
 /// * `task` - A `PrintTask` containing all the print job configuration
-/// 
+///
 /// # Returns
-/// 
+///
 /// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
-pub fn print_qr_code(task: PrintTask) -> EscposResult<()> {
-    let address = task
-        .address
-        .clone()
-        .unwrap_or_else(|| "taskbob".to_string());
-    let port = task.port.unwrap_or(9100);
-    let codepage = match task.codepage.as_deref() {
-        Some("PC850") => PageCode::PC850,
-        Some("ISO8859_15") => PageCode::ISO8859_15,
-        Some("WPC1252") => PageCode::WPC1252,
-        Some("PC437") => PageCode::PC437,
-        Some("ISO8859_7") => PageCode::ISO8859_7,
-        _ => PageCode::PC850,
+pub fn print_code(task: PrintTask) -> EscposResult<()> {
+    send(&task, write_code)
+}
+
+/// Writes the message as a code block, see [`print_code`].
+///
+/// Returns the estimated paper of the slip in dots.
+fn write_code<D: Driver>(
+    binding: &mut Printer<D>,
+    task: &PrintTask,
+    options: &RenderOptions,
+    _now: DateTime<Local>,
+) -> EscposResult<u32> {
+    let codepage = resolve_codepage(task, options);
+    let printer = binding
+        .debug_mode(Some(DebugMode::Dec))
+        .init()?
+        .page_code(codepage)?
+        .justify(JustifyMode::LEFT)?;
+
+    let columns = options.capabilities.font_b_columns.max(2);
+    if let Some(title) = &task.title {
+        printer
+            .bold(true)?
+            .writeln(title)?
+            .bold(false)?
+            .writeln(&"─".repeat(columns))?;
+    }
+
+    printer.custom(&[ESC_SELECT_FONT[0], ESC_SELECT_FONT[1], 1])?;
+    let lines = code_lines(&ansi::strip(&task.message), columns);
+    for line in &lines {
+        printer.writeln(line)?;
+    }
+    printer.custom(&[ESC_SELECT_FONT[0], ESC_SELECT_FONT[1], 0])?;
+
+    if let Some(from) = &task.from {
+        printer
+            .justify(JustifyMode::RIGHT)?
+            .writeln(&sender_line(from, &options.labels))?
+            .justify(JustifyMode::LEFT)?;
+    }
+
+    write_codes(printer, task, &options.capabilities)?;
+
+    feed_and_cut(printer, options, false)?;
+
+    let title_lines = if task.title.is_some() { 2 } else { 0 };
+    Ok((title_lines + lines.len() as u32) * LINE_DOTS
+        + footer_dots(task, LINE_DOTS)
+        + cut_dots(&options.profile))
+}
+
+/// Splits code into printed lines of at most `columns` characters.
+///
+/// Tabs are expanded to the next tab stop. Lines that do not fit are cut one character
+/// before the limit and marked with a trailing `\`, the rest continues on the next line.
+fn code_lines(code: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for line in code.lines() {
+        let mut chars: Vec<char> = Vec::new();
+        for c in line.chars() {
+            if c == '\t' {
+                let spaces = CODE_TAB_WIDTH - chars.len() % CODE_TAB_WIDTH;
+                chars.extend(std::iter::repeat_n(' ', spaces));
+            } else {
+                chars.push(c);
+            }
+        }
+
+        let mut rest = &chars[..];
+        while rest.len() > columns {
+            let (head, tail) = rest.split_at(columns - 1);
+            lines.push(format!("{}\\", head.iter().collect::<String>()));
+            rest = tail;
+        }
+        lines.push(rest.iter().collect());
+    }
+    lines
+}
+
+/// Expands placeholders such as `{weekday}` or `{counter:name}` in the title, message,
+/// date, link and footer barcode of a task, see [`Expander`].
+fn expand_placeholders(task: PrintTask) -> EscposResult<PrintTask> {
+    let mut expander = Expander::new();
+    Ok(PrintTask {
+        title: task
+            .title
+            .as_deref()
+            .map(|t| expander.expand(t))
+            .transpose()?,
+        message: expander.expand(&task.message)?,
+        date: task
+            .date
+            .as_deref()
+            .map(|d| expander.expand(d))
+            .transpose()?,
+        link: task
+            .link
+            .as_deref()
+            .map(|l| expander.expand(l))
+            .transpose()?,
+        footer_barcode: task
+            .footer_barcode
+            .as_deref()
+            .map(|b| expander.expand(b))
+            .transpose()?,
+        ..task
+    })
+}
+
+/// Prints a name badge.
+///
+/// The title is printed centered in bold at the largest text size that fits its longest
+/// word on a Font A line of the printer model, followed by the message (e.g. the
+/// affiliation) in smaller letters and the link as QR code, for example to a profile
+/// page. Without a title, the message is used as name. The badge is finished with a
+/// partial cut, so a stack of badges stays together until torn off.
+///
+/// # Arguments
+///
+/// * `task` - A `PrintTask` containing all the print job configuration
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn print_badge(task: PrintTask) -> EscposResult<()> {
+    send(&task, write_badge)
+}
+
+/// Writes a name badge, see [`print_badge`].
+///
+/// Returns the estimated paper of the badge in dots.
+fn write_badge<D: Driver>(
+    binding: &mut Printer<D>,
+    task: &PrintTask,
+    options: &RenderOptions,
+    _now: DateTime<Local>,
+) -> EscposResult<u32> {
+    let (name, affiliation) = match &task.title {
+        Some(title) => (ansi::strip(title), ansi::strip(&task.message)),
+        None => (ansi::strip(&task.message), String::new()),
     };
+    let longest_word = name
+        .split_whitespace()
+        .map(|word| word.chars().count())
+        .max()
+        .unwrap_or(0);
+    let columns = options.capabilities.font_a_columns;
+    let size = (2..=4)
+        .rev()
+        .find(|size| longest_word <= columns / *size as usize)
+        .unwrap_or(2);
 
-    let driver = NetworkDriver::open(&address, port, None)?;
-    let mut binding = Printer::new(driver, Protocol::default(), Some(PrinterOptions::default()));
+    let codepage = resolve_codepage(task, options);
     let printer = binding
         .debug_mode(Some(DebugMode::Dec))
         .init()?
         .page_code(codepage)?
         .smoothing(true)?
         .justify(JustifyMode::CENTER)?
-        .reverse(false)?
-        .qrcode(&task.message)?
-        .feed()?;
+        .feed()?
+        .bold(true)?
+        .size(size, size)?;
+
+    let name_lines = word_wrap(&name, columns / size as usize, "");
+    for line in &name_lines {
+        printer.writeln(line)?;
+    }
+    printer.bold(false)?.size(2, 2)?;
+    let mut dots = LINE_DOTS + name_lines.len() as u32 * 24 * size as u32;
 
-    printer.print_cut()?;
+    if !affiliation.trim().is_empty() {
+        printer.feed()?;
+        dots += LINE_DOTS;
+        for paragraph in affiliation.lines() {
+            for line in word_wrap(paragraph, columns / 2, "") {
+                printer.writeln(&line)?;
+                dots += DOUBLE_LINE_DOTS;
+            }
+        }
+    }
+
+    if let Some(link) = &task.link {
+        printer.feed()?;
+        if options.capabilities.codes_2d {
+            printer.qrcode(link)?;
+            dots += LINE_DOTS + CODE_DOTS;
+        } else {
+            printer.writeln(link)?;
+            dots += LINE_DOTS + DOUBLE_LINE_DOTS;
+        }
+    }
+
+    feed_and_cut(printer, options, true)?;
+    Ok(dots + cut_dots(&options.profile))
+}
+
+/// Word wraps a paragraph with a hanging indent.
+///
+/// The first line starts with `prefix`, e.g. a list number, and the following lines are
+/// indented by its width so the text stays aligned. Words longer than a line are cut.
+///
+/// # Arguments
+///
+/// * `text` - The paragraph; line breaks and runs of whitespace are treated as one space
+/// * `width` - The maximum number of characters per line, including the prefix
+/// * `prefix` - Text put in front of the first line
+///
+/// # Returns
+///
+/// * `Vec<String>` - The wrapped lines, at least one
+pub(crate) fn word_wrap(text: &str, width: usize, prefix: &str) -> Vec<String> {
+    let indent = prefix.chars().count();
+    let width = width.saturating_sub(indent).max(1);
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let used = line.chars().count();
+        if used > 0 && used + 1 + word.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > width {
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| match index {
+            0 => format!("{}{}", prefix, line),
+            _ => format!("{}{}", " ".repeat(indent), line),
+        })
+        .collect()
+}
+
+/// Applies [`typography::normalize`] to the title, message and sender unless the task
+/// disables it.
+fn normalize_typography(task: PrintTask) -> PrintTask {
+    if task.normalize == Some(false) {
+        return task;
+    }
+    PrintTask {
+        title: task.title.as_deref().map(typography::normalize),
+        message: typography::normalize(&task.message),
+        from: task.from.as_deref().map(typography::normalize),
+        ..task
+    }
+}
+
+/// Applies the whitespace options `dedent`, `trim_trailing` and `squeeze_blank` of the
+/// task to its message, so log output does not waste paper on empty space.
+fn compress_whitespace(task: PrintTask) -> PrintTask {
+    let mut message = task.message;
+    if task.dedent == Some(true) {
+        message = typography::dedent(&message);
+    }
+    if task.trim_trailing == Some(true) {
+        message = typography::trim_trailing_whitespace(&message);
+    }
+    if task.squeeze_blank == Some(true) {
+        message = typography::squeeze_blank_lines(&message);
+    }
+    PrintTask { message, ..task }
+}
+
+/// Formats the attribution line printed below the message.
+fn sender_line(from: &str, labels: &Labels) -> String {
+    labels.format(Label::From, &[("name", ansi::strip(from).trim())])
+}
+
+/// Connection print jobs are sent over: a printer, or the output file capturing their
+/// commands, see [`set_output`].
+#[derive(Clone)]
+enum Connection {
+    Printer(NetworkDriver),
+    Output(FileDriver),
+}
+
+impl Driver for Connection {
+    fn name(&self) -> String {
+        match self {
+            Connection::Printer(driver) => driver.name(),
+            Connection::Output(driver) => driver.name(),
+        }
+    }
+
+    fn write(&self, data: &[u8]) -> EscposResult<()> {
+        match self {
+            Connection::Printer(driver) => driver.write(data),
+            Connection::Output(driver) => driver.write(data),
+        }
+    }
+
+    fn read(&self, buf: &mut [u8]) -> EscposResult<usize> {
+        match self {
+            Connection::Printer(driver) => driver.read(buf),
+            Connection::Output(driver) => driver.read(buf),
+        }
+    }
+
+    fn flush(&self) -> EscposResult<()> {
+        match self {
+            Connection::Printer(driver) => driver.flush(),
+            Connection::Output(driver) => driver.flush(),
+        }
+    }
+}
+
+/// Opens the connection a print job is sent over: the output file if one is set,
+/// otherwise the network connection to the printer.
+fn open(address: &str, port: u16) -> EscposResult<Connection> {
+    match OUTPUT.get() {
+        Some(path) => FileDriver::open(path).map(Connection::Output),
+        None => connect(address, port).map(Connection::Printer),
+    }
+}
+
+/// Opens the network connection to a printer, recording the connect latency in the
+/// printer metrics.
+fn connect(address: &str, port: u16) -> EscposResult<NetworkDriver> {
+    let started = Instant::now();
+    let driver = NetworkDriver::open(address, port, None);
+    metrics::record_connect(
+        &metrics::printer_label(Some(address), Some(port)),
+        started.elapsed(),
+        driver.is_ok(),
+    );
+    driver
+}
+
+/// Writes the printer commands of all following print jobs to a file instead of sending
+/// them to the printers.
+///
+/// The file is created or truncated, every job then appends its exact byte stream, which
+/// can be sent to a printer later with [`replay`]. Printer status queries still use the
+/// network. The paper estimates of captured jobs are neither added to the roll of the
+/// printer nor to the paper metrics. The output can only be set once, later calls are
+/// ignored.
+///
+/// # Arguments
+///
+/// * `path` - The file to write the commands to
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an I/O error if the file cannot be created
+pub fn set_output(path: &str) -> EscposResult<()> {
+    File::create(path).map_err(|e| PrinterError::Io(format!("cannot create {}: {}", path, e)))?;
+    let _ = OUTPUT.set(PathBuf::from(path));
+    Ok(())
+}
+
+/// Sends a previously captured byte stream to a printer unchanged, see [`set_output`].
+///
+/// # Arguments
+///
+/// * `address` - The printer host, or None for the default printer
+/// * `port` - The printer port, or None for the default port
+/// * `data` - The printer commands to send
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once the data is sent, or an I/O error on failure
+pub fn replay(address: Option<&str>, port: Option<u16>, data: &[u8]) -> EscposResult<()> {
+    let (address, port) = resolve_printer(address, port);
+    let driver = connect(&address, port)?;
+    driver.write(data)?;
+    driver.flush()?;
+    info!("Replayed {} bytes on {}:{}", data.len(), address, port);
+    Ok(())
+}
+
+/// Registers the printer, codepage and title used for tasks without them.
+///
+/// The defaults can only be registered once, later calls are ignored. Without a call,
+/// tasks are printed on `taskbob:9100` with the PC850 codepage.
+///
+/// # Arguments
+///
+/// * `defaults` - The `[defaults]` section of the configuration
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for an unsupported codepage
+pub fn set_defaults(defaults: DefaultsConfig) -> EscposResult<()> {
+    if page_code(&defaults.codepage).is_none() {
+        return Err(PrinterError::Input(format!(
+            "unsupported default codepage: {}",
+            defaults.codepage
+        )));
+    }
+    let _ = DEFAULTS.set(defaults);
     Ok(())
 }
 
+/// Returns the registered defaults, or the built-in ones.
+fn defaults() -> &'static DefaultsConfig {
+    DEFAULTS.get_or_init(DefaultsConfig::default)
+}
+
+/// Returns the printer of a task, filling in the configured default address and port.
+///
+/// # Arguments
+///
+/// * `address` - The printer address of the task, if any
+/// * `port` - The printer port of the task, if any
+///
+/// # Returns
+///
+/// * `(String, u16)` - The address and port to connect to
+pub fn resolve_printer(address: Option<&str>, port: Option<u16>) -> (String, u16) {
+    let defaults = defaults();
+    (
+        address.unwrap_or(&defaults.address).to_string(),
+        port.unwrap_or(defaults.port),
+    )
+}
+
+/// Registers the printer profiles of the configuration, see [`PrinterProfile`].
+///
+/// Profiles can only be registered once, later calls are ignored. Printers without a
+/// profile use the default feed.
+///
+/// # Arguments
+///
+/// * `profiles` - The profiles keyed by printer label, see [`metrics::printer_label`]
+pub fn set_profiles(profiles: HashMap<String, PrinterProfile>) {
+    let _ = PROFILES.set(profiles);
+}
+
+/// Returns the registered profile of a printer, or the default profile.
+pub(crate) fn profile(address: &str, port: u16) -> PrinterProfile {
+    PROFILES
+        .get()
+        .and_then(|profiles| {
+            profiles
+                .get(&metrics::printer_label(Some(address), Some(port)))
+                .or_else(|| profiles.get(address))
+        })
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Feeds the paper as configured in the printer profile and cuts it between the feeds.
+///
+/// Partial cuts are replaced by full cuts on printers that only cut fully, and printers
+/// without a cutter only feed the paper to be torn off.
+fn feed_and_cut<D: Driver>(
+    printer: &mut Printer<D>,
+    options: &RenderOptions,
+    partial: bool,
+) -> EscposResult<()> {
+    let profile = &options.profile;
+    if profile.feed_before_cut > 0 {
+        printer.feeds(profile.feed_before_cut)?;
+    }
+    match options.capabilities.cutter {
+        Cutter::None => {}
+        Cutter::Partial if partial => {
+            printer.partial_cut()?;
+        }
+        Cutter::Full | Cutter::Partial => {
+            printer.cut()?;
+        }
+    }
+    if profile.feed_after_cut > 0 {
+        printer.feeds(profile.feed_after_cut)?;
+    }
+    Ok(())
+}
+
+/// Writes the link of a task as a QR code and its footer barcode as a Code128 barcode,
+/// centered below the message, or as text on printers that cannot print them.
+fn write_codes<D: Driver>(
+    printer: &mut Printer<D>,
+    task: &PrintTask,
+    capabilities: &Capabilities,
+) -> EscposResult<()> {
+    if let Some(link) = &task.link {
+        printer.justify(JustifyMode::CENTER)?;
+        if capabilities.codes_2d {
+            printer.qrcode(link)?;
+        } else {
+            printer.writeln(link)?;
+        }
+        printer.justify(JustifyMode::LEFT)?;
+    }
+
+    if let Some(data) = &task.footer_barcode {
+        printer.justify(JustifyMode::CENTER)?;
+        if capabilities.barcodes {
+            printer.custom(&barcode::code128_command(data))?;
+        } else {
+            printer.writeln(data)?;
+        }
+        printer.justify(JustifyMode::LEFT)?;
+    }
+    Ok(())
+}
+
+/// Estimates the paper below the message of a slip: sender line, link QR code and
+/// footer barcode.
+fn footer_dots(task: &PrintTask, line_dots: u32) -> u32 {
+    [
+        task.from.as_ref().map(|_| line_dots),
+        task.link.as_ref().map(|_| CODE_DOTS),
+        task.footer_barcode.as_ref().map(|_| CODE_DOTS),
+    ]
+    .into_iter()
+    .flatten()
+    .sum()
+}
+
+/// Estimates the paper fed around the cut of a slip.
+fn cut_dots(profile: &PrinterProfile) -> u32 {
+    (u32::from(profile.feed_before_cut) + u32::from(profile.feed_after_cut)) * LINE_DOTS
+        + CUTTER_DOTS
+}
+
+/// Records the estimated paper of a printed job, see [`paper::record`]. Jobs written to
+/// the output file instead of the printer used no paper and are not recorded.
+fn record_paper(connection: &Connection, address: &str, port: u16, dots: u32) {
+    if let Connection::Output(_) = connection {
+        return;
+    }
+    let printer = metrics::printer_label(Some(address), Some(port));
+    paper::record(&printer, u64::from(dots.div_ceil(DOTS_PER_MM)));
+}
+
+/// Feeds the paper by the given number of dots using `ESC J`, which accepts at most 255
+/// per command.
+fn feed_dots<D: Driver>(printer: &mut Printer<D>, mut dots: u32) -> EscposResult<()> {
+    while dots > 0 {
+        let step = dots.min(255);
+        printer.custom(&[0x1B, 0x4A, step as u8])?;
+        dots -= step;
+    }
+    Ok(())
+}
+
+/// Splits a task into the slips that should be printed.
+///
+/// When `split_items` is enabled or the task targets label media, every non-empty line
+/// of the message becomes its own task sharing the title, date and printer settings of
+/// the original. Otherwise the task is returned unchanged as a single slip.
+///
+/// # Arguments
+///
+/// * `task` - A reference to the `PrintTask` to split
+///
+/// # Returns
+///
+/// * `Vec<PrintTask>` - One task per slip to print
+pub fn split_task(task: &PrintTask) -> Vec<PrintTask> {
+    let labels = Media::parse(task.media.as_deref()).is_ok_and(Media::is_label);
+    if task.split_items != Some(true) && !labels {
+        return vec![task.clone()];
+    }
+
+    task.message
+        .lines()
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| PrintTask {
+            message: item.to_string(),
+            ..task.clone()
+        })
+        .collect()
+}
+
+/// Prints the message as a 2D code to an ESC/POS printer over the network.
+/// This function connects to a network printer and prints the provided task as a QR code,
+/// PDF417 or DataMatrix symbol depending on the `symbology` field of the task.
+/// The content filters and plugins are applied to the encoded data like to messages.
+/// It handles character encoding and establishes the printer connection using TCP/IP.
+///
+/// # Arguments
+///
+/// * `task` - A `PrintTask` containing all the print job configuration
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn print_code_2d(task: PrintTask) -> EscposResult<()> {
+    let Some(task) = filters::apply(expand_placeholders(task)?) else {
+        info!("Dropped a job matching a content filter");
+        return Ok(());
+    };
+    let task = plugins::apply(task)?;
+    let code = Code2D::parse(task.symbology.as_deref().unwrap_or("QR"))?;
+    barcode::validate_2d(code, &task.message)?;
+    let options = RenderOptions::configured(&task);
+    if !options.capabilities.codes_2d {
+        return Err(PrinterError::Input(
+            "the printer model does not support 2D codes".to_string(),
+        ));
+    }
+
+    hooks::run(&task, || {
+        let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+        let codepage = resolve_codepage(&task, &options);
+
+        let driver = open(&address, port)?;
+        let mut binding = Printer::new(
+            driver.clone(),
+            Protocol::default(),
+            Some(PrinterOptions::default()),
+        );
+        let printer = binding
+            .debug_mode(Some(DebugMode::Dec))
+            .init()?
+            .page_code(codepage)?
+            .smoothing(true)?
+            .justify(JustifyMode::CENTER)?
+            .reverse(false)?;
+
+        match code {
+            Code2D::Qr => printer.qrcode(&task.message)?,
+            Code2D::Pdf417 => printer.pdf417(&task.message)?,
+            Code2D::DataMatrix => printer.data_matrix(&task.message)?,
+        };
+
+        feed_and_cut(printer, &options, false)?;
+        printer.print()?;
+        record_paper(
+            &driver,
+            &address,
+            port,
+            CODE_DOTS + cut_dots(&options.profile),
+        );
+        Ok(())
+    })
+}
+
+/// Prints the message as a one-dimensional barcode to an ESC/POS printer over the network.
+///
+/// The content filters and plugins are applied to the data like to messages, and the
+/// data is
+/// validated against the selected symbology before the printer connection is opened, so
+/// invalid input, also after a `replace` filter, never reaches the printer.
+///
+/// # Arguments
+///
+/// * `task` - A `PrintTask` whose `barcode` field names the symbology to use
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn print_barcode(task: PrintTask) -> EscposResult<()> {
+    let Some(task) = filters::apply(expand_placeholders(task)?) else {
+        info!("Dropped a job matching a content filter");
+        return Ok(());
+    };
+    let task = plugins::apply(task)?;
+    let symbology = Symbology::parse(task.barcode.as_deref().unwrap_or_default())?;
+    let data = task.message.trim();
+    barcode::validate(symbology, data)?;
+    let options = RenderOptions::configured(&task);
+    if !options.capabilities.barcodes {
+        return Err(PrinterError::Input(
+            "the printer model does not support barcodes".to_string(),
+        ));
+    }
+
+    hooks::run(&task, || {
+        let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+        let codepage = resolve_codepage(&task, &options);
+
+        let driver = open(&address, port)?;
+        let mut binding = Printer::new(
+            driver.clone(),
+            Protocol::default(),
+            Some(PrinterOptions::default()),
+        );
+        let printer = binding
+            .debug_mode(Some(DebugMode::Dec))
+            .init()?
+            .page_code(codepage)?
+            .justify(JustifyMode::CENTER)?;
+
+        match symbology {
+            Symbology::Ean13 => printer.ean13(data)?,
+            Symbology::Ean8 => printer.ean8(data)?,
+            Symbology::UpcA => printer.upca(data)?,
+            Symbology::UpcE => printer.upce(data)?,
+            Symbology::Code39 => printer.code39(data)?,
+            Symbology::Itf => printer.itf(data)?,
+            Symbology::Code128 => printer.custom(&barcode::code128_command(data))?,
+        };
+
+        feed_and_cut(printer, &options, false)?;
+        printer.print()?;
+        record_paper(
+            &driver,
+            &address,
+            port,
+            CODE_DOTS + cut_dots(&options.profile),
+        );
+        Ok(())
+    })
+}
+
+/// How raster images are sent to printers with a small receive buffer.
+///
+/// Large dithered images sent at once overflow the buffer of cheap printers, which then
+/// drop data and print torn images. The default sends every image as one command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunking {
+    /// Number of dot rows sent per raster command, 0 sends every image in one piece.
+    pub rows: u32,
+    /// Milliseconds to wait after every chunk.
+    pub delay: u64,
+    /// Whether to wait after every chunk until the printer reports it printed, using
+    /// `GS r 1`. Printers not answering the request fail the job.
+    pub flow_control: bool,
+}
+
+impl Chunking {
+    /// Returns whether images are sent in pieces rather than all at once.
+    fn is_enabled(&self) -> bool {
+        self.rows > 0 || self.delay > 0 || self.flow_control
+    }
+}
+
+/// Prints images to an ESC/POS printer over the network.
+///
+/// Every bitmap is printed centered as a raster image and cut off. A non-empty `message`
+/// of the task is printed as a caption below the last image, after the content filters
+/// and plugins; a `drop` filter matching the caption or title drops the whole job, and a
+/// plugin can reject or reroute it. With `chunking`, the images
+/// are split into bands that are sent one at a time, pausing or waiting for the printer
+/// after each band.
+///
+/// # Arguments
+///
+/// * `task` - A `PrintTask` providing the printer settings and the optional caption
+/// * `images` - The bitmaps to print, see [`Bitmap`]
+/// * `chunking` - How the raster data is split and paced, see [`Chunking`]
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn print_images(task: PrintTask, images: &[Bitmap], chunking: Chunking) -> EscposResult<()> {
+    let Some(task) = filters::apply(expand_placeholders(task)?) else {
+        info!("Dropped a job matching a content filter");
+        return Ok(());
+    };
+    let task = normalize_typography(plugins::apply(task)?);
+    hooks::run(&task, || {
+        let (address, port) = resolve_printer(task.address.as_deref(), task.port);
+        let options = RenderOptions::configured(&task);
+        let codepage = resolve_codepage(&task, &options);
+
+        let driver = open(&address, port)?;
+        let mut binding = Printer::new(
+            driver.clone(),
+            Protocol::default(),
+            Some(PrinterOptions::default()),
+        );
+        let printer = binding
+            .debug_mode(Some(DebugMode::Dec))
+            .init()?
+            .page_code(codepage)?
+            .justify(JustifyMode::CENTER)?;
+
+        let mut dots = 0;
+        for (index, image) in images.iter().enumerate() {
+            for band in image.bands(chunking.rows) {
+                printer.custom(&band.raster_command())?;
+                if chunking.is_enabled() {
+                    printer.print()?;
+                    wait_for_chunk(&driver, chunking)?;
+                }
+            }
+            if index + 1 == images.len() && !task.message.trim().is_empty() {
+                printer.feed()?.writeln(task.message.trim())?;
+                dots += 2 * LINE_DOTS;
+            }
+            feed_and_cut(printer, &options, false)?;
+            dots += image.height + cut_dots(&options.profile);
+        }
+
+        printer.print()?;
+        record_paper(&driver, &address, port, dots);
+        Ok(())
+    })
+}
+
+/// Paces a chunk of raster data that was just sent, see [`Chunking`].
+///
+/// Blocks the calling thread, so the API server sends images from the queue worker.
+fn wait_for_chunk(driver: &Connection, chunking: Chunking) -> EscposResult<()> {
+    if let Connection::Output(_) = driver {
+        return Ok(());
+    }
+    if chunking.flow_control {
+        driver.write(&GS_TRANSMIT_STATUS)?;
+        driver.flush()?;
+        let mut status = [0; 1];
+        if driver.read(&mut status)? == 0 {
+            return Err(PrinterError::Io(
+                "the printer closed the connection while printing".to_string(),
+            ));
+        }
+    }
+    if chunking.delay > 0 {
+        thread::sleep(Duration::from_millis(chunking.delay));
+    }
+    Ok(())
+}
+
+/// Paper supply reported by the roll paper sensors of a printer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaperStatus {
+    /// Enough paper left.
+    Ok,
+    /// The roll is nearly used up.
+    NearEnd,
+    /// The printer is out of paper.
+    Out,
+}
+
+impl PaperStatus {
+    /// Returns the status name as published to MQTT.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PaperStatus::Ok => "ok",
+            PaperStatus::NearEnd => "near_end",
+            PaperStatus::Out => "out",
+        }
+    }
+}
+
+/// Real-time status of a printer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrinterStatus {
+    /// Whether the printer reports itself online, e.g. not offline with an open cover.
+    pub online: bool,
+    /// The paper supply.
+    pub paper: PaperStatus,
+}
+
+/// Queries the real-time status of an ESC/POS printer over the network.
+///
+/// Sends `DLE EOT 1` and `DLE EOT 4` and reads the printer and roll paper sensor
+/// status bytes. Nothing is printed.
+///
+/// # Arguments
+///
+/// * `address` - The printer address, defaults to the configured printer
+/// * `port` - The printer port, defaults to the configured port
+///
+/// # Returns
+///
+/// * `EscposResult<PrinterStatus>` - The status, an I/O error if the printer is
+///   unreachable or does not answer, or an invalid response error if the answer is no
+///   status byte
+pub fn query_status(address: Option<&str>, port: Option<u16>) -> EscposResult<PrinterStatus> {
+    let [printer, paper] = request_status(
+        address,
+        port,
+        [
+            RealTimeStatusRequest::Printer,
+            RealTimeStatusRequest::RollPaperSensor,
+        ],
+    )?;
+    let paper = if !is_set(
+        &paper,
+        RealTimeStatusResponse::RollPaperEndSensorPaperPresent,
+    ) {
+        PaperStatus::Out
+    } else if !is_set(
+        &paper,
+        RealTimeStatusResponse::RollPaperNearEndSensorPaperAdequate,
+    ) {
+        PaperStatus::NearEnd
+    } else {
+        PaperStatus::Ok
+    };
+
+    Ok(PrinterStatus {
+        online: is_set(&printer, RealTimeStatusResponse::Online),
+        paper,
+    })
+}
+
+/// Queries the offline and error causes of an ESC/POS printer over the network.
+///
+/// Sends `DLE EOT 2` and `DLE EOT 3`, e.g. after a job to find out whether it was
+/// really printed: a successful TCP write only means the printer received the data.
+/// Nothing is printed.
+///
+/// # Arguments
+///
+/// * `address` - The printer address, defaults to the configured printer
+/// * `port` - The printer port, defaults to the configured port
+///
+/// # Returns
+///
+/// * `EscposResult<Vec<&'static str>>` - The reported problems such as "autocutter
+///   error", empty if the printer reports none, an I/O error if the printer is
+///   unreachable or does not answer, or an invalid response error if the answer is no
+///   status byte
+pub fn query_errors(address: Option<&str>, port: Option<u16>) -> EscposResult<Vec<&'static str>> {
+    let [offline, error] = request_status(
+        address,
+        port,
+        [
+            RealTimeStatusRequest::OfflineCause,
+            RealTimeStatusRequest::ErrorCause,
+        ],
+    )?;
+    let mut problems = Vec::new();
+    if !is_set(&offline, RealTimeStatusResponse::CoverClosed) {
+        problems.push("cover open");
+    }
+    for (status, flag, problem) in [
+        (
+            &offline,
+            RealTimeStatusResponse::PrintingStopsDueToPaperEnd,
+            "paper end",
+        ),
+        (
+            &error,
+            RealTimeStatusResponse::AutocutterErrorOccurred,
+            "autocutter error",
+        ),
+        (
+            &error,
+            RealTimeStatusResponse::UnrecoverableErrorOccurred,
+            "unrecoverable error",
+        ),
+        (
+            &error,
+            RealTimeStatusResponse::AutoRecoverableErrorOccurred,
+            "auto-recoverable error",
+        ),
+        (
+            &error,
+            RealTimeStatusResponse::RecoverableErrorOccurred,
+            "recoverable error",
+        ),
+    ] {
+        if is_set(status, flag) {
+            problems.push(problem);
+        }
+    }
+    if problems.is_empty() && is_set(&offline, RealTimeStatusResponse::ErrorOccurred) {
+        problems.push("error");
+    }
+    Ok(problems)
+}
+
+/// Sends real-time status requests to a printer and parses one status byte per request.
+fn request_status<const N: usize>(
+    address: Option<&str>,
+    port: Option<u16>,
+    requests: [RealTimeStatusRequest; N],
+) -> EscposResult<[HashMap<RealTimeStatusResponse, bool>; N]> {
+    let (address, port) = resolve_printer(address, port);
+    let driver = connect(&address, port)?;
+    let mut printer = Printer::new(driver.clone(), Protocol::default(), None);
+    for request in requests {
+        printer.real_time_status(request)?;
+    }
+    printer.send_status()?;
+
+    let mut response = [0; N];
+    let mut received = 0;
+    while received < response.len() {
+        match driver.read(&mut response[received..])? {
+            0 => return Err(PrinterError::Io("the printer sent no status".to_string())),
+            count => received += count,
+        }
+    }
+
+    let mut statuses = requests.map(|_| HashMap::new());
+    for (index, request) in requests.into_iter().enumerate() {
+        statuses[index] = RealTimeStatusResponse::parse(request, response[index])?;
+    }
+    Ok(statuses)
+}
+
+/// Returns whether a flag of a parsed status byte is set.
+fn is_set(status: &HashMap<RealTimeStatusResponse, bool>, flag: RealTimeStatusResponse) -> bool {
+    status.get(&flag).copied().unwrap_or(false)
+}
+
+/// Maps the codepage name of a task to the printer page code, defaulting to the
+/// codepage of the render options for missing and unknown names, and to the first
+/// codepage of the printer model for codepages the model does not support.
+fn resolve_codepage(task: &PrintTask, options: &RenderOptions) -> PageCode {
+    let supported = &options.capabilities.codepages;
+    [task.codepage.as_deref(), options.codepage.as_deref()]
+        .into_iter()
+        .flatten()
+        .chain(supported.iter().map(String::as_str))
+        .filter(|name| supported.iter().any(|codepage| codepage == name))
+        .find_map(page_code)
+        .unwrap_or(PageCode::PC850)
+}
+
+/// Returns whether a codepage name is supported by the renderer.
+pub(crate) fn is_supported_codepage(name: &str) -> bool {
+    page_code(name).is_some()
+}
+
+/// Maps a codepage name to the printer page code.
+fn page_code(name: &str) -> Option<PageCode> {
+    match name {
+        "PC850" => Some(PageCode::PC850),
+        "ISO8859_15" => Some(PageCode::ISO8859_15),
+        "WPC1252" => Some(PageCode::WPC1252),
+        "PC437" => Some(PageCode::PC437),
+        "ISO8859_7" => Some(PageCode::ISO8859_7),
+        _ => None,
+    }
+}
+
 /// Generates a formatted string representation of a note.
 /// This function creates a visually appealing box around the message,
-/// including a fixed "NOTE" title at the top. It ensures that lines do not exceed
-/// a maximum width for better readability.
-/// 
+/// including a "NOTE" title in the configured language at the top. It ensures that lines
+/// do not exceed a maximum width for better readability.
+///
 /// # Arguments
-/// 
+///
 /// * `task` - A reference to the `PrintTask` to be formatted
-/// 
+///
 /// # Returns
-/// 
+///
 /// * `String` - The formatted string ready for printing
-pub fn generate_note_string(task: &PrintTask) -> String {
-    let max_width = 24;
+pub fn generate_note_string(task: &PrintTask, options: &RenderOptions) -> String {
     let frame_header = "┌──────────────────────┐";
-    let frame_title_box = "│        NOTE          │";
-    let frame_separator = "├──────────────────────┤";
-    let frame_body = "│ {} │";
+    let frame_title_box = format!("│        {}│", fit(&options.labels.text(Label::Note), 14));
+    let frame_separator = separator_line(header_style(task, options).separator);
     let frame_footer = "└──────────────────────┘";
 
     let mut output = format!("{}\n", frame_header);
     output.push_str(&format!("{}\n", frame_title_box));
     output.push_str(&format!("{}\n", frame_separator));
-    for line in task.message.lines() {
-        let mut start = 0;
-        while start < line.len() {
-            let end = usize::min(start + max_width, line.len());
-            let segment = &line[start..end];
-            output.push_str(&format!(
-                "{}\n",
-                frame_body.replace("{}", &format!("{:<20}", segment)[..20])
-            ));
-            start += max_width;
-        }
-    }
-    output.push_str(&format!("{}", frame_footer));
+    output.push_str(&frame_message(&task.message, dictionary(task).as_ref()));
+    output.push_str(frame_footer);
 
     output
 }
@@ -199,41 +1604,417 @@ pub fn generate_note_string(task: &PrintTask) -> String {
 /// # Returns
 ///
 /// * `String` - The formatted string ready for printing
-pub fn generate_task_string(task: &PrintTask) -> String {
-    let max_width = 24;
-    let title = task.title.clone().unwrap_or_else(|| "NOTE".to_string());
+pub fn generate_task_string(
+    task: &PrintTask,
+    options: &RenderOptions,
+    now: DateTime<Local>,
+) -> String {
+    let title = task
+        .title
+        .clone()
+        .or_else(|| options.title.clone())
+        .unwrap_or_else(|| options.labels.text(Label::Note));
     let frame_header = "┌──────────────────────┐";
     let frame_title_box = "│ {title} {date} │";
-    let frame_separator = "├──────────────────────┤";
-    let frame_body = "│ {} │";
+    let frame_separator = separator_line(header_style(task, options).separator);
     let frame_footer = "└──────────────────────┘";
     let date_str = task
         .date
         .clone()
-        .unwrap_or_else(|| Local::now().format("%d/%m/%Y").to_string());
-    let short_date_day_month = &date_str[0..5];
+        .unwrap_or_else(|| now.format("%d/%m/%Y").to_string());
+    let short_date_day_month = format!("{:>5}", date_str.chars().take(5).collect::<String>());
 
     let mut output = format!("{}\n", frame_header);
     output.push_str(&format!(
         "{}\n",
         frame_title_box
-            .replace("{title}", &format!("{:<14}", title)[..14])
-            .replace("{date}", &format!("{:>5}", short_date_day_month)[..5])
+            .replace("{title}", &fit(&title, FRAME_TITLE_WIDTH))
+            .replace("{date}", &short_date_day_month)
     ));
     output.push_str(&format!("{}\n", frame_separator));
-    for line in task.message.lines() {
-        let mut start = 0;
-        while start < line.len() {
-            let end = usize::min(start + max_width, line.len());
-            let segment = &line[start..end];
-            output.push_str(&format!(
-                "{}\n",
-                frame_body.replace("{}", &format!("{:<20}", segment)[..20])
-            ));
-            start += max_width;
+    output.push_str(&frame_message(&task.message, dictionary(task).as_ref()));
+    output.push_str(frame_footer);
+
+    output
+}
+
+/// Renders the frame of a task as `print_task` prints it in frame mode, as text.
+///
+/// Like [`render_task`], the rendering is pure and leaves out placeholders, content
+/// filters and plugins. Typography and whitespace are normalized and ANSI escape
+/// sequences are removed like when printing.
+///
+/// # Arguments
+///
+/// * `task` - A reference to the `PrintTask` to be rendered
+/// * `options` - The configuration to render with, see [`RenderOptions::configured`]
+/// * `now` - The time used for the date if the task has none
+///
+/// # Returns
+///
+/// * `String` - The framed slip, one printed line per line
+pub fn render_text(task: &PrintTask, options: &RenderOptions, now: DateTime<Local>) -> String {
+    let task = compress_whitespace(normalize_typography(task.clone()));
+    let task = PrintTask {
+        message: ansi::strip(&task.message),
+        ..task
+    };
+    generate_frame(&task, options, now)
+}
+
+/// Renders the frame of a task with the configuration and the current date, for previews.
+/// Placeholders are left as they are, since expanding them increments counters.
+pub(crate) fn preview_frame(task: &PrintTask) -> String {
+    render_text(task, &RenderOptions::configured(task), Local::now())
+}
+
+/// Returns the header style of a task, falling back to the printer profile.
+fn header_style(task: &PrintTask, options: &RenderOptions) -> HeaderStyle {
+    task.header.or(options.profile.header).unwrap_or_default()
+}
+
+/// Builds the rule between the title row and the message of a frame.
+fn separator_line(separator: char) -> String {
+    format!("├{}┤", separator.to_string().repeat(FRAME_BODY_WIDTH + 2))
+}
+
+/// Frames a slip with its title and date, or as a note if neither the task nor the
+/// render options have a title.
+fn generate_frame(task: &PrintTask, options: &RenderOptions, now: DateTime<Local>) -> String {
+    if task.title.is_some() || options.title.is_some() {
+        generate_task_string(task, options, now)
+    } else {
+        generate_note_string(task, options)
+    }
+}
+
+/// Loads the hyphenation dictionary selected by a task, ignoring unknown languages.
+fn dictionary(task: &PrintTask) -> Option<Standard> {
+    task.hyphenate
+        .as_deref()
+        .and_then(|code| hyphenate::load(code).ok())
+}
+
+/// Number of characters that fit between the left and right border of the frame.
+pub(crate) const FRAME_BODY_WIDTH: usize = 20;
+
+/// Width of the title in the title box of a task frame, next to the date.
+pub(crate) const FRAME_TITLE_WIDTH: usize = 14;
+
+/// Line of the title row in a frame, below the top border.
+const FRAME_TITLE_ROW: usize = 1;
+
+/// Width of a frame in characters, including the borders and padding.
+const FRAME_WIDTH: usize = FRAME_BODY_WIDTH + 4;
+
+/// Returns the character width frames are printed in: double width if a frame fits
+/// the Font A line of the printer model that way, else normal width, e.g. on 58mm paper.
+fn frame_scale(capabilities: &Capabilities) -> u8 {
+    if capabilities.font_a_columns >= 2 * FRAME_WIDTH {
+        2
+    } else {
+        1
+    }
+}
+
+/// Wraps the message into framed body lines of `FRAME_BODY_WIDTH` characters.
+///
+/// Lines longer than the frame are split into several body lines. Empty lines are kept
+/// as empty body lines. Splitting is done on characters rather than bytes, so multibyte
+/// characters such as umlauts or box drawing characters are never cut in half. With a
+/// hyphenation dictionary, lines are word wrapped and long words are hyphenated instead.
+fn frame_message(message: &str, dictionary: Option<&Standard>) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    body_lines(&chars, dictionary)
+        .into_iter()
+        .map(|line| {
+            let mut segment: String = chars[line.range].iter().collect();
+            if line.hyphen {
+                segment.push('-');
+            }
+            format!("│ {} │\n", fit(&segment, FRAME_BODY_WIDTH))
+        })
+        .collect()
+}
+
+/// Splits message characters into the character ranges shown on each framed body line,
+/// following the line semantics of [`str::lines`].
+fn body_lines(chars: &[char], dictionary: Option<&Standard>) -> Vec<WrappedLine> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let newline = chars[start..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map(|offset| start + offset);
+        let mut end = newline.unwrap_or(chars.len());
+        if end > start && chars[end - 1] == '\r' {
+            end -= 1;
+        }
+
+        match dictionary {
+            Some(dictionary) => {
+                lines.extend(
+                    hyphenate::wrap(&chars[start..end], FRAME_BODY_WIDTH, dictionary)
+                        .into_iter()
+                        .map(|line| WrappedLine {
+                            range: start + line.range.start..start + line.range.end,
+                            hyphen: line.hyphen,
+                        }),
+                );
+            }
+            None => {
+                if end == start {
+                    lines.push(WrappedLine {
+                        range: start..start,
+                        hyphen: false,
+                    });
+                }
+                let mut segment = start;
+                while segment < end {
+                    let segment_end = (segment + FRAME_BODY_WIDTH).min(end);
+                    lines.push(WrappedLine {
+                        range: segment..segment_end,
+                        hyphen: false,
+                    });
+                    segment = segment_end;
+                }
+            }
         }
+
+        start = newline.map_or(chars.len(), |newline| newline + 1);
     }
-    output.push_str(&format!("{}", frame_footer));
+    lines
+}
 
-    output
+/// Writes a framed slip, reproducing the text styles of the message in the body lines
+/// and styling the title row as set in the header style.
+///
+/// The frame is rendered from the plain message, so the layout is identical to an
+/// unstyled slip.
+fn write_styled_frame<D: Driver>(
+    printer: &mut Printer<D>,
+    frame: &str,
+    message: &str,
+    styles: &[Style],
+    header: HeaderStyle,
+    scale: u8,
+    dictionary: Option<&Standard>,
+) -> EscposResult<()> {
+    let chars: Vec<char> = message.chars().collect();
+    let bodies = if styles.iter().all(|style| *style == Style::default()) {
+        Vec::new()
+    } else {
+        body_lines(&chars, dictionary)
+    };
+    let lines: Vec<&str> = frame.lines().collect();
+    // Header, title and separator precede the body, the footer follows it.
+    let body_start = lines.len().saturating_sub(bodies.len() + 1);
+
+    for (index, line) in lines.iter().enumerate() {
+        if index == FRAME_TITLE_ROW {
+            printer
+                .bold(header.bold)?
+                .reverse(header.reverse)?
+                .size(scale, if header.double_height { 2 } else { 1 })?
+                .writeln(line)?
+                .bold(false)?
+                .reverse(false)?
+                .size(scale, 2)?;
+            continue;
+        }
+        let Some(body) = index
+            .checked_sub(body_start)
+            .and_then(|body| bodies.get(body))
+        else {
+            printer.writeln(line)?;
+            continue;
+        };
+
+        let range = &body.range;
+        let mut text = chars[range.clone()].to_vec();
+        if body.hyphen {
+            text.push('-');
+        }
+        let mut runs: Vec<(Style, String)> = Vec::new();
+        for offset in 0..FRAME_BODY_WIDTH {
+            let (c, style) = match text.get(offset) {
+                Some(c) => (
+                    *c,
+                    styles
+                        .get(range.start + offset)
+                        .copied()
+                        .unwrap_or_default(),
+                ),
+                None => (' ', Style::default()),
+            };
+            match runs.last_mut() {
+                Some((run_style, run)) if *run_style == style => run.push(c),
+                _ => runs.push((style, c.to_string())),
+            }
+        }
+
+        printer.write("│ ")?;
+        for (style, run) in &runs {
+            apply_style(printer, *style)?;
+            printer.write(run)?;
+        }
+        apply_style(printer, Style::default())?;
+        printer.writeln(" │")?;
+    }
+    Ok(())
+}
+
+/// Switches emphasis, underline and inversion to the given style.
+fn apply_style<D: Driver>(printer: &mut Printer<D>, style: Style) -> EscposResult<()> {
+    printer
+        .bold(style.bold)?
+        .underline(if style.underline {
+            UnderlineMode::Single
+        } else {
+            UnderlineMode::None
+        })?
+        .reverse(style.invert)?;
+    Ok(())
+}
+
+/// Pads or truncates text to exactly `width` characters.
+fn fit(text: &str, width: usize) -> String {
+    let truncated: String = text.chars().take(width).collect();
+    format!("{:<width$}", truncated, width = width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 3, 9, 8, 30, 0).unwrap()
+    }
+
+    fn task(message: &str) -> PrintTask {
+        PrintTask {
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn german() -> RenderOptions {
+        RenderOptions {
+            labels: Labels {
+                language: Language::German,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renders_note_without_title() {
+        let text = render_text(&task("Buy milk"), &RenderOptions::default(), now());
+        assert_eq!(
+            text,
+            "┌──────────────────────┐\n\
+             │        NOTE          │\n\
+             ├──────────────────────┤\n\
+             │ Buy milk             │\n\
+             └──────────────────────┘"
+        );
+    }
+
+    #[test]
+    fn renders_title_and_date_of_now() {
+        let task = PrintTask {
+            title: Some("Groceries".to_string()),
+            ..task("Buy milk")
+        };
+        let text = render_text(&task, &RenderOptions::default(), now());
+        assert_eq!(
+            text,
+            "┌──────────────────────┐\n\
+             │ Groceries      09/03 │\n\
+             ├──────────────────────┤\n\
+             │ Buy milk             │\n\
+             └──────────────────────┘"
+        );
+    }
+
+    #[test]
+    fn renders_default_title_of_options() {
+        let options = RenderOptions {
+            title: Some("TODO".to_string()),
+            ..Default::default()
+        };
+        let task = PrintTask {
+            date: Some("24/12/2024".to_string()),
+            ..task("Wrap gifts")
+        };
+        let text = render_text(&task, &options, now());
+        assert_eq!(
+            text,
+            "┌──────────────────────┐\n\
+             │ TODO           24/12 │\n\
+             ├──────────────────────┤\n\
+             │ Wrap gifts           │\n\
+             └──────────────────────┘"
+        );
+    }
+
+    #[test]
+    fn wraps_long_lines_on_characters() {
+        let text = render_text(
+            &task("Grüße aus dem Büro, bis morgen\n\nÜbermorgen"),
+            &RenderOptions::default(),
+            now(),
+        );
+        assert_eq!(
+            text,
+            "┌──────────────────────┐\n\
+             │        NOTE          │\n\
+             ├──────────────────────┤\n\
+             │ Grüße aus dem Büro,  │\n\
+             │ bis morgen           │\n\
+             │                      │\n\
+             │ Übermorgen           │\n\
+             └──────────────────────┘"
+        );
+    }
+
+    #[test]
+    fn renders_labels_of_options() {
+        let options = RenderOptions {
+            labels: Labels {
+                overrides: HashMap::from([("note".to_string(), "MEMO".to_string())]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let note = render_text(&task("Call Bob"), &options, now());
+        assert!(note.contains("│        MEMO          │"));
+
+        let note = render_text(&task("Call Bob"), &german(), now());
+        assert!(note.contains("│        NOTIZ         │"));
+    }
+
+    #[test]
+    fn renders_sender_line_in_language_of_options() {
+        assert_eq!(sender_line(" Alice ", &Labels::default()), "From: Alice");
+        assert_eq!(sender_line("Alice", &german().labels), "Von: Alice");
+    }
+
+    #[test]
+    fn render_task_is_deterministic() {
+        let task = PrintTask {
+            from: Some("Alice".to_string()),
+            ..task("Buy milk")
+        };
+        let first = render_task(&task, &german(), now()).unwrap();
+        let second = render_task(&task, &german(), now()).unwrap();
+        assert_eq!(first, second);
+        let contains = |needle: &[u8]| first.windows(needle.len()).any(|part| part == needle);
+        assert!(contains(b"NOTIZ"));
+        assert!(contains(b"Von: Alice"));
+    }
 }
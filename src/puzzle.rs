@@ -0,0 +1,317 @@
+use crate::cli::Args;
+use crate::i18n::{self, Label};
+use crate::printer::{PrintTask, print_task};
+use crate::table::Table;
+use clap::{Args as ClapArgs, ValueEnum};
+use escpos::errors::Result as EscposResult;
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Size of the word search grid; letters separated by spaces fill the 20 column frame.
+const WORD_SEARCH_SIZE: usize = 10;
+
+/// Words hidden in word search puzzles. All fit into the grid in any direction.
+const WORDS: &[&str] = &[
+    "PAPER", "PRINT", "TASK", "NOTE", "LIST", "COFFEE", "GARDEN", "KITCHEN", "WINDOW", "PENCIL",
+    "RIVER", "FOREST", "CANDLE", "BASKET", "ORANGE", "PLANET", "ROCKET", "SUMMER", "WINTER",
+    "SPRING", "AUTUMN", "CASTLE", "DRAGON", "MARKET", "TICKET", "HAMMER", "LADDER", "PUZZLE",
+    "BRIDGE", "ISLAND", "CLOUD", "STONE", "HONEY", "LEMON", "TIGER", "ZEBRA", "PIANO", "GUITAR",
+];
+
+/// Type of puzzle to generate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PuzzleKind {
+    Sudoku,
+    WordSearch,
+}
+
+/// Difficulty of the generated puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Options for the puzzle mode.
+#[derive(ClapArgs)]
+pub struct PuzzleArgs {
+    /// Type of puzzle to print
+    #[arg(value_enum)]
+    pub kind: PuzzleKind,
+
+    /// Puzzle difficulty
+    #[arg(long, value_enum, default_value = "medium")]
+    pub difficulty: Difficulty,
+
+    /// Also print the solution on a separate slip
+    #[arg(long)]
+    pub solution: bool,
+}
+
+/// Generates a puzzle and prints it, optionally followed by its solution.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `puzzle` - The puzzle options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an ESC/POS error on failure
+pub fn run_puzzle(args: &Args, puzzle: &PuzzleArgs) -> EscposResult<()> {
+    let (title, message, solution) = match puzzle.kind {
+        PuzzleKind::Sudoku => {
+            let (grid, solution) = generate_sudoku(puzzle.difficulty);
+            (
+                i18n::text(Label::Sudoku),
+                format!(
+                    "{}\n\nDifficulty: {:?}",
+                    render_sudoku(&grid),
+                    puzzle.difficulty
+                ),
+                render_sudoku(&solution),
+            )
+        }
+        PuzzleKind::WordSearch => {
+            let (grid, words, solution) = generate_word_search(puzzle.difficulty);
+            (
+                i18n::text(Label::WordSearch),
+                format!("{}\n\nFind:\n{}", render_letters(&grid), words.join("\n")),
+                render_letters(&solution),
+            )
+        }
+    };
+
+    print_task(puzzle_task(args, &title, message))?;
+    if puzzle.solution {
+        print_task(puzzle_task(args, &i18n::text(Label::Solution), solution))?;
+    }
+    Ok(())
+}
+
+fn puzzle_task(args: &Args, title: &str, message: String) -> PrintTask {
+    PrintTask {
+        title: Some(title.to_string()),
+        message,
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        ..Default::default()
+    }
+}
+
+/// Generates a sudoku with a unique solution.
+///
+/// # Returns
+///
+/// * `([[u8; 9]; 9], [[u8; 9]; 9])` - The puzzle (0 for empty cells) and its solution
+pub fn generate_sudoku(difficulty: Difficulty) -> ([[u8; 9]; 9], [[u8; 9]; 9]) {
+    let mut rng = rand::rng();
+    let mut solution = [[0u8; 9]; 9];
+    fill_sudoku(&mut solution, &mut rng);
+
+    let clues = match difficulty {
+        Difficulty::Easy => 38,
+        Difficulty::Medium => 30,
+        Difficulty::Hard => 25,
+    };
+
+    let mut grid = solution;
+    let mut cells: Vec<usize> = (0..81).collect();
+    cells.shuffle(&mut rng);
+    let mut remaining = 81;
+    for cell in cells {
+        if remaining <= clues {
+            break;
+        }
+        let (row, col) = (cell / 9, cell % 9);
+        let value = grid[row][col];
+        grid[row][col] = 0;
+        if count_solutions(&mut grid.clone(), 2) == 1 {
+            remaining -= 1;
+        } else {
+            grid[row][col] = value;
+        }
+    }
+
+    (grid, solution)
+}
+
+fn can_place(grid: &[[u8; 9]; 9], row: usize, col: usize, value: u8) -> bool {
+    let (box_row, box_col) = (row / 3 * 3, col / 3 * 3);
+    (0..9).all(|i| {
+        grid[row][i] != value
+            && grid[i][col] != value
+            && grid[box_row + i / 3][box_col + i % 3] != value
+    })
+}
+
+fn fill_sudoku(grid: &mut [[u8; 9]; 9], rng: &mut impl Rng) -> bool {
+    let Some(cell) = (0..81).find(|c| grid[c / 9][c % 9] == 0) else {
+        return true;
+    };
+    let (row, col) = (cell / 9, cell % 9);
+    let mut digits: Vec<u8> = (1..=9).collect();
+    digits.shuffle(rng);
+    for value in digits {
+        if can_place(grid, row, col, value) {
+            grid[row][col] = value;
+            if fill_sudoku(grid, rng) {
+                return true;
+            }
+            grid[row][col] = 0;
+        }
+    }
+    false
+}
+
+/// Counts the solutions of a grid, stopping once `limit` is reached.
+fn count_solutions(grid: &mut [[u8; 9]; 9], limit: usize) -> usize {
+    let Some(cell) = (0..81).find(|c| grid[c / 9][c % 9] == 0) else {
+        return 1;
+    };
+    let (row, col) = (cell / 9, cell % 9);
+    let mut count = 0;
+    for value in 1..=9 {
+        if can_place(grid, row, col, value) {
+            grid[row][col] = value;
+            count += count_solutions(grid, limit - count);
+            grid[row][col] = 0;
+            if count >= limit {
+                break;
+            }
+        }
+    }
+    count
+}
+
+/// Renders a sudoku grid as a table with one column per 3x3 block.
+fn render_sudoku(grid: &[[u8; 9]; 9]) -> String {
+    let mut table = Table::new(&[5, 5, 5]);
+    for (i, row) in grid.iter().enumerate() {
+        if i > 0 && i % 3 == 0 {
+            table = table.rule();
+        }
+        let blocks: Vec<String> = row
+            .chunks(3)
+            .map(|block| {
+                block
+                    .iter()
+                    .map(|v| match v {
+                        0 => ".".to_string(),
+                        v => v.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+        table = table.row(&blocks);
+    }
+    table.render()
+}
+
+/// Generates a word search grid.
+///
+/// Easy puzzles only place words left to right and top to bottom, medium puzzles add
+/// diagonals and hard puzzles use all eight directions including backwards words.
+///
+/// # Returns
+///
+/// * `(Vec<Vec<char>>, Vec<String>, Vec<Vec<char>>)` - The filled grid, the hidden words
+///   and a solution grid that only shows the letters of the hidden words
+pub fn generate_word_search(
+    difficulty: Difficulty,
+) -> (Vec<Vec<char>>, Vec<String>, Vec<Vec<char>>) {
+    let mut rng = rand::rng();
+    let (count, directions): (usize, &[(i32, i32)]) = match difficulty {
+        Difficulty::Easy => (5, &[(0, 1), (1, 0)]),
+        Difficulty::Medium => (7, &[(0, 1), (1, 0), (1, 1), (-1, 1)]),
+        Difficulty::Hard => (
+            9,
+            &[
+                (0, 1),
+                (1, 0),
+                (1, 1),
+                (-1, 1),
+                (0, -1),
+                (-1, 0),
+                (-1, -1),
+                (1, -1),
+            ],
+        ),
+    };
+
+    let mut candidates: Vec<&str> = WORDS.to_vec();
+    candidates.shuffle(&mut rng);
+
+    let mut grid = vec![vec![' '; WORD_SEARCH_SIZE]; WORD_SEARCH_SIZE];
+    let mut placed = Vec::new();
+    for word in candidates {
+        if placed.len() == count {
+            break;
+        }
+        if place_word(&mut grid, word, directions, &mut rng) {
+            placed.push(word.to_string());
+        }
+    }
+
+    let solution: Vec<Vec<char>> = grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|c| if *c == ' ' { '.' } else { *c })
+                .collect()
+        })
+        .collect();
+    for cell in grid.iter_mut().flatten() {
+        if *cell == ' ' {
+            *cell = rng.random_range(b'A'..=b'Z') as char;
+        }
+    }
+
+    placed.sort();
+    (grid, placed, solution)
+}
+
+fn place_word(
+    grid: &mut [Vec<char>],
+    word: &str,
+    directions: &[(i32, i32)],
+    rng: &mut impl Rng,
+) -> bool {
+    let size = WORD_SEARCH_SIZE as i32;
+    let letters: Vec<char> = word.chars().collect();
+    for _ in 0..200 {
+        let (dr, dc) = directions[rng.random_range(0..directions.len())];
+        let row = rng.random_range(0..size);
+        let col = rng.random_range(0..size);
+        let positions: Vec<(i32, i32)> = (0..letters.len() as i32)
+            .map(|i| (row + dr * i, col + dc * i))
+            .collect();
+
+        let fits = positions.iter().zip(&letters).all(|(&(r, c), letter)| {
+            (0..size).contains(&r) && (0..size).contains(&c) && {
+                let cell = grid[r as usize][c as usize];
+                cell == ' ' || cell == *letter
+            }
+        });
+        if fits {
+            for (&(r, c), letter) in positions.iter().zip(&letters) {
+                grid[r as usize][c as usize] = *letter;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// Renders a letter grid as a borderless table with one column per letter.
+fn render_letters(grid: &[Vec<char>]) -> String {
+    let mut table = Table::new(&[1; WORD_SEARCH_SIZE]).borders(false);
+    for row in grid {
+        let cells: Vec<String> = row.iter().map(char::to_string).collect();
+        table = table.row(&cells);
+    }
+    table.render()
+}
@@ -0,0 +1,1130 @@
+use crate::api::{AppState, PrintResponse};
+use crate::cli::Args;
+use crate::client::{self, parse_response, request};
+use crate::config::{HistoryConfig, QueueConfig, SpoolConfig, VerifyConfig};
+use crate::filters;
+use crate::metrics;
+use crate::printer::{
+    Chunking, PrintTask, print_barcode, print_code_2d, print_images, print_task, query_errors,
+};
+use crate::raster::Bitmap;
+use crate::spool::{Spool, SpooledJob};
+use crate::summary;
+use crate::table::Table;
+use async_graphql::{Enum, SimpleObject};
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, header::RETRY_AFTER},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Local, TimeDelta};
+use clap::{Args as ClapArgs, Subcommand};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, oneshot};
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+
+/// Number of job events buffered for each event subscriber.
+const EVENT_CAPACITY: usize = 256;
+
+/// Lifecycle state of a print job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Waiting for the printer.
+    Queued,
+    /// Stored in the offline spool until the printer is reachable again.
+    Spooled,
+    /// Being sent to the printer.
+    Printing,
+    /// Printed successfully.
+    Printed,
+    /// Printing failed.
+    Failed,
+    /// Dropped because it was still waiting when its `expires_at` time passed.
+    Expired,
+    /// Cancelled while it was waiting.
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Returns the status name as used in the JSON responses.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Spooled => "spooled",
+            JobStatus::Printing => "printing",
+            JobStatus::Printed => "printed",
+            JobStatus::Failed => "failed",
+            JobStatus::Expired => "expired",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether the job is done and its status will not change anymore.
+    pub fn is_finished(self) -> bool {
+        matches!(
+            self,
+            JobStatus::Printed | JobStatus::Failed | JobStatus::Expired | JobStatus::Cancelled
+        )
+    }
+}
+
+/// Public information about a job, as returned by the job endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+#[graphql(name = "Job")]
+pub struct JobInfo {
+    /// Sequential job ID, unique while the server runs.
+    pub id: u64,
+    pub status: JobStatus,
+    /// Title of the task, if any.
+    pub title: Option<String>,
+    /// Sender of the task, if given.
+    pub from: Option<String>,
+    /// Identifier of the job in the submitting system, e.g. an order ID or an alert
+    /// fingerprint.
+    pub reference: Option<String>,
+    pub submitted_at: DateTime<Local>,
+    /// Time after which the job is dropped instead of printed.
+    pub expires_at: Option<DateTime<Local>>,
+    /// Time the job was printed, failed or expired.
+    pub finished_at: Option<DateTime<Local>>,
+    /// Error message of failed jobs.
+    pub error: Option<String>,
+}
+
+/// Final result of a job, delivered to the submitter.
+#[derive(Debug)]
+pub enum JobOutcome {
+    Printed,
+    Failed(PrinterError),
+    Expired,
+    /// The printer is unreachable and the job will be printed from the spool later.
+    Spooled,
+    Cancelled,
+}
+
+struct Job {
+    info: JobInfo,
+    task: PrintTask,
+    /// Images of image and PDF jobs, printed instead of the task with its message as caption.
+    images: Option<ImageJob>,
+    done: Option<oneshot::Sender<JobOutcome>>,
+}
+
+/// Raster images of an image or PDF job, see [`print_images`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageJob {
+    /// The bitmaps printed one after another.
+    pub images: Vec<Bitmap>,
+    /// How the raster data is split and paced.
+    pub chunking: Chunking,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: u64,
+    paused: bool,
+    pending: VecDeque<Job>,
+    spooled: VecDeque<Job>,
+    current: Option<JobInfo>,
+    history: VecDeque<JobInfo>,
+    limits: QueueConfig,
+    retention: HistoryConfig,
+}
+
+impl QueueState {
+    /// Adds a finished job to the history, forgetting the oldest jobs.
+    fn record(&mut self, info: JobInfo) {
+        self.history.push_back(info);
+        while self.history.len() > self.retention.max_count {
+            self.history.pop_front();
+        }
+    }
+
+    /// Forgets the finished jobs older than the retention allows, or all of them.
+    ///
+    /// Returns the number of forgotten jobs.
+    fn purge(&mut self, all: bool) -> usize {
+        let before = self.history.len();
+        let max_age = i64::try_from(self.retention.max_age)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .filter(|max_age| !max_age.is_zero());
+        match max_age {
+            _ if all => self.history.clear(),
+            Some(max_age) => {
+                let oldest = Local::now() - max_age;
+                self.history
+                    .retain(|info| info.finished_at.is_none_or(|finished| finished >= oldest));
+            }
+            None => {}
+        }
+        before - self.history.len()
+    }
+}
+
+/// Error of [`JobQueue::submit`] when the maximum number of waiting jobs is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueFull {
+    /// Seconds after which the client should try again.
+    pub retry_after: u64,
+}
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "print queue is full, retry in {} seconds",
+            self.retry_after
+        )
+    }
+}
+
+impl IntoResponse for QueueFull {
+    /// Answers with HTTP 429 Too Many Requests and a `Retry-After` header.
+    fn into_response(self) -> Response {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(RETRY_AFTER, self.retry_after.to_string())],
+            Json(PrintResponse {
+                success: false,
+                message: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+/// Result of a history purge, as returned by the purge endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeResult {
+    /// Number of finished jobs removed from the history.
+    pub purged: usize,
+    /// Number of finished jobs left in the history.
+    pub remaining: usize,
+}
+
+/// Summary of the queue, as returned by the queue endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct QueueStatus {
+    /// Whether printing is paused and new jobs are held.
+    pub paused: bool,
+    /// Number of jobs waiting in the queue.
+    pub queued: usize,
+    /// Number of jobs waiting in the offline spool.
+    pub spooled: usize,
+}
+
+/// Print queue of the API server.
+///
+/// Jobs are printed one after another by a single worker thread, so concurrent requests
+/// never interleave on the printer. Jobs may carry an expiry time: a job still waiting
+/// when it expires is dropped with the `expired` status instead of printing stale
+/// information. With offline spooling enabled, jobs failing because the printer is
+/// unreachable are written to disk and retried in order. With verification enabled, the
+/// printer is asked for errors after every job, so a cutter jam or voltage drop fails
+/// the job instead of reporting it printed.
+pub struct JobQueue {
+    state: Mutex<QueueState>,
+    ready: Condvar,
+    spool: Option<Spool>,
+    verify: Option<VerifyConfig>,
+    events: broadcast::Sender<JobInfo>,
+    /// Held while a job is sent to the printer.
+    printer: Mutex<()>,
+}
+
+impl JobQueue {
+    /// Creates a queue and starts its worker thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `spool` - Offline spooling settings, or None to fail jobs while the printer is
+    ///   unreachable. Jobs left in the spool by a previous run are printed first.
+    /// * `verify` - Verification settings, or None to consider every job printed once
+    ///   the printer received it
+    /// * `limits` - Maximum number of waiting jobs, see [`JobQueue::submit`]
+    /// * `history` - Retention of finished jobs. With a `max_age`, a second thread purges
+    ///   older jobs every `purge_interval` seconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Arc<JobQueue>` - The queue, shared between the API handlers and the worker
+    pub fn start(
+        spool: Option<SpoolConfig>,
+        verify: Option<VerifyConfig>,
+        limits: QueueConfig,
+        history: HistoryConfig,
+    ) -> Arc<JobQueue> {
+        let purge_interval =
+            (history.max_age > 0).then(|| Duration::from_secs(history.purge_interval.max(1)));
+        let mut state = QueueState {
+            next_id: 1,
+            limits,
+            retention: history,
+            ..Default::default()
+        };
+        let spool = spool.and_then(|config| match Spool::open(config) {
+            Ok((spool, jobs)) => {
+                if !jobs.is_empty() {
+                    info!("Loaded {} spooled jobs", jobs.len());
+                }
+                state.next_id = spool.highest_id() + 1;
+                state.spooled = jobs
+                    .into_iter()
+                    .map(|job| Job {
+                        info: job.info,
+                        task: job.task,
+                        images: job.images,
+                        done: None,
+                    })
+                    .collect();
+                Some(spool)
+            }
+            Err(e) => {
+                error!("Offline spooling disabled: {}", e);
+                None
+            }
+        });
+
+        let queue = Arc::new(JobQueue {
+            state: Mutex::new(state),
+            ready: Condvar::new(),
+            spool,
+            verify,
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            printer: Mutex::new(()),
+        });
+        let worker = Arc::clone(&queue);
+        thread::spawn(move || worker.run());
+        if let Some(interval) = purge_interval {
+            let purger = Arc::clone(&queue);
+            thread::spawn(move || {
+                loop {
+                    thread::sleep(interval);
+                    let purged = purger.purge(false).purged;
+                    if purged > 0 {
+                        info!("Purged {} jobs from the history", purged);
+                    }
+                }
+            });
+        }
+        queue
+    }
+
+    fn lock(&self) -> MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Adds a task to the end of the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to print. Barcode and 2D code tasks are supported as well.
+    /// * `expires_at` - Optional time after which the task must not be printed anymore
+    /// * `reference` - Optional identifier of the job in the submitting system, stored
+    ///   with the job
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(u64, oneshot::Receiver<JobOutcome>), QueueFull>` - The job ID and a
+    ///   receiver for its outcome, or QueueFull if `max_depth` jobs are already waiting
+    pub fn submit(
+        &self,
+        task: PrintTask,
+        expires_at: Option<DateTime<Local>>,
+        reference: Option<String>,
+    ) -> Result<(u64, oneshot::Receiver<JobOutcome>), QueueFull> {
+        self.enqueue(task, None, expires_at, reference)
+    }
+
+    /// Adds an image job to the end of the queue, see [`JobQueue::submit`].
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The printer settings and the optional caption of the images
+    /// * `images` - The images to print
+    /// * `expires_at` - Optional time after which the images must not be printed anymore
+    /// * `reference` - Optional identifier of the job in the submitting system
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(u64, oneshot::Receiver<JobOutcome>), QueueFull>` - The job ID and a
+    ///   receiver for its outcome, or QueueFull if `max_depth` jobs are already waiting
+    pub fn submit_images(
+        &self,
+        task: PrintTask,
+        images: ImageJob,
+        expires_at: Option<DateTime<Local>>,
+        reference: Option<String>,
+    ) -> Result<(u64, oneshot::Receiver<JobOutcome>), QueueFull> {
+        self.enqueue(task, Some(images), expires_at, reference)
+    }
+
+    fn enqueue(
+        &self,
+        task: PrintTask,
+        images: Option<ImageJob>,
+        expires_at: Option<DateTime<Local>>,
+        reference: Option<String>,
+    ) -> Result<(u64, oneshot::Receiver<JobOutcome>), QueueFull> {
+        let task = filters::redact(task);
+        let mut state = self.lock();
+        let limits = &state.limits;
+        if limits.max_depth > 0 && state.pending.len() >= limits.max_depth {
+            warn!("Print queue is full, rejecting job");
+            metrics::record_rejected();
+            return Err(QueueFull {
+                retry_after: limits.retry_after,
+            });
+        }
+        let (done, outcome) = oneshot::channel();
+        let id = state.next_id;
+        state.next_id += 1;
+        let info = JobInfo {
+            id,
+            status: JobStatus::Queued,
+            title: task.title.clone(),
+            from: task.from.clone(),
+            reference,
+            submitted_at: Local::now(),
+            expires_at,
+            finished_at: None,
+            error: None,
+        };
+        self.publish(&info);
+        state.pending.push_back(Job {
+            info,
+            task,
+            images,
+            done: Some(done),
+        });
+        self.ready.notify_one();
+        Ok((id, outcome))
+    }
+
+    /// Adds a task to the end of the queue and waits until it is printed or spooled.
+    ///
+    /// Used by the command line modes printing without an API server, so their jobs
+    /// go through the same spooling, verification and history as submitted jobs.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - The task to print
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<()>` - Ok(()) once the job is printed or spooled, the error of a
+    ///   failed job, or an I/O error if the job expired, was cancelled or the queue is full
+    pub fn print_blocking(&self, task: PrintTask) -> EscposResult<()> {
+        let (id, outcome) = self
+            .submit(task, None, None)
+            .map_err(|full| PrinterError::Io(full.to_string()))?;
+        match outcome.blocking_recv() {
+            Ok(JobOutcome::Printed) => Ok(()),
+            Ok(JobOutcome::Spooled) => {
+                warn!("Printer unreachable, job {} spooled", id);
+                Ok(())
+            }
+            Ok(JobOutcome::Failed(e)) => Err(e),
+            Ok(JobOutcome::Expired | JobOutcome::Cancelled) | Err(_) => Err(PrinterError::Io(
+                format!("job {} expired or was cancelled", id),
+            )),
+        }
+    }
+
+    /// Removes finished jobs from the history.
+    ///
+    /// # Arguments
+    ///
+    /// * `all` - Whether to remove every finished job instead of only those older than
+    ///   the configured `max_age`
+    ///
+    /// # Returns
+    ///
+    /// * `PurgeResult` - The number of removed and remaining finished jobs
+    pub fn purge(&self, all: bool) -> PurgeResult {
+        let mut state = self.lock();
+        let purged = state.purge(all);
+        PurgeResult {
+            purged,
+            remaining: state.history.len(),
+        }
+    }
+
+    /// Returns the recently finished jobs, the job being printed, the spooled jobs and the
+    /// waiting jobs.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<JobInfo>` - The jobs ordered by ID
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let state = self.lock();
+        let mut jobs: Vec<JobInfo> = state
+            .history
+            .iter()
+            .cloned()
+            .chain(state.current.clone())
+            .chain(state.spooled.iter().map(|job| job.info.clone()))
+            .chain(state.pending.iter().map(|job| job.info.clone()))
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+
+    /// Returns a single job.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID
+    ///
+    /// # Returns
+    ///
+    /// * `Option<JobInfo>` - The job, or None if it is unknown or no longer in the history
+    pub fn job(&self, id: u64) -> Option<JobInfo> {
+        self.jobs().into_iter().find(|job| job.id == id)
+    }
+
+    /// Subscribes to the status changes of the jobs.
+    ///
+    /// Every change, from `queued` to the final status, is sent as the updated job. A
+    /// subscriber that falls more than `EVENT_CAPACITY` events behind receives the number
+    /// of missed events as error, after which the stream ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_id` - Only send the changes of this job, or of all jobs if None
+    ///
+    /// # Returns
+    ///
+    /// * `impl Stream<Item = Result<JobInfo, u64>>` - The stream of job events
+    pub fn events(
+        &self,
+        job_id: Option<u64>,
+    ) -> impl Stream<Item = Result<JobInfo, u64>> + Send + use<> {
+        let mut lagged = false;
+        BroadcastStream::new(self.events.subscribe())
+            .filter(move |event| match (event, job_id) {
+                (Ok(job), Some(id)) => job.id == id,
+                _ => true,
+            })
+            .map(|event| event.map_err(|BroadcastStreamRecvError::Lagged(missed)| missed))
+            .take_while(move |event| {
+                let pass = !lagged;
+                lagged |= event.is_err();
+                pass
+            })
+    }
+
+    /// Runs an operation on the printer between two jobs, such as a status query, so it
+    /// never interleaves with a job being printed.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation, run on the calling thread once no job is printing
+    ///
+    /// # Returns
+    ///
+    /// * `T` - The result of the operation
+    pub fn between_jobs<T>(&self, operation: impl FnOnce() -> T) -> T {
+        let _printer = self.printer.lock().unwrap_or_else(|e| e.into_inner());
+        operation()
+    }
+
+    /// Returns whether the queue is paused and how many jobs are waiting.
+    ///
+    /// # Returns
+    ///
+    /// * `QueueStatus` - The current queue status
+    pub fn status(&self) -> QueueStatus {
+        let state = self.lock();
+        QueueStatus {
+            paused: state.paused,
+            queued: state.pending.len(),
+            spooled: state.spooled.len(),
+        }
+    }
+
+    /// Pauses or resumes printing. While paused, submitted jobs are held in the queue
+    /// and the spool is not retried. The job being printed is finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `paused` - Whether to pause (true) or resume (false) printing
+    pub fn set_paused(&self, paused: bool) {
+        self.lock().paused = paused;
+        info!("Print queue {}", if paused { "paused" } else { "resumed" });
+        self.ready.notify_one();
+    }
+
+    /// Cancels a job that is still waiting in the queue or the spool.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID
+    ///
+    /// # Returns
+    ///
+    /// * `Option<JobInfo>` - The cancelled job, or None if no waiting job has this ID
+    pub fn cancel(&self, id: u64) -> Option<JobInfo> {
+        let mut job = {
+            let mut state = self.lock();
+            if let Some(index) = state.pending.iter().position(|job| job.info.id == id) {
+                state.pending.remove(index)
+            } else {
+                let index = state.spooled.iter().position(|job| job.info.id == id)?;
+                if let Some(spool) = &self.spool {
+                    spool.remove(id);
+                }
+                state.spooled.remove(index)
+            }
+        }?;
+
+        info!("Job {} cancelled", id);
+        let done = job.done.take();
+        let mut info = job.info;
+        info.status = JobStatus::Cancelled;
+        info.finished_at = Some(Local::now());
+        self.publish(&info);
+        self.lock().record(info.clone());
+        if let Some(done) = done {
+            let _ = done.send(JobOutcome::Cancelled);
+        }
+        Some(info)
+    }
+
+    /// Worker loop printing the queued jobs in order and retrying the spooled jobs.
+    fn run(&self) {
+        let retry_interval = self
+            .spool
+            .as_ref()
+            .map(Spool::retry_interval)
+            .unwrap_or_default();
+        let mut retry_at = Instant::now();
+        loop {
+            let job = {
+                let mut state = self.lock();
+                loop {
+                    if !state.paused {
+                        if !state.spooled.is_empty() && Instant::now() >= retry_at {
+                            break None;
+                        }
+                        if let Some(job) = state.pending.pop_front() {
+                            break Some(job);
+                        }
+                    }
+                    state = if state.paused || state.spooled.is_empty() {
+                        self.ready.wait(state).unwrap_or_else(|e| e.into_inner())
+                    } else {
+                        let timeout = retry_at.saturating_duration_since(Instant::now());
+                        self.ready
+                            .wait_timeout(state, timeout)
+                            .unwrap_or_else(|e| e.into_inner())
+                            .0
+                    };
+                }
+            };
+
+            match job {
+                Some(job) => {
+                    let was_empty = self.lock().spooled.is_empty();
+                    self.process(job);
+                    if was_empty {
+                        retry_at = Instant::now() + retry_interval;
+                    }
+                }
+                None => {
+                    self.flush_spool();
+                    retry_at = Instant::now() + retry_interval;
+                }
+            }
+        }
+    }
+
+    /// Prints or spools a job taken from the queue and reports the outcome to its submitter.
+    fn process(&self, mut job: Job) {
+        let done = job.done.take();
+        let outcome = if job.info.expires_at.is_some_and(|at| at <= Local::now()) {
+            warn!("Job {} expired before it could be printed", job.info.id);
+            self.finish(job.info, JobOutcome::Expired)
+        } else if let Some(spool) = &self.spool
+            && !self.lock().spooled.is_empty()
+        {
+            // Nothing is printed before the spooled jobs, so the order is kept.
+            self.spool(spool, job)
+        } else {
+            match (self.print(&job), &self.spool) {
+                (Err(PrinterError::Io(e)), Some(spool)) => {
+                    warn!("Printer unreachable: {}", e);
+                    self.spool(spool, job)
+                }
+                (result, _) => {
+                    summary::record(&job.task, result.is_ok());
+                    self.finish(job.info, result.into())
+                }
+            }
+        };
+
+        if let Some(done) = done {
+            // The submitter may have gone away, e.g. after a client timeout.
+            let _ = done.send(outcome);
+        }
+    }
+
+    /// Prints the spooled jobs in order until the printer fails again.
+    fn flush_spool(&self) {
+        let Some(spool) = &self.spool else {
+            return;
+        };
+        loop {
+            let next = {
+                let mut state = self.lock();
+                if state.paused {
+                    None
+                } else {
+                    state.spooled.pop_front()
+                }
+            };
+            let Some(job) = next else {
+                return;
+            };
+            let now = Local::now();
+            let too_old = job
+                .info
+                .submitted_at
+                .checked_add_signed(spool.max_age())
+                .is_some_and(|at| at <= now);
+            if too_old || job.info.expires_at.is_some_and(|at| at <= now) {
+                warn!(
+                    "Spooled job {} expired before it could be printed",
+                    job.info.id
+                );
+                spool.remove(job.info.id);
+                self.finish(job.info, JobOutcome::Expired);
+                continue;
+            }
+
+            match self.print(&job) {
+                Err(PrinterError::Io(e)) => {
+                    let mut state = self.lock();
+                    state.current = None;
+                    state.spooled.push_front(job);
+                    info!(
+                        "Printer still unreachable, {} jobs spooled: {}",
+                        state.spooled.len(),
+                        e
+                    );
+                    return;
+                }
+                result => {
+                    spool.remove(job.info.id);
+                    summary::record(&job.task, result.is_ok());
+                    self.finish(job.info, result.into());
+                }
+            }
+        }
+    }
+
+    /// Marks a job as being printed and sends it to the printer.
+    fn print(&self, job: &Job) -> EscposResult<()> {
+        let mut info = job.info.clone();
+        info.status = JobStatus::Printing;
+        self.publish(&info);
+        self.lock().current = Some(info);
+
+        let _printer = self.printer.lock().unwrap_or_else(|e| e.into_inner());
+        let printer = metrics::printer_label(job.task.address.as_deref(), job.task.port);
+        let started = Instant::now();
+        let result = self.send(job);
+        metrics::record_print(&printer, started.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Sends a job to the printer and, with verification enabled, checks that the printer
+    /// reports no error afterwards, retransmitting the job as configured.
+    ///
+    /// Printers that cannot be queried are assumed to have printed the job. A reported
+    /// error fails the job with an invalid response error, so it is not spooled.
+    fn send(&self, job: &Job) -> EscposResult<()> {
+        let Some(verify) = &self.verify else {
+            return dispatch(job);
+        };
+        let mut retransmits = 0;
+        loop {
+            dispatch(job)?;
+            thread::sleep(Duration::from_secs(verify.delay));
+
+            let problems = match query_errors(job.task.address.as_deref(), job.task.port) {
+                Ok(problems) => problems,
+                Err(e) => {
+                    warn!("Cannot verify job {}: {}", job.info.id, e);
+                    return Ok(());
+                }
+            };
+            if problems.is_empty() {
+                return Ok(());
+            }
+            let error = format!("the printer reported {}", problems.join(", "));
+            if retransmits >= verify.retransmit {
+                return Err(PrinterError::InvalidResponse(error));
+            }
+            retransmits += 1;
+            warn!(
+                "Job {}: {}, retransmitting ({}/{})",
+                job.info.id, error, retransmits, verify.retransmit
+            );
+        }
+    }
+
+    /// Writes a job to the spool, or fails it if the spool is full.
+    fn spool(&self, spool: &Spool, mut job: Job) -> JobOutcome {
+        if self.lock().spooled.len() >= spool.max_count() {
+            warn!("Spool is full, job {} failed", job.info.id);
+            let error = "the printer is unreachable and the spool is full".to_string();
+            return self.finish(job.info, JobOutcome::Failed(PrinterError::Io(error)));
+        }
+
+        job.info.status = JobStatus::Spooled;
+        let spooled = SpooledJob {
+            info: job.info.clone(),
+            task: job.task.clone(),
+            images: job.images.clone(),
+        };
+        if let Err(e) = spool.save(&spooled) {
+            error!("Cannot spool job {}: {}", job.info.id, e);
+            return self.finish(job.info, JobOutcome::Failed(e));
+        }
+
+        info!("Job {} spooled until the printer is reachable", job.info.id);
+        self.publish(&job.info);
+        let mut state = self.lock();
+        state.current = None;
+        state.spooled.push_back(job);
+        JobOutcome::Spooled
+    }
+
+    /// Records the final status of a job in the history.
+    fn finish(&self, mut info: JobInfo, outcome: JobOutcome) -> JobOutcome {
+        info.status = match &outcome {
+            JobOutcome::Printed => JobStatus::Printed,
+            JobOutcome::Failed(_) => JobStatus::Failed,
+            JobOutcome::Expired => JobStatus::Expired,
+            JobOutcome::Spooled => JobStatus::Spooled,
+            JobOutcome::Cancelled => JobStatus::Cancelled,
+        };
+        match &outcome {
+            JobOutcome::Printed => info!("Job {} printed", info.id),
+            JobOutcome::Failed(e) => info.error = Some(e.to_string()),
+            _ => {}
+        }
+        info.finished_at = Some(Local::now());
+        self.publish(&info);
+
+        let mut state = self.lock();
+        state.current = None;
+        state.record(info);
+        outcome
+    }
+
+    /// Sends a status change of a job to the event subscribers.
+    fn publish(&self, info: &JobInfo) {
+        // Sending only fails without subscribers.
+        let _ = self.events.send(info.clone());
+    }
+}
+
+impl From<EscposResult<()>> for JobOutcome {
+    fn from(result: EscposResult<()>) -> Self {
+        match result {
+            Ok(()) => JobOutcome::Printed,
+            Err(e) => JobOutcome::Failed(e),
+        }
+    }
+}
+
+/// Prints a job as images, barcode, 2D code or framed text, like the print endpoints.
+fn dispatch(job: &Job) -> EscposResult<()> {
+    let task = job.task.clone();
+    if let Some(images) = &job.images {
+        print_images(task, &images.images, images.chunking)
+    } else if task.barcode.is_some() {
+        print_barcode(task)
+    } else if task.encode == Some(true) {
+        print_code_2d(task)
+    } else {
+        print_task(task)
+    }
+}
+
+/// Query parameters of the job list endpoint.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct JobsQuery {
+    /// Only list the jobs submitted with this reference.
+    pub reference: Option<String>,
+}
+
+/// Job list endpoint handler.
+///
+/// This function handles GET requests to `/jobs` and returns the job being printed,
+/// the waiting jobs and the last finished jobs with their status. With
+/// `?reference=...`, only the jobs submitted with that reference are listed.
+///
+/// # Returns
+///
+/// * `Json<Vec<JobInfo>>` - The jobs, oldest first
+pub async fn list_jobs_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<JobsQuery>,
+) -> Json<Vec<JobInfo>> {
+    let jobs = state.queue.jobs();
+    Json(match query.reference {
+        Some(reference) => jobs
+            .into_iter()
+            .filter(|job| job.reference.as_ref() == Some(&reference))
+            .collect(),
+        None => jobs,
+    })
+}
+
+/// Job status endpoint handler.
+///
+/// This function handles GET requests to `/jobs/{id}`.
+///
+/// # Returns
+///
+/// * `Result<Json<JobInfo>, StatusCode>` - The job, or HTTP 404 if it is unknown
+pub async fn get_job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobInfo>, StatusCode> {
+    state.queue.job(id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Job cancel endpoint handler.
+///
+/// This function handles DELETE requests to `/jobs/{id}`. Jobs waiting in the queue or
+/// the spool are dropped with the `cancelled` status.
+///
+/// # Returns
+///
+/// * `Result<Json<JobInfo>, StatusCode>` - The cancelled job, HTTP 404 if the job is
+///   unknown or HTTP 409 Conflict if it is already printing or finished
+pub async fn cancel_job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobInfo>, StatusCode> {
+    match state.queue.cancel(id) {
+        Some(job) => Ok(Json(job)),
+        None if state.queue.job(id).is_some() => Err(StatusCode::CONFLICT),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Queue status endpoint handler.
+///
+/// This function handles GET requests to `/queue`.
+///
+/// # Returns
+///
+/// * `Json<QueueStatus>` - Whether the queue is paused and how many jobs are waiting
+pub async fn queue_status_handler(State(state): State<Arc<AppState>>) -> Json<QueueStatus> {
+    Json(state.queue.status())
+}
+
+/// Queue pause endpoint handler.
+///
+/// This function handles POST requests to `/queue/pause`. Jobs submitted while the
+/// queue is paused are held until it is resumed.
+///
+/// # Returns
+///
+/// * `Json<QueueStatus>` - The queue status after pausing
+pub async fn pause_queue_handler(State(state): State<Arc<AppState>>) -> Json<QueueStatus> {
+    state.queue.set_paused(true);
+    Json(state.queue.status())
+}
+
+/// Queue resume endpoint handler.
+///
+/// This function handles POST requests to `/queue/resume`.
+///
+/// # Returns
+///
+/// * `Json<QueueStatus>` - The queue status after resuming
+pub async fn resume_queue_handler(State(state): State<Arc<AppState>>) -> Json<QueueStatus> {
+    state.queue.set_paused(false);
+    Json(state.queue.status())
+}
+
+/// Query parameters of the purge endpoint.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct PurgeQuery {
+    /// Whether to remove every finished job instead of only the expired ones.
+    pub all: bool,
+}
+
+/// History purge endpoint handler.
+///
+/// This function handles POST requests to `/admin/purge`. Finished jobs older than the
+/// configured `max_age` are removed from the history, or all finished jobs with
+/// `?all=true`. Waiting and spooled jobs are kept.
+///
+/// # Returns
+///
+/// * `Json<PurgeResult>` - The number of removed and remaining finished jobs
+pub async fn purge_history_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PurgeQuery>,
+) -> Json<PurgeResult> {
+    let result = state.queue.purge(query.all);
+    info!("Purged {} jobs from the history", result.purged);
+    Json(result)
+}
+
+/// Options for the queue inspection mode.
+#[derive(ClapArgs)]
+pub struct QueueArgs {
+    #[command(subcommand)]
+    pub action: QueueAction,
+}
+
+/// Actions of the queue inspection mode.
+#[derive(Subcommand)]
+pub enum QueueAction {
+    /// Show the waiting, held and spooled jobs
+    List {
+        /// Include the recently finished jobs
+        #[arg(long)]
+        all: bool,
+    },
+    /// Cancel a waiting or spooled job
+    Cancel {
+        /// ID of the job, as shown by `list`
+        id: u64,
+    },
+    /// Hold new jobs until the queue is resumed
+    Pause,
+    /// Print the held jobs
+    Resume,
+}
+
+/// Inspects and manages the print queue of a running API server.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the `--server` URL
+/// * `queue` - The queue options selecting the action
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on success, an input error for unknown jobs or an I/O
+///   error if the server cannot be reached
+pub fn run_queue(args: &Args, queue: &QueueArgs) -> EscposResult<()> {
+    let server = client::base_url(args.server.as_deref());
+    match queue.action {
+        QueueAction::List { all } => {
+            let status: QueueStatus = request(ureq::get(&format!("{}/queue", server)))?;
+            let jobs: Vec<JobInfo> = request(ureq::get(&format!("{}/jobs", server)))?;
+            println!("{}", render_jobs(&status, &jobs, all));
+        }
+        QueueAction::Cancel { id } => {
+            let job: JobInfo = match ureq::delete(&format!("{}/jobs/{}", server, id)).call() {
+                Err(ureq::Error::Status(404, _)) => {
+                    return Err(PrinterError::Input(format!("job {} not found", id)));
+                }
+                Err(ureq::Error::Status(409, _)) => {
+                    return Err(PrinterError::Input(format!(
+                        "job {} is not waiting anymore",
+                        id
+                    )));
+                }
+                response => parse_response(response)?,
+            };
+            println!("Cancelled job {} {}", job.id, job.title.unwrap_or_default());
+        }
+        QueueAction::Pause | QueueAction::Resume => {
+            let action = match queue.action {
+                QueueAction::Pause => "pause",
+                _ => "resume",
+            };
+            let status: QueueStatus = request(ureq::post(&format!("{}/queue/{}", server, action)))?;
+            println!("{}", status_line(&status));
+        }
+    }
+    Ok(())
+}
+
+fn status_line(status: &QueueStatus) -> String {
+    format!(
+        "Queue {}: {} queued, {} spooled",
+        if status.paused { "paused" } else { "running" },
+        status.queued,
+        status.spooled
+    )
+}
+
+/// Renders the jobs as a table for the terminal.
+fn render_jobs(status: &QueueStatus, jobs: &[JobInfo], all: bool) -> String {
+    let mut table = Table::new(&[6, 9, 8, 12, 30])
+        .borders(false)
+        .row(&["ID", "STATUS", "TIME", "FROM", "TITLE"])
+        .rule();
+    for job in jobs {
+        let waiting = matches!(
+            job.status,
+            JobStatus::Queued | JobStatus::Spooled | JobStatus::Printing
+        );
+        if !all && !waiting {
+            continue;
+        }
+        let status = match job.status {
+            JobStatus::Queued if status.paused => "held",
+            other => other.as_str(),
+        };
+        table = table.row(&[
+            job.id.to_string(),
+            status.to_string(),
+            job.submitted_at.format("%H:%M:%S").to_string(),
+            job.from.clone().unwrap_or_default(),
+            job.title.clone().unwrap_or_default(),
+        ]);
+    }
+    let rendered = table.render();
+    let rows: Vec<&str> = rendered.lines().map(str::trim_end).collect();
+    format!("{}\n{}", status_line(status), rows.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_expired_jobs() {
+        let queue = JobQueue::start(None, None, QueueConfig::default(), HistoryConfig::default());
+        let task = PrintTask {
+            message: "Stale".to_string(),
+            // Nothing listens here, the job must not even be sent.
+            address: Some("127.0.0.1".to_string()),
+            port: Some(1),
+            ..Default::default()
+        };
+        let expired = Local::now() - TimeDelta::minutes(1);
+        let (id, outcome) = queue.submit(task, Some(expired), None).unwrap();
+
+        assert!(matches!(outcome.blocking_recv(), Ok(JobOutcome::Expired)));
+        let job = queue.job(id).unwrap();
+        assert_eq!(job.status, JobStatus::Expired);
+        assert!(job.finished_at.is_some());
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{Local, NaiveTime};
+use escpos::errors::{PrinterError, Result as EscposResult};
+
+/// Time range of the day in which nothing is printed, e.g. `22:00-07:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Parses a time range such as `22:00-07:00`.
+    ///
+    /// # Arguments
+    ///
+    /// * `range` - Start and end time in 24-hour `HH:MM` format, separated by a dash.
+    ///   The range may span midnight.
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<QuietHours>` - The range, or an input error for malformed ranges
+    pub fn parse(range: &str) -> EscposResult<Self> {
+        let invalid = || {
+            PrinterError::Input(format!(
+                "invalid quiet hours {}, expected e.g. 22:00-07:00",
+                range
+            ))
+        };
+        let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+        let parse = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        Ok(QuietHours {
+            start: parse(start).ok_or_else(invalid)?,
+            end: parse(end).ok_or_else(invalid)?,
+        })
+    }
+
+    /// Returns whether a time falls into the range, which may span midnight.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// Returns whether the current local time falls into the range.
+    pub fn is_active(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn contains_range_within_a_day() {
+        let quiet = QuietHours::parse("12:00-13:30").unwrap();
+        assert!(quiet.contains(time(12, 0)));
+        assert!(quiet.contains(time(13, 29)));
+        assert!(!quiet.contains(time(13, 30)));
+        assert!(!quiet.contains(time(11, 59)));
+    }
+
+    #[test]
+    fn contains_range_spanning_midnight() {
+        let quiet = QuietHours::parse("22:00 - 07:00").unwrap();
+        assert!(quiet.contains(time(23, 15)));
+        assert!(quiet.contains(time(0, 0)));
+        assert!(quiet.contains(time(6, 59)));
+        assert!(!quiet.contains(time(7, 0)));
+        assert!(!quiet.contains(time(21, 59)));
+    }
+
+    #[test]
+    fn rejects_malformed_ranges() {
+        for range in ["22:00", "22:00-", "25:00-07:00", "late-early"] {
+            assert!(matches!(
+                QuietHours::parse(range),
+                Err(PrinterError::Input(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use escpos::errors::{PrinterError, Result as EscposResult};
+use image::{DynamicImage, GrayImage, imageops::FilterType};
+use serde::{Deserialize, Serialize};
+
+/// Printable width in dots of common 58mm thermal printers.
+pub const DEFAULT_WIDTH: u32 = 384;
+
+/// Luminance threshold separating black from white dots.
+const THRESHOLD: i16 = 128;
+
+/// Method used to reduce grayscale images to black and white dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dither {
+    /// Plain threshold, best for logos, text and line art.
+    Threshold,
+    /// Floyd-Steinberg error diffusion, best for photos.
+    FloydSteinberg,
+    /// Atkinson error diffusion, higher contrast than Floyd-Steinberg.
+    Atkinson,
+}
+
+impl Dither {
+    /// Parses a dithering name (`none`/`threshold`, `floyd-steinberg`, `atkinson`),
+    /// defaulting to Floyd-Steinberg.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The optional dithering name, case-insensitive
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Dither>` - The matching method, or an input error for unknown names
+    pub fn parse(name: Option<&str>) -> EscposResult<Dither> {
+        match name.map(|n| n.to_lowercase().replace('_', "-")).as_deref() {
+            Some("none") | Some("threshold") => Ok(Dither::Threshold),
+            None | Some("floyd-steinberg") | Some("fs") => Ok(Dither::FloydSteinberg),
+            Some("atkinson") => Ok(Dither::Atkinson),
+            Some(other) => Err(PrinterError::Input(format!(
+                "unsupported dithering method: {}",
+                other
+            ))),
+        }
+    }
+
+    /// Error diffusion kernel as `(dx, dy, weight)` entries and the weight divisor.
+    fn kernel(self) -> (&'static [(i32, i32, i16)], i16) {
+        match self {
+            Dither::Threshold => (&[], 1),
+            Dither::FloydSteinberg => (&[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)], 16),
+            Dither::Atkinson => (
+                &[
+                    (1, 0, 1),
+                    (2, 0, 1),
+                    (-1, 1, 1),
+                    (0, 1, 1),
+                    (1, 1, 1),
+                    (0, 2, 1),
+                ],
+                8,
+            ),
+        }
+    }
+}
+
+/// A black and white image packed into rows of bytes, eight dots per byte with the most
+/// significant bit leftmost, as expected by the ESC/POS raster commands.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bitmap {
+    /// Width in dots, always a multiple of eight.
+    pub width: u32,
+    /// Height in dots.
+    pub height: u32,
+    /// Packed rows, `width / 8` bytes each.
+    pub data: Vec<u8>,
+}
+
+impl Bitmap {
+    /// Decodes an image file (PNG, JPEG, GIF, BMP or WebP) and converts it to a bitmap.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The encoded image
+    /// * `max_width` - Maximum width in dots, larger images are scaled down
+    /// * `dither` - The dithering method
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Bitmap>` - The bitmap, or an input error if the image cannot be decoded
+    pub fn decode(bytes: &[u8], max_width: u32, dither: Dither) -> EscposResult<Bitmap> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|e| PrinterError::Input(format!("cannot decode image: {}", e)))?;
+        Bitmap::from_image(&image, max_width, dither)
+    }
+
+    /// Converts a decoded image to a bitmap.
+    ///
+    /// Transparent areas are treated as white paper. Images wider than `max_width` are
+    /// scaled down keeping the aspect ratio, narrower images keep their size. The width
+    /// is padded with white to a multiple of eight dots.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The decoded image
+    /// * `max_width` - Maximum width in dots
+    /// * `dither` - The dithering method
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<Bitmap>` - The bitmap, or an input error for empty images
+    pub fn from_image(
+        image: &DynamicImage,
+        max_width: u32,
+        dither: Dither,
+    ) -> EscposResult<Bitmap> {
+        if image.width() == 0 || image.height() == 0 {
+            return Err(PrinterError::Input("image is empty".to_string()));
+        }
+
+        let rgba = image.to_rgba8();
+        let gray = GrayImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+            let [r, g, b, a] = rgba.get_pixel(x, y).0;
+            let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+            let alpha = a as u32;
+            image::Luma([((luma * alpha + 255 * (255 - alpha)) / 255) as u8])
+        });
+
+        let max_width = max_width.max(8);
+        let gray = if gray.width() > max_width {
+            let height = (gray.height() as u64 * max_width as u64 / gray.width() as u64).max(1);
+            image::imageops::resize(&gray, max_width, height as u32, FilterType::Triangle)
+        } else {
+            gray
+        };
+        if gray.height() > u16::MAX as u32 {
+            return Err(PrinterError::Input(format!(
+                "image is too tall to print ({} dots)",
+                gray.height()
+            )));
+        }
+        Ok(Bitmap::from_gray(&gray, dither))
+    }
+
+    /// Converts a grayscale image to a bitmap without scaling.
+    ///
+    /// # Arguments
+    ///
+    /// * `gray` - The grayscale image
+    /// * `dither` - The dithering method
+    ///
+    /// # Returns
+    ///
+    /// * `Bitmap` - The dithered bitmap
+    pub fn from_gray(gray: &GrayImage, dither: Dither) -> Bitmap {
+        let (width, height) = (gray.width() as i32, gray.height() as i32);
+        let mut levels: Vec<i16> = gray.pixels().map(|p| p.0[0] as i16).collect();
+        let (kernel, divisor) = dither.kernel();
+
+        let padded = (width as u32).div_ceil(8) * 8;
+        let row_bytes = padded as usize / 8;
+        let mut data = vec![0u8; row_bytes * height as usize];
+
+        for y in 0..height {
+            for x in 0..width {
+                let index = (y * width + x) as usize;
+                let old = levels[index];
+                let black = old < THRESHOLD;
+                if black {
+                    data[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+                }
+
+                let error = old - if black { 0 } else { 255 };
+                for &(dx, dy, weight) in kernel {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx >= 0 && nx < width && ny < height {
+                        levels[(ny * width + nx) as usize] += error * weight / divisor;
+                    }
+                }
+            }
+        }
+
+        Bitmap {
+            width: padded,
+            height: height as u32,
+            data,
+        }
+    }
+
+    /// Splits the bitmap into horizontal bands of at most `rows` dot rows each.
+    ///
+    /// The bands printed one after another as separate raster commands yield the same
+    /// image, but each command is small enough for the receive buffer of the printer.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - Maximum height of a band in dots, 0 keeps the bitmap in one piece
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<Bitmap>` - The bands from top to bottom
+    pub fn bands(&self, rows: u32) -> Vec<Bitmap> {
+        if rows == 0 || rows >= self.height {
+            return vec![self.clone()];
+        }
+        let row_bytes = (self.width / 8) as usize;
+        self.data
+            .chunks(row_bytes * rows as usize)
+            .map(|data| Bitmap {
+                width: self.width,
+                height: (data.len() / row_bytes) as u32,
+                data: data.to_vec(),
+            })
+            .collect()
+    }
+
+    /// Encodes the bitmap as a `GS v 0` raster bit image command.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<u8>` - The complete command including the image data
+    pub fn raster_command(&self) -> Vec<u8> {
+        let row_bytes = (self.width / 8) as u16;
+        let height = self.height as u16;
+        let mut command = vec![
+            0x1D,
+            0x76,
+            0x30,
+            0x00,
+            (row_bytes & 0xFF) as u8,
+            (row_bytes >> 8) as u8,
+            (height & 0xFF) as u8,
+            (height >> 8) as u8,
+        ];
+        command.extend_from_slice(&self.data);
+        command
+    }
+}
@@ -0,0 +1,90 @@
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+/// Text printed instead of a secret.
+const MASK: &str = "****";
+
+/// Authorization headers and similar, the scheme is kept.
+static AUTH_SCHEME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(bearer|basic|token)(\s+)[A-Za-z0-9._~+/=-]{8,}").unwrap());
+
+/// Assignments such as `password=...` or `"api_key": "..."`, the name is kept.
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b((?:api|access|secret|private|auth)[_-]?(?:key|token)|token|secret|password|passwd|pwd)("?\s*[:=]\s*"?)[^\s"',;&]{4,}"#,
+    )
+    .unwrap()
+});
+
+/// Tokens with a well-known prefix: GitHub, GitLab, Slack, AWS access keys, Stripe and
+/// OpenAI style keys and JSON web tokens.
+static TOKEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\b(?:gh[pousr]_[A-Za-z0-9]{30,}|github_pat_[A-Za-z0-9_]{30,}|glpat-[A-Za-z0-9_-]{20,}",
+        r"|xox[abprs]-[A-Za-z0-9-]{10,}|AKIA[0-9A-Z]{16}|[sr]k_(?:live|test)_[A-Za-z0-9]{16,}",
+        r"|sk-[A-Za-z0-9_-]{20,}|eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,})",
+    ))
+    .unwrap()
+});
+
+/// Runs of 13 to 19 digits, optionally grouped by spaces or dashes.
+static CARD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b\d(?:[ -]?\d){12,18}\b").unwrap());
+
+/// Masks obvious secrets in a text.
+///
+/// Replaces bearer and basic credentials, values assigned to names like `password` or
+/// `api_key`, tokens with a well-known prefix (GitHub, GitLab, Slack, AWS, Stripe, JWT)
+/// and card numbers passing the Luhn check. Card numbers keep their last four digits.
+///
+/// # Arguments
+///
+/// * `text` - The text to scrub
+///
+/// # Returns
+///
+/// * `String` - The text with every detected secret replaced by `****`
+///
+/// # Examples
+///
+/// ```
+/// use taskprinter::redact::secrets;
+///
+/// assert_eq!(
+///     secrets("Authorization: Bearer abcdef123456 card 4111 1111 1111 1111"),
+///     "Authorization: Bearer **** card **** 1111"
+/// );
+/// ```
+pub fn secrets(text: &str) -> String {
+    let text = TOKEN.replace_all(text, MASK);
+    let text = AUTH_SCHEME.replace_all(&text, format!("${{1}}${{2}}{}", MASK));
+    let text = ASSIGNMENT.replace_all(&text, format!("${{1}}${{2}}{}", MASK));
+    CARD.replace_all(&text, |captures: &Captures| {
+        let number = &captures[0];
+        let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+        if luhn(&digits) {
+            let last: String = digits[digits.len() - 4..]
+                .iter()
+                .map(|digit| char::from_digit(*digit, 10).unwrap_or('0'))
+                .collect();
+            format!("{} {}", MASK, last)
+        } else {
+            number.to_string()
+        }
+    })
+    .into_owned()
+}
+
+/// Checks the Luhn checksum used by payment card numbers.
+fn luhn(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
@@ -0,0 +1,44 @@
+use crate::cli::Args;
+use crate::metrics::parse_printer;
+use crate::printer;
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use std::fs;
+use std::path::PathBuf;
+
+/// Options for the replay mode.
+#[derive(ClapArgs)]
+pub struct ReplayArgs {
+    /// File with the printer commands captured by `--output`
+    pub file: PathBuf,
+
+    /// Printer to send the commands to as `host` or `host:port`, instead of `--address`
+    /// and `--port`
+    #[arg(long)]
+    pub printer: Option<String>,
+}
+
+/// Sends a byte stream captured with `--output` to a printer unchanged.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `replay` - The replay options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) once the stream is sent, or an error if the file cannot
+///   be read or the printer cannot be reached
+pub fn run_replay(args: &Args, replay: &ReplayArgs) -> EscposResult<()> {
+    let data = fs::read(&replay.file).map_err(|e| {
+        PrinterError::Input(format!("cannot read {}: {}", replay.file.display(), e))
+    })?;
+    let (address, port) = match &replay.printer {
+        Some(printer) => {
+            let (address, port) = parse_printer(printer);
+            (Some(address), port.or(args.port))
+        }
+        None => (args.address.clone(), args.port),
+    };
+    printer::replay(address.as_deref(), port, &data)
+}
@@ -0,0 +1,295 @@
+use crate::config::{ScheduleConfig, ScheduleSource};
+use crate::digest::digest_message;
+use crate::fortune::random_fortune;
+use crate::i18n::{self, Label};
+use crate::pomodoro::{self, Phase};
+use crate::printer::PrintTask;
+use crate::queue::JobQueue;
+use crate::weather;
+use chrono::{DateTime, Datelike, Days, Local, NaiveTime, TimeDelta, Weekday};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{info, warn};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A scheduled slip with its parsed time and weekdays.
+struct Schedule {
+    config: ScheduleConfig,
+    time: NaiveTime,
+    /// Days the slip is printed on, empty for every day.
+    weekdays: Vec<Weekday>,
+}
+
+impl Schedule {
+    fn parse(config: ScheduleConfig) -> EscposResult<Schedule> {
+        let time = NaiveTime::parse_from_str(&config.time, "%H:%M")
+            .map_err(|_| PrinterError::Input(format!("invalid schedule time: {}", config.time)))?;
+        let weekdays = config
+            .weekdays
+            .iter()
+            .map(|weekday| {
+                weekday.parse().map_err(|_| {
+                    PrinterError::Input(format!("invalid schedule weekday: {}", weekday))
+                })
+            })
+            .collect::<EscposResult<Vec<Weekday>>>()?;
+        if config.source == ScheduleSource::Message && config.message.is_none() {
+            return Err(PrinterError::Input(format!(
+                "the message slip at {} requires a message",
+                config.time
+            )));
+        }
+        if config.source == ScheduleSource::Weather
+            && (config.latitude.is_none() || config.longitude.is_none())
+        {
+            return Err(PrinterError::Input(format!(
+                "the weather slip at {} requires a latitude and longitude",
+                config.time
+            )));
+        }
+        Ok(Schedule {
+            config,
+            time,
+            weekdays,
+        })
+    }
+}
+
+/// Starts printing the configured slips at their times.
+///
+/// Every slip is built when it is due, so a fortune slip shows a new quote and a weather
+/// slip the forecast of the day, and submitted to the print queue. Slips that cannot be
+/// built, e.g. because the weather service is unreachable, are skipped with a warning.
+/// A pomodoro session submits a slip per phase while it runs.
+///
+/// # Arguments
+///
+/// * `schedules` - The `[[schedule]]` entries of the configuration
+/// * `queue` - The print queue of the API server
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for an invalid time or weekday, a
+///   message slip without message or a weather slip without location
+pub fn start(schedules: Vec<ScheduleConfig>, queue: Arc<JobQueue>) -> EscposResult<()> {
+    let schedules = schedules
+        .into_iter()
+        .map(Schedule::parse)
+        .collect::<EscposResult<Vec<_>>>()?;
+
+    for schedule in schedules {
+        let queue = Arc::clone(&queue);
+        tokio::spawn(async move {
+            let name = source_name(schedule.config.source);
+            loop {
+                let at = next_run(Local::now(), &schedule.weekdays, schedule.time);
+                info!("Next {} slip at {}", name, at.format("%Y-%m-%d %H:%M"));
+                let wait = (at - Local::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+
+                // Fetching the forecast blocks, so slips are built off the runtime.
+                let config = schedule.config.clone();
+                let slips = tokio::task::spawn_blocking(move || scheduled_slips(&config))
+                    .await
+                    .unwrap_or_else(|e| Err(PrinterError::Io(e.to_string())));
+                match slips {
+                    Ok(slips) => {
+                        tokio::spawn(submit_slips(name, slips, Arc::clone(&queue)));
+                    }
+                    Err(e) => warn!("Cannot build the {} slip: {:?}", name, e),
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Returns the first time after `now` at the given time on one of the weekdays.
+///
+/// # Arguments
+///
+/// * `now` - The time to start looking from
+/// * `weekdays` - The days to consider, or an empty slice for every day
+/// * `time` - The local time of the day
+///
+/// # Returns
+///
+/// * `DateTime<Local>` - The next matching time
+pub fn next_run(now: DateTime<Local>, weekdays: &[Weekday], time: NaiveTime) -> DateTime<Local> {
+    (0..=7)
+        .filter_map(|days| now.date_naive().checked_add_days(Days::new(days)))
+        .filter(|date| weekdays.is_empty() || weekdays.contains(&date.weekday()))
+        // Times skipped by a daylight saving change are left out.
+        .filter_map(|date| date.and_time(time).and_local_timezone(Local).earliest())
+        .find(|at| *at > now)
+        .unwrap_or_else(|| now + TimeDelta::weeks(1))
+}
+
+/// Submits the slips of a schedule entry, waiting the minutes of each before the next.
+async fn submit_slips(name: &'static str, slips: Vec<(PrintTask, u64)>, queue: Arc<JobQueue>) {
+    for (task, minutes) in slips {
+        match queue.submit(task, None, None) {
+            Ok((id, _)) => info!("Queued the {} slip as job {}", name, id),
+            Err(e) => warn!("Cannot print the {} slip: {}", name, e),
+        }
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+    }
+}
+
+/// Builds the slips of a schedule entry, each with the minutes until the next one.
+fn scheduled_slips(config: &ScheduleConfig) -> EscposResult<Vec<(PrintTask, u64)>> {
+    let slip = |label: Label, message: String| PrintTask {
+        title: Some(config.title.clone().unwrap_or_else(|| i18n::text(label))),
+        message,
+        address: config.address.clone(),
+        port: config.port,
+        ..Default::default()
+    };
+    let (label, message) = match config.source {
+        ScheduleSource::Message => (Label::Note, config.message.clone().unwrap_or_default()),
+        ScheduleSource::Fortune => (Label::Fortune, random_fortune(config.file.as_deref())?),
+        ScheduleSource::Weather => (
+            Label::Weather,
+            weather::today(
+                config.latitude.unwrap_or_default(),
+                config.longitude.unwrap_or_default(),
+            )?,
+        ),
+        ScheduleSource::Digest => (Label::Digest, digest_message(config)?),
+        ScheduleSource::Pomodoro => {
+            let phases = pomodoro::phases(config.work, config.break_minutes, config.cycles);
+            return Ok(phases
+                .into_iter()
+                .map(|Phase { message, minutes }| (slip(Label::Pomodoro, message), minutes))
+                .collect());
+        }
+    };
+    Ok(vec![(slip(label, message), 0)])
+}
+
+/// Name of a source in log messages.
+fn source_name(source: ScheduleSource) -> &'static str {
+    match source {
+        ScheduleSource::Message => "message",
+        ScheduleSource::Fortune => "fortune",
+        ScheduleSource::Weather => "weather",
+        ScheduleSource::Digest => "digest",
+        ScheduleSource::Pomodoro => "pomodoro",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        // 2024-03-04 is a Monday.
+        Local
+            .with_ymd_and_hms(2024, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn next_run_is_later_today_or_tomorrow() {
+        assert_eq!(next_run(at(4, 6, 0), &[], time(7, 30)), at(4, 7, 30));
+        assert_eq!(next_run(at(4, 7, 30), &[], time(7, 30)), at(5, 7, 30));
+    }
+
+    #[test]
+    fn next_run_skips_other_weekdays() {
+        let weekdays = [
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        // Friday after the time, then the weekend.
+        assert_eq!(next_run(at(8, 9, 0), &weekdays, time(7, 30)), at(11, 7, 30));
+        assert_eq!(
+            next_run(at(4, 9, 0), &[Weekday::Mon], time(7, 30)),
+            at(11, 7, 30)
+        );
+    }
+
+    #[test]
+    fn parses_schedule_entries() {
+        let schedule = Schedule::parse(ScheduleConfig {
+            time: "07:30".to_string(),
+            weekdays: vec!["mon".to_string(), "Friday".to_string()],
+            source: ScheduleSource::Fortune,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(schedule.time, time(7, 30));
+        assert_eq!(schedule.weekdays, [Weekday::Mon, Weekday::Fri]);
+    }
+
+    #[test]
+    fn rejects_invalid_schedule_entries() {
+        let invalid = [
+            ScheduleConfig {
+                time: "7.30".to_string(),
+                source: ScheduleSource::Fortune,
+                ..Default::default()
+            },
+            ScheduleConfig {
+                weekdays: vec!["someday".to_string()],
+                source: ScheduleSource::Fortune,
+                ..Default::default()
+            },
+            ScheduleConfig::default(),
+            ScheduleConfig {
+                source: ScheduleSource::Weather,
+                latitude: Some(52.52),
+                ..Default::default()
+            },
+        ];
+        for config in invalid {
+            assert!(matches!(
+                Schedule::parse(config),
+                Err(PrinterError::Input(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn builds_message_slips() {
+        let slips = scheduled_slips(&ScheduleConfig {
+            message: Some("Plan for {weekday}".to_string()),
+            port: Some(9100),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(slips.len(), 1);
+        let (task, minutes) = &slips[0];
+        assert_eq!(*minutes, 0);
+        assert_eq!(task.title.as_deref(), Some("NOTE"));
+        assert_eq!(task.message, "Plan for {weekday}");
+        assert_eq!(task.port, Some(9100));
+    }
+
+    #[test]
+    fn builds_a_slip_per_pomodoro_phase() {
+        let slips = scheduled_slips(&ScheduleConfig {
+            source: ScheduleSource::Pomodoro,
+            work: 50,
+            break_minutes: 10,
+            cycles: 2,
+            ..Default::default()
+        })
+        .unwrap();
+        let minutes: Vec<u64> = slips.iter().map(|(_, minutes)| *minutes).collect();
+        assert_eq!(minutes, [50, 10, 50, 0]);
+        assert!(
+            slips
+                .iter()
+                .all(|(task, _)| task.title.as_deref() == Some("POMODORO"))
+        );
+    }
+}
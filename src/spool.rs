@@ -0,0 +1,346 @@
+use crate::config::SpoolConfig;
+use crate::counters;
+use crate::printer::PrintTask;
+use crate::queue::{ImageJob, JobInfo};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use chrono::TimeDelta;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, warn};
+use pbkdf2::pbkdf2_hmac_array;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Length of the random nonce stored in front of every encrypted job.
+const NONCE_LEN: usize = 12;
+
+/// Length of the random salt the spool key is derived with.
+const SALT_LEN: usize = 16;
+
+/// PBKDF2-HMAC-SHA256 rounds deriving the spool key from the passphrase.
+#[cfg(not(test))]
+const KDF_ROUNDS: u32 = 600_000;
+
+/// Fewer rounds keep the tests fast in unoptimized builds.
+#[cfg(test)]
+const KDF_ROUNDS: u32 = 1_000;
+
+/// A job written to the spool directory.
+#[derive(Serialize, Deserialize)]
+pub struct SpooledJob {
+    /// Status information of the job.
+    pub info: JobInfo,
+    /// The task to print.
+    pub task: PrintTask,
+    /// The images of image and PDF jobs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImageJob>,
+}
+
+/// Directory of jobs waiting for an unreachable printer.
+///
+/// Every job is stored in its own JSON file named after the zero padded job ID, so the
+/// spool survives restarts and is printed in submission order. With a key, the files
+/// are encrypted with ChaCha20-Poly1305 and end in `.enc` instead of `.json`. The cipher
+/// key is derived from the passphrase with PBKDF2-HMAC-SHA256 and a random salt kept in
+/// the `salt` file of the directory.
+pub struct Spool {
+    dir: PathBuf,
+    config: SpoolConfig,
+    cipher: Option<ChaCha20Poly1305>,
+}
+
+impl Spool {
+    /// Opens the spool directory, creating it if needed, and loads the jobs left in it.
+    ///
+    /// Files that cannot be read or decrypted, e.g. after the key was changed, are logged
+    /// and renamed to end in `.failed`, so they are kept for recovery but not retried.
+    /// Plain JSON files are loaded also with a key, so enabling encryption keeps the jobs
+    /// spooled before.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The spool settings
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<(Spool, Vec<SpooledJob>)>` - The spool and its jobs ordered by ID,
+    ///   or an I/O error if the directory cannot be created or listed or its salt cannot
+    ///   be read or created
+    pub fn open(config: SpoolConfig) -> EscposResult<(Spool, Vec<SpooledJob>)> {
+        let dir = config
+            .dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| counters::state_dir().join("spool"));
+        fs::create_dir_all(&dir).map_err(|e| {
+            PrinterError::Io(format!("cannot create spool {}: {}", dir.display(), e))
+        })?;
+
+        let key = config
+            .key
+            .clone()
+            .or_else(|| std::env::var("TASKPRINTER_SPOOL_KEY").ok())
+            .filter(|key| !key.is_empty());
+        let cipher = key.map(|key| derive_cipher(&dir, &key)).transpose()?;
+        let spool = Spool {
+            dir,
+            cipher,
+            config,
+        };
+
+        let entries = fs::read_dir(&spool.dir).map_err(|e| {
+            PrinterError::Io(format!("cannot read spool {}: {}", spool.dir.display(), e))
+        })?;
+        let paths = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "json" || ext == "enc")
+            });
+        let mut jobs = Vec::new();
+        for path in paths {
+            match spool.read(&path) {
+                Ok(job) => jobs.push(job),
+                Err(e) => spool.quarantine(&path, &e),
+            }
+        }
+        jobs.sort_by_key(|job: &SpooledJob| job.info.id);
+
+        Ok((spool, jobs))
+    }
+
+    /// Reads a plain or encrypted job file.
+    fn read(&self, path: &Path) -> Result<SpooledJob, String> {
+        let content = fs::read(path).map_err(|e| e.to_string())?;
+        let json = if path.extension().is_some_and(|ext| ext == "enc") {
+            let cipher = self
+                .cipher
+                .as_ref()
+                .ok_or("the job is encrypted, but no spool key is set")?;
+            if content.len() < NONCE_LEN {
+                return Err("the file is truncated".to_string());
+            }
+            let (nonce, ciphertext) = content.split_at(NONCE_LEN);
+            cipher
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| "cannot decrypt the job, the spool key is wrong".to_string())?
+        } else {
+            content
+        };
+        serde_json::from_slice(&json).map_err(|e| e.to_string())
+    }
+
+    /// Moves a job file that cannot be loaded aside by appending `.failed` to its name.
+    fn quarantine(&self, path: &Path, reason: &str) {
+        let mut failed = path.as_os_str().to_owned();
+        failed.push(".failed");
+        match fs::rename(path, &failed) {
+            Ok(()) => error!(
+                "Cannot load spooled job {}, moved it to {}: {}",
+                path.display(),
+                Path::new(&failed).display(),
+                reason
+            ),
+            Err(e) => error!(
+                "Cannot load spooled job {}: {}, and cannot move it aside: {}",
+                path.display(),
+                reason,
+                e
+            ),
+        }
+    }
+
+    /// Returns the highest job ID of the files in the spool directory.
+    ///
+    /// Files that could not be loaded count as well, so new jobs never reuse their IDs.
+    ///
+    /// # Returns
+    ///
+    /// * `u64` - The highest ID, or 0 if the spool is empty
+    pub fn highest_id(&self) -> u64 {
+        fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                name.to_str()?.split('.').next()?.parse::<u64>().ok()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Writes a job to the spool.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job to store
+    ///
+    /// # Returns
+    ///
+    /// * `EscposResult<()>` - Ok(()) once the job is on disk, or an I/O error
+    pub fn save(&self, job: &SpooledJob) -> EscposResult<()> {
+        let path = self.path(job.info.id);
+        // Write to a temporary file first so a crash never leaves a truncated job.
+        let temp = path.with_extension("tmp");
+        let mut content = serde_json::to_vec(job).map_err(|e| PrinterError::Io(e.to_string()))?;
+        if let Some(cipher) = &self.cipher {
+            let nonce: [u8; NONCE_LEN] = rand::random();
+            let ciphertext = cipher
+                .encrypt(Nonce::from_slice(&nonce), content.as_slice())
+                .map_err(|e| PrinterError::Io(format!("cannot encrypt job: {}", e)))?;
+            content = [nonce.as_slice(), &ciphertext].concat();
+        }
+        fs::write(&temp, content).map_err(|e| PrinterError::Io(e.to_string()))?;
+        fs::rename(&temp, &path).map_err(|e| PrinterError::Io(e.to_string()))
+    }
+
+    /// Deletes a job from the spool once it is printed or dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The job ID
+    pub fn remove(&self, id: u64) {
+        // Jobs spooled before encryption was enabled are still plain JSON files.
+        for extension in ["enc", "json"] {
+            match fs::remove_file(self.dir.join(format!("{:010}.{}", id, extension))) {
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => warn!("Cannot remove spooled job {}: {}", id, e),
+                Ok(()) => {}
+            }
+        }
+    }
+
+    /// Time between attempts to print the spooled jobs.
+    pub fn retry_interval(&self) -> Duration {
+        Duration::from_secs(self.config.retry_interval.max(1))
+    }
+
+    /// Age after which a spooled job is dropped as expired.
+    pub fn max_age(&self) -> TimeDelta {
+        i64::try_from(self.config.max_age)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .unwrap_or(TimeDelta::MAX)
+    }
+
+    /// Maximum number of spooled jobs.
+    pub fn max_count(&self) -> usize {
+        self.config.max_count
+    }
+
+    fn path(&self, id: u64) -> PathBuf {
+        let extension = if self.cipher.is_some() { "enc" } else { "json" };
+        self.dir.join(format!("{:010}.{}", id, extension))
+    }
+}
+
+/// Derives the cipher of the spool from the passphrase and the salt stored in the spool
+/// directory, creating the salt on first use.
+fn derive_cipher(dir: &Path, passphrase: &str) -> EscposResult<ChaCha20Poly1305> {
+    let path = dir.join("salt");
+    let salt = match fs::read(&path) {
+        Ok(salt) if salt.len() == SALT_LEN => salt,
+        Ok(_) => {
+            return Err(PrinterError::Io(format!(
+                "the spool salt {} is corrupt",
+                path.display()
+            )));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let salt: [u8; SALT_LEN] = rand::random();
+            fs::write(&path, salt)
+                .map_err(|e| PrinterError::Io(format!("cannot write {}: {}", path.display(), e)))?;
+            salt.to_vec()
+        }
+        Err(e) => {
+            return Err(PrinterError::Io(format!(
+                "cannot read {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+    let key = pbkdf2_hmac_array::<Sha256, 32>(passphrase.as_bytes(), &salt, KDF_ROUNDS);
+    Ok(ChaCha20Poly1305::new(&key.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::queue::JobStatus;
+    use chrono::Local;
+
+    /// Returns an empty spool directory for a test.
+    fn spool_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("taskprinter-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn config(dir: &Path, key: &str) -> SpoolConfig {
+        SpoolConfig {
+            dir: Some(dir.display().to_string()),
+            key: Some(key.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn job(id: u64, message: &str) -> SpooledJob {
+        SpooledJob {
+            info: JobInfo {
+                id,
+                status: JobStatus::Spooled,
+                title: None,
+                from: None,
+                reference: None,
+                submitted_at: Local::now(),
+                expires_at: None,
+                finished_at: None,
+                error: None,
+            },
+            task: PrintTask {
+                message: message.to_string(),
+                ..Default::default()
+            },
+            images: None,
+        }
+    }
+
+    #[test]
+    fn encrypts_and_loads_jobs() {
+        let dir = spool_dir("round-trip");
+        let (spool, jobs) = Spool::open(config(&dir, "secret")).unwrap();
+        assert!(jobs.is_empty());
+        spool.save(&job(7, "Top secret")).unwrap();
+        spool.save(&job(3, "Also secret")).unwrap();
+
+        let content = fs::read(dir.join("0000000007.enc")).unwrap();
+        assert!(!String::from_utf8_lossy(&content).contains("Top secret"));
+
+        let (spool, jobs) = Spool::open(config(&dir, "secret")).unwrap();
+        let messages: Vec<&str> = jobs.iter().map(|job| job.task.message.as_str()).collect();
+        assert_eq!(messages, ["Also secret", "Top secret"]);
+        assert_eq!(spool.highest_id(), 7);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn moves_aside_jobs_of_another_key() {
+        let dir = spool_dir("wrong-key");
+        let (spool, _) = Spool::open(config(&dir, "secret")).unwrap();
+        spool.save(&job(5, "Top secret")).unwrap();
+
+        let (spool, jobs) = Spool::open(config(&dir, "guess")).unwrap();
+        assert!(jobs.is_empty());
+        assert!(!dir.join("0000000005.enc").exists());
+        assert!(dir.join("0000000005.enc.failed").exists());
+        // The ID of the unreadable job is not handed out again.
+        assert_eq!(spool.highest_id(), 5);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
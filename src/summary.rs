@@ -0,0 +1,200 @@
+use crate::api::{AppState, PrintResponse, print_response};
+use crate::config::SummaryConfig;
+use crate::i18n::{self, Label};
+use crate::metrics;
+use crate::printer::PrintTask;
+use crate::queue::{JobOutcome, JobQueue};
+use crate::scheduler;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Local, NaiveTime, Weekday};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Usage since the last summary, see [`record`].
+static USAGE: Mutex<Usage> = Mutex::new(Usage::new());
+
+/// Jobs printed by the queue since the last summary.
+#[derive(Clone)]
+struct Usage {
+    since: Option<DateTime<Local>>,
+    printed: u64,
+    failed: u64,
+    /// Printed jobs by sender, None for jobs without a `from`.
+    senders: BTreeMap<Option<String>, u64>,
+    /// Paper printed by the server before the counting started, see
+    /// [`metrics::paper_total_mm`].
+    paper_start_mm: u64,
+}
+
+impl Usage {
+    const fn new() -> Usage {
+        Usage {
+            since: None,
+            printed: 0,
+            failed: 0,
+            senders: BTreeMap::new(),
+            paper_start_mm: 0,
+        }
+    }
+}
+
+fn usage() -> MutexGuard<'static, Usage> {
+    USAGE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Records a job the queue printed or failed to print, for the weekly summary.
+///
+/// # Arguments
+///
+/// * `task` - The job, whose sender is counted
+/// * `printed` - Whether the job was printed
+pub fn record(task: &PrintTask, printed: bool) {
+    let mut usage = usage();
+    usage.since.get_or_insert_with(Local::now);
+    if !printed {
+        usage.failed += 1;
+        return;
+    }
+    usage.printed += 1;
+    *usage.senders.entry(task.from.clone()).or_default() += 1;
+}
+
+/// Starts printing the weekly usage summary at the configured day and time.
+///
+/// The summary lists the jobs printed and failed since the previous summary, the
+/// senders with the most jobs and an estimate of the paper used. Weeks without any job
+/// are skipped. The counts are kept in memory and start over when the server restarts.
+///
+/// # Arguments
+///
+/// * `config` - The schedule and printer of the summary
+/// * `queue` - The print queue of the API server, whose jobs are counted and which
+///   prints the summary
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()), or an input error for an invalid weekday or time
+pub fn start(config: SummaryConfig, queue: Arc<JobQueue>) -> EscposResult<()> {
+    let weekday: Weekday = config
+        .weekday
+        .parse()
+        .map_err(|_| PrinterError::Input(format!("invalid summary weekday: {}", config.weekday)))?;
+    let time = NaiveTime::parse_from_str(&config.time, "%H:%M")
+        .map_err(|_| PrinterError::Input(format!("invalid summary time: {}", config.time)))?;
+    usage().since.get_or_insert_with(Local::now);
+
+    tokio::spawn(async move {
+        loop {
+            let at = scheduler::next_run(Local::now(), &[weekday], time);
+            info!("Next weekly summary at {}", at.format("%Y-%m-%d %H:%M"));
+            let wait = (at - Local::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            let usage = std::mem::replace(
+                &mut *usage(),
+                Usage {
+                    since: Some(Local::now()),
+                    paper_start_mm: metrics::paper_total_mm(),
+                    ..Usage::new()
+                },
+            );
+            if usage.printed + usage.failed == 0 {
+                info!("No jobs this week, weekly summary skipped");
+                continue;
+            }
+            match queue.submit(summary_task(&usage, &config), None, None) {
+                Ok((id, _)) => info!("Queued weekly summary as job {}", id),
+                Err(e) => warn!("Cannot print the weekly summary: {}", e),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Builds the summary slip of the counted jobs.
+fn summary_task(usage: &Usage, config: &SummaryConfig) -> PrintTask {
+    let since = usage.since.unwrap_or_else(Local::now);
+    let mut lines = vec![
+        format!(
+            "{} - {}",
+            since.format("%d/%m"),
+            Local::now().format("%d/%m")
+        ),
+        String::new(),
+        i18n::format(Label::Printed, &[("count", &usage.printed.to_string())]),
+        i18n::format(Label::Failed, &[("count", &usage.failed.to_string())]),
+        i18n::format(
+            Label::Paper,
+            &[(
+                "length",
+                &paper_length(metrics::paper_total_mm().saturating_sub(usage.paper_start_mm)),
+            )],
+        ),
+    ];
+
+    let mut senders: Vec<(&Option<String>, &u64)> = usage.senders.iter().collect();
+    senders.sort_by(|a, b| b.1.cmp(a.1));
+    if !senders.is_empty() && config.top > 0 {
+        lines.push(String::new());
+        lines.push(i18n::text(Label::TopSenders));
+        for (sender, count) in senders.into_iter().take(config.top) {
+            let sender = sender
+                .clone()
+                .unwrap_or_else(|| i18n::text(Label::Anonymous));
+            lines.push(format!("{:>3} {}", count, sender));
+        }
+    }
+
+    PrintTask {
+        title: Some(i18n::text(Label::WeeklySummary)),
+        message: lines.join("\n"),
+        address: config.address.clone(),
+        port: config.port,
+        ..Default::default()
+    }
+}
+
+/// Formats a paper length in centimeters, or meters from one meter on.
+fn paper_length(mm: u64) -> String {
+    if mm < 1000 {
+        format!("{} cm", mm / 10)
+    } else {
+        format!("{:.1} m", mm as f64 / 1000.0)
+    }
+}
+
+/// Summary print endpoint handler.
+///
+/// This function handles POST requests to `/admin/summary` and prints the usage summary
+/// of the jobs since the last weekly summary right away, without resetting the counts.
+///
+/// # Returns
+///
+/// * `Result<Json<PrintResponse>, Response>` - A success response once the summary is
+///   printed, HTTP 429 if the queue is full and HTTP 500 if printing failed
+pub async fn print_summary_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<PrintResponse>, Response> {
+    let config = state.config.summary.clone().unwrap_or_default();
+    let usage = usage().clone();
+    let (id, outcome) = state
+        .queue
+        .submit(summary_task(&usage, &config), None, None)
+        .map_err(IntoResponse::into_response)?;
+    info!("Queued usage summary as job {}", id);
+    let result = match outcome.await {
+        Ok(JobOutcome::Printed | JobOutcome::Spooled) => Ok(()),
+        Ok(JobOutcome::Failed(e)) => Err(e),
+        Ok(JobOutcome::Expired | JobOutcome::Cancelled) => {
+            return Err(StatusCode::GONE.into_response());
+        }
+        Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR.into_response()),
+    };
+    print_response(result).map_err(IntoResponse::into_response)
+}
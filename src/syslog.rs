@@ -0,0 +1,438 @@
+use crate::cli::Args;
+use crate::config::{Config, SyslogConfig};
+use crate::printer::{PrintTask, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{debug, error, info, warn};
+use regex::Regex;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Names of the severities, indexed by their numerical code.
+pub(crate) const SEVERITIES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+/// Names of the facilities, indexed by their numerical code.
+const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+/// Largest message accepted, longer TCP frames are truncated.
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// Options for the syslog listener mode.
+#[derive(ClapArgs)]
+pub struct SyslogArgs {
+    /// Least severe level that is printed, overriding the configuration
+    #[arg(long)]
+    pub severity: Option<String>,
+}
+
+/// A received syslog message.
+struct SyslogMessage {
+    facility: u8,
+    severity: u8,
+    host: Option<String>,
+    text: String,
+}
+
+/// Limits the number of slips printed per minute to protect the paper roll.
+pub(crate) struct RateLimit {
+    max_per_minute: u32,
+    printed: VecDeque<Instant>,
+    dropped: u64,
+}
+
+impl RateLimit {
+    /// Creates a limit of `max_per_minute` slips, 0 disables the limit.
+    pub(crate) fn new(max_per_minute: u32) -> Self {
+        RateLimit {
+            max_per_minute,
+            printed: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Returns whether another slip may be printed now, counting it as dropped if not.
+    pub(crate) fn allow(&mut self) -> bool {
+        if self.max_per_minute == 0 {
+            return true;
+        }
+        self.printed
+            .retain(|at| at.elapsed() < Duration::from_secs(60));
+        if self.printed.len() >= self.max_per_minute as usize {
+            self.dropped += 1;
+            return false;
+        }
+        self.printed.push_back(Instant::now());
+        true
+    }
+
+    /// Prefixes a message with the number of messages dropped since the last slip.
+    pub(crate) fn annotate(&mut self, message: String) -> String {
+        match std::mem::take(&mut self.dropped) {
+            0 => message,
+            dropped => format!("({} messages dropped)\n{}", dropped, message),
+        }
+    }
+}
+
+/// Filters deciding which messages are printed.
+struct Filter {
+    severity: u8,
+    facilities: Vec<u8>,
+    patterns: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+impl Filter {
+    fn new(config: &SyslogConfig, severity: Option<&str>) -> EscposResult<Self> {
+        let severity = severity.unwrap_or(&config.severity);
+        let severity = code(&SEVERITIES, severity)
+            .ok_or_else(|| PrinterError::Input(format!("unknown syslog severity {}", severity)))?;
+        let facilities = config
+            .facilities
+            .iter()
+            .map(|facility| {
+                code(&FACILITIES, facility).ok_or_else(|| {
+                    PrinterError::Input(format!("unknown syslog facility {}", facility))
+                })
+            })
+            .collect::<EscposResult<_>>()?;
+        Ok(Filter {
+            severity,
+            facilities,
+            patterns: compile(&config.patterns)?,
+            exclude: compile(&config.exclude)?,
+        })
+    }
+
+    fn matches(&self, message: &SyslogMessage) -> bool {
+        message.severity <= self.severity
+            && (self.facilities.is_empty() || self.facilities.contains(&message.facility))
+            && (self.patterns.is_empty()
+                || self
+                    .patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(&message.text)))
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| pattern.is_match(&message.text))
+    }
+}
+
+/// Runs a syslog listener that prints the messages passing the configured filters.
+///
+/// Messages are received via UDP and TCP on the configured port, in the BSD (RFC 3164)
+/// or the IETF (RFC 5424) format. TCP streams may separate messages by newlines or use
+/// octet counting. Every message at or above the configured severity, of one of the
+/// configured facilities and matching the patterns is printed as a slip with severity
+/// and host as title. To keep a flood of errors from emptying the paper roll, at most
+/// `max_per_minute` slips are printed, further messages are dropped and logged. The
+/// function runs until the process is terminated.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `syslog` - The syslog mode options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Only returns on a configuration error or if no listener can
+///   be started, print errors are logged
+pub fn run_syslog(args: &Args, syslog: &SyslogArgs) -> EscposResult<()> {
+    let config = Config::load(args.config.as_deref())
+        .map_err(|e| PrinterError::Input(e.to_string()))?
+        .syslog
+        .ok_or_else(|| {
+            PrinterError::Input("syslog mode requires a [syslog] config section".to_string())
+        })?;
+    let filter = Filter::new(&config, syslog.severity.as_deref())?;
+    if !config.udp && !config.tcp {
+        return Err(PrinterError::Input(
+            "syslog mode requires udp or tcp to be enabled".to_string(),
+        ));
+    }
+
+    let address = (config.bind.as_str(), config.port);
+    let bind_error = |e: std::io::Error| {
+        PrinterError::Io(format!("cannot listen on port {}: {}", config.port, e))
+    };
+    let (sender, receiver) = mpsc::channel();
+    if config.udp {
+        let socket = UdpSocket::bind(address).map_err(bind_error)?;
+        let sender = sender.clone();
+        thread::spawn(move || receive_udp(socket, sender));
+    }
+    if config.tcp {
+        let listener = TcpListener::bind(address).map_err(bind_error)?;
+        let sender = sender.clone();
+        thread::spawn(move || accept_tcp(listener, sender));
+    }
+    drop(sender);
+    info!(
+        "Listening for syslog messages on {}:{}",
+        config.bind, config.port
+    );
+
+    let mut limit = RateLimit::new(config.max_per_minute);
+    for message in receiver {
+        if !filter.matches(&message) {
+            continue;
+        }
+        if !limit.allow() {
+            warn!(
+                "Dropped syslog message, more than {} per minute",
+                config.max_per_minute
+            );
+            continue;
+        }
+
+        let severity = SEVERITIES[message.severity as usize].to_uppercase();
+        let result = print_task(PrintTask {
+            title: Some(match &message.host {
+                Some(host) => format!("{} {}", severity, host),
+                None => severity,
+            }),
+            message: limit.annotate(message.text),
+            address: args.address.clone(),
+            port: args.port,
+            codepage: args.codepage.clone(),
+            ..Default::default()
+        });
+        if let Err(e) = result {
+            error!("Failed to print syslog message: {:?}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Receives syslog datagrams, one message per datagram.
+fn receive_udp(socket: UdpSocket, sender: Sender<SyslogMessage>) {
+    let mut buffer = vec![0; MAX_MESSAGE_LEN];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((len, _)) => {
+                if let Some(message) = parse(&String::from_utf8_lossy(&buffer[..len]))
+                    && sender.send(message).is_err()
+                {
+                    return;
+                }
+            }
+            Err(e) => warn!("Cannot receive syslog datagram: {}", e),
+        }
+    }
+}
+
+/// Accepts syslog TCP connections, reading each on its own thread.
+fn accept_tcp(listener: TcpListener, sender: Sender<SyslogMessage>) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let sender = sender.clone();
+                thread::spawn(move || receive_tcp(stream, sender));
+            }
+            Err(e) => warn!("Cannot accept syslog connection: {}", e),
+        }
+    }
+}
+
+/// Reads the messages of a TCP connection until it is closed.
+fn receive_tcp(stream: TcpStream, sender: Sender<SyslogMessage>) {
+    let peer = stream
+        .peer_addr()
+        .map(|peer| peer.to_string())
+        .unwrap_or_default();
+    let mut reader = BufReader::new(stream);
+    loop {
+        match read_frame(&mut reader) {
+            Ok(Some(frame)) => {
+                if let Some(message) = parse(&String::from_utf8_lossy(&frame))
+                    && sender.send(message).is_err()
+                {
+                    return;
+                }
+            }
+            Ok(None) => return,
+            Err(e) => {
+                debug!("Syslog connection from {} failed: {}", peer, e);
+                return;
+            }
+        }
+    }
+}
+
+/// Reads the next message of a TCP stream, or None at the end of the stream.
+///
+/// Frames starting with a digit use octet counting (`<length> <message>`, RFC 6587),
+/// all others end with a newline.
+fn read_frame(reader: &mut impl BufRead) -> std::io::Result<Option<Vec<u8>>> {
+    let first = match reader.fill_buf()?.first() {
+        Some(byte) => *byte,
+        None => return Ok(None),
+    };
+    let mut frame = Vec::new();
+    if first.is_ascii_digit() {
+        reader.read_until(b' ', &mut frame)?;
+        let len: usize = String::from_utf8_lossy(&frame)
+            .trim()
+            .parse()
+            .map_err(|_| std::io::Error::other("invalid octet count"))?;
+        frame.clear();
+        reader
+            .take(len.min(MAX_MESSAGE_LEN) as u64)
+            .read_to_end(&mut frame)?;
+        // Skip the rest of a frame that is too long.
+        std::io::copy(
+            &mut reader.take(len.saturating_sub(MAX_MESSAGE_LEN) as u64),
+            &mut std::io::sink(),
+        )?;
+    } else {
+        reader
+            .take(MAX_MESSAGE_LEN as u64)
+            .read_until(b'\n', &mut frame)?;
+    }
+    Ok(Some(frame))
+}
+
+/// Parses a message in the BSD (RFC 3164) or IETF (RFC 5424) format.
+///
+/// Messages without priority are treated as `user.notice`. Returns None for empty
+/// messages.
+fn parse(raw: &str) -> Option<SyslogMessage> {
+    let raw = raw.trim_end_matches(['\r', '\n', '\0']);
+    let (priority, rest) = match raw
+        .strip_prefix('<')
+        .and_then(|rest| rest.split_once('>'))
+        .and_then(|(priority, rest)| Some((priority.parse::<u8>().ok()?, rest)))
+    {
+        Some((priority, rest)) if priority < 192 => (priority, rest),
+        _ => (13, raw),
+    };
+
+    let (host, text) = match rest.strip_prefix("1 ") {
+        Some(rest) => parse_rfc5424(rest),
+        None => parse_rfc3164(rest),
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(SyslogMessage {
+        facility: priority / 8,
+        severity: priority % 8,
+        host,
+        text: text.to_string(),
+    })
+}
+
+/// Splits the header fields after the priority of an RFC 5424 message off its text.
+fn parse_rfc5424(rest: &str) -> (Option<String>, String) {
+    let mut fields = rest.splitn(6, ' ');
+    let (_timestamp, host, app) = (fields.next(), fields.next(), fields.next());
+    let (_process, _message_id) = (fields.next(), fields.next());
+    let rest = fields.next().unwrap_or_default();
+
+    // Skip the structured data, either `-` or a sequence of `[...]` elements.
+    let message = match rest.strip_prefix('-') {
+        Some(message) => message,
+        None => {
+            let mut end = 0;
+            let mut inside = false;
+            let mut escaped = false;
+            for (index, c) in rest.char_indices() {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' if inside => escaped = true,
+                    '[' if !inside => inside = true,
+                    ']' if inside => inside = false,
+                    _ if !inside => break,
+                    _ => {}
+                }
+                end = index + c.len_utf8();
+            }
+            &rest[end..]
+        }
+    };
+    let message = message.trim_start().trim_start_matches('\u{feff}');
+
+    let host = host.filter(|host| *host != "-").map(str::to_string);
+    let text = match app.filter(|app| *app != "-") {
+        Some(app) => format!("{}: {}", app, message),
+        None => message.to_string(),
+    };
+    (host, text)
+}
+
+/// Splits the timestamp and host of an RFC 3164 message off its text, which keeps the
+/// tag, e.g. `sshd[123]: ...`.
+fn parse_rfc3164(rest: &str) -> (Option<String>, String) {
+    // Mmm dd hh:mm:ss
+    let bytes = rest.as_bytes();
+    let has_timestamp = bytes.len() > 16
+        && bytes[3] == b' '
+        && bytes[9] == b':'
+        && bytes[12] == b':'
+        && bytes[15] == b' ';
+    if !has_timestamp {
+        return (None, rest.to_string());
+    }
+    match rest[16..].split_once(' ') {
+        Some((host, text)) => (Some(host.to_string()), text.to_string()),
+        None => (None, rest[16..].to_string()),
+    }
+}
+
+/// Looks up the numerical code of a severity or facility name.
+fn code(names: &[&str], name: &str) -> Option<u8> {
+    let name = match name.to_lowercase().as_str() {
+        "panic" => "emerg".to_string(),
+        "error" => "err".to_string(),
+        "warn" => "warning".to_string(),
+        name => name.to_string(),
+    };
+    names
+        .iter()
+        .position(|candidate| *candidate == name)
+        .map(|code| code as u8)
+}
+
+/// Compiles the regular expressions of a filter.
+fn compile(patterns: &[String]) -> EscposResult<Vec<Regex>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Regex::new(pattern)
+                .map_err(|e| PrinterError::Input(format!("invalid pattern {}: {}", pattern, e)))
+        })
+        .collect()
+}
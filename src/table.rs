@@ -0,0 +1,117 @@
+/// A single table row, either a set of cells or a horizontal rule.
+#[derive(Debug, Clone)]
+enum Row {
+    Cells(Vec<String>),
+    Rule,
+}
+
+/// Renders fixed-width text tables for the narrow character grid of receipt printers.
+///
+/// Tables are built row by row and rendered to plain text, either with box drawing
+/// borders or, for very narrow layouts, with columns separated by a single space.
+/// Cell content longer than its column is truncated, shorter content is padded.
+///
+/// # Examples
+///
+/// ```
+/// use taskprinter::table::Table;
+///
+/// let text = Table::new(&[10, 6])
+///     .row(&["Milk", "1.29"])
+///     .rule()
+///     .row(&["Total", "1.29"])
+///     .render();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Table {
+    widths: Vec<usize>,
+    rows: Vec<Row>,
+    borders: bool,
+}
+
+impl Table {
+    /// Creates an empty bordered table with the given column widths in characters.
+    pub fn new(widths: &[usize]) -> Self {
+        Table {
+            widths: widths.to_vec(),
+            rows: Vec::new(),
+            borders: true,
+        }
+    }
+
+    /// Enables or disables box drawing borders. Without borders, columns are separated
+    /// by a single space and rules are drawn with dashes.
+    pub fn borders(mut self, borders: bool) -> Self {
+        self.borders = borders;
+        self
+    }
+
+    /// Appends a row of cells. Missing cells are rendered empty, extra cells are ignored.
+    pub fn row<S: AsRef<str>>(mut self, cells: &[S]) -> Self {
+        self.rows.push(Row::Cells(
+            cells.iter().map(|cell| cell.as_ref().to_string()).collect(),
+        ));
+        self
+    }
+
+    /// Appends a horizontal rule.
+    pub fn rule(mut self) -> Self {
+        self.rows.push(Row::Rule);
+        self
+    }
+
+    /// Total width of the rendered table in characters.
+    pub fn width(&self) -> usize {
+        let separators = if self.borders {
+            self.widths.len() + 1
+        } else {
+            self.widths.len().saturating_sub(1)
+        };
+        self.widths.iter().sum::<usize>() + separators
+    }
+
+    /// Renders the table to text, one line per row without a trailing newline.
+    pub fn render(&self) -> String {
+        let mut lines = Vec::new();
+        if self.borders {
+            lines.push(self.border('┌', '┬', '┐'));
+        }
+        for row in &self.rows {
+            match row {
+                Row::Cells(cells) => lines.push(self.cells(cells)),
+                Row::Rule if self.borders => lines.push(self.border('├', '┼', '┤')),
+                Row::Rule => lines.push("-".repeat(self.width())),
+            }
+        }
+        if self.borders {
+            lines.push(self.border('└', '┴', '┘'));
+        }
+        lines.join("\n")
+    }
+
+    fn border(&self, left: char, middle: char, right: char) -> String {
+        let segments: Vec<String> = self.widths.iter().map(|w| "─".repeat(*w)).collect();
+        format!("{}{}{}", left, segments.join(&middle.to_string()), right)
+    }
+
+    fn cells(&self, cells: &[String]) -> String {
+        let padded: Vec<String> = self
+            .widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| pad(cells.get(i).map(String::as_str).unwrap_or(""), *width))
+            .collect();
+
+        if self.borders {
+            format!("│{}│", padded.join("│"))
+        } else {
+            padded.join(" ")
+        }
+    }
+}
+
+/// Truncates or pads text to exactly `width` characters.
+fn pad(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    format!("{:<width$}", text, width = width)
+}
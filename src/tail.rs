@@ -0,0 +1,278 @@
+use crate::ansi;
+use crate::cli::Args;
+use crate::client;
+use crate::config::Config;
+use crate::i18n::{self, Label};
+use crate::printer::PrintTask;
+use crate::queue::JobQueue;
+use crate::quiet_hours::QuietHours;
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use regex::Regex;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Delay between two checks of the followed file for new lines.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Options for the file tail mode.
+#[derive(ClapArgs)]
+pub struct TailArgs {
+    /// File to print the lines of
+    pub file: PathBuf,
+
+    /// Keep following the file and print new lines as they are appended
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Only print lines matching this regular expression
+    #[arg(long)]
+    pub grep: Option<String>,
+
+    /// Seconds to collect lines before printing them as one slip
+    #[arg(long, default_value = "60")]
+    pub interval: u64,
+
+    /// Maximum number of lines per slip, further lines of a batch are only counted
+    #[arg(long, default_value = "20")]
+    pub max_lines: usize,
+
+    /// Time range in which lines are collected but not printed, e.g. "22:00-07:00"
+    #[arg(long)]
+    pub quiet_hours: Option<String>,
+}
+
+/// Prints the lines of a file, optionally following it like `tail -f`.
+///
+/// Without `--follow`, the matching lines currently in the file are printed and the
+/// function returns. With `--follow`, only lines appended after the start are printed.
+/// The file is polled, so it may be rotated or truncated: when it becomes shorter than
+/// the position read so far, reading starts over from its beginning.
+///
+/// Matching lines are collected for `interval` seconds after the first one and then
+/// printed as a single slip with the file name (or `--title`) as title, so a burst of
+/// errors does not print a slip per line. During the quiet hours, lines are collected
+/// and printed as one batch afterwards. With `--server`, the slips are submitted to the
+/// queue of the API server, otherwise to a local print queue using the spooling,
+/// verification and queue settings of the configuration. Without spooling, the lines are
+/// kept for the next batch while the printer is unreachable.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `tail` - The tail mode options
+/// * `config` - The configuration providing the settings of the local print queue
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - An error if an option is invalid, the file cannot be read or,
+///   without `--follow`, printing fails
+pub fn run_tail(args: &Args, tail: &TailArgs, config: &Config) -> EscposResult<()> {
+    let pattern = tail
+        .grep
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| PrinterError::Input(format!("invalid pattern: {}", e)))?;
+    let quiet_hours = tail
+        .quiet_hours
+        .as_deref()
+        .map(QuietHours::parse)
+        .transpose()?;
+    let printer = Printer::new(args, config);
+    let matches = |line: &str| {
+        pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&ansi::strip(line)))
+    };
+
+    if !tail.follow {
+        let content = fs::read(&tail.file)
+            .map_err(|e| PrinterError::Io(format!("cannot read {}: {}", tail.file.display(), e)))?;
+        let lines: Vec<String> = String::from_utf8_lossy(&content)
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .filter(|line| !line.is_empty() && matches(line))
+            .collect();
+        if lines.is_empty() {
+            return Ok(());
+        }
+        return print_batch(args, tail, &printer, &lines);
+    }
+
+    let mut reader = LineReader::new(&tail.file);
+    reader.skip_to_end()?;
+    info!("Following {}", tail.file.display());
+    let interval = Duration::from_secs(tail.interval);
+    let mut pending: Vec<String> = Vec::new();
+    let mut batch_start = Instant::now();
+    let mut failing = false;
+    loop {
+        match reader.read_lines() {
+            Ok(lines) => {
+                failing = false;
+                for line in lines.into_iter().filter(|line| matches(line)) {
+                    if pending.is_empty() {
+                        batch_start = Instant::now();
+                    }
+                    pending.push(line);
+                }
+            }
+            // Warn once, the file may be missing for a while during log rotation.
+            Err(e) if !failing => {
+                warn!("{}", e);
+                failing = true;
+            }
+            Err(_) => {}
+        }
+
+        let quiet = quiet_hours.as_ref().is_some_and(QuietHours::is_active);
+        if !pending.is_empty() && batch_start.elapsed() >= interval && !quiet {
+            match print_batch(args, tail, &printer, &pending) {
+                Ok(()) => pending.clear(),
+                Err(PrinterError::Io(e)) => {
+                    warn!("Cannot print {} lines, retrying: {}", pending.len(), e);
+                    batch_start = Instant::now();
+                }
+                Err(e) => {
+                    error!("Failed to print {} lines: {:?}", pending.len(), e);
+                    pending.clear();
+                }
+            }
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Destination of the slips: the queue of an API server, or a local print queue.
+enum Printer {
+    Server(String),
+    Queue(Arc<JobQueue>),
+}
+
+impl Printer {
+    fn new(args: &Args, config: &Config) -> Self {
+        match &args.server {
+            Some(server) => Printer::Server(client::base_url(Some(server))),
+            None => Printer::Queue(JobQueue::start(
+                config.spool.clone(),
+                config.verify.clone(),
+                config.queue.clone().unwrap_or_default(),
+                config.history.clone().unwrap_or_default(),
+            )),
+        }
+    }
+
+    fn print(&self, task: PrintTask) -> EscposResult<()> {
+        match self {
+            Printer::Server(server) => client::submit(server, &task),
+            Printer::Queue(queue) => queue.print_blocking(task),
+        }
+    }
+}
+
+/// Prints a batch of lines as a single slip, counting the lines beyond `max_lines`.
+fn print_batch(
+    args: &Args,
+    tail: &TailArgs,
+    printer: &Printer,
+    lines: &[String],
+) -> EscposResult<()> {
+    let shown = &lines[..lines.len().min(tail.max_lines.max(1))];
+    let mut message = shown.join("\n");
+    if lines.len() > shown.len() {
+        let count = (lines.len() - shown.len()).to_string();
+        message.push('\n');
+        message.push_str(&i18n::format(Label::MoreLines, &[("count", &count)]));
+    }
+    if args.strip_ansi {
+        message = ansi::strip(&message);
+    }
+
+    let task = PrintTask {
+        title: args.title.clone().or_else(|| {
+            tail.file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        }),
+        message,
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        mode: args.mode.clone(),
+        hyphenate: args.hyphenate.clone(),
+        normalize: Some(!args.no_normalize),
+        squeeze_blank: Some(args.squeeze_blank),
+        trim_trailing: Some(args.trim_trailing),
+        dedent: Some(args.dedent),
+        from: args.from.clone(),
+        ..Default::default()
+    };
+    printer.print(task)
+}
+
+/// Reads the complete lines appended to a file since the last call.
+struct LineReader<'a> {
+    path: &'a Path,
+    position: u64,
+}
+
+impl<'a> LineReader<'a> {
+    fn new(path: &'a Path) -> Self {
+        LineReader { path, position: 0 }
+    }
+
+    fn open(&self) -> EscposResult<File> {
+        File::open(self.path)
+            .map_err(|e| PrinterError::Io(format!("cannot read {}: {}", self.path.display(), e)))
+    }
+
+    fn skip_to_end(&mut self) -> EscposResult<()> {
+        let file = self.open()?;
+        self.position = file
+            .metadata()
+            .map_err(|e| PrinterError::Io(format!("cannot read {}: {}", self.path.display(), e)))?
+            .len();
+        Ok(())
+    }
+
+    /// Returns the new complete lines. A trailing line without newline is left for the
+    /// next call, as it may still be written.
+    fn read_lines(&mut self) -> EscposResult<Vec<String>> {
+        let read_error = |e: std::io::Error| {
+            PrinterError::Io(format!("cannot read {}: {}", self.path.display(), e))
+        };
+        let mut file = self.open()?;
+        let len = file.metadata().map_err(read_error)?.len();
+        if len < self.position {
+            info!("{} was truncated or rotated", self.path.display());
+            self.position = 0;
+        }
+        if len == self.position {
+            return Ok(Vec::new());
+        }
+
+        file.seek(SeekFrom::Start(self.position))
+            .map_err(read_error)?;
+        let mut content = Vec::new();
+        file.take(len - self.position)
+            .read_to_end(&mut content)
+            .map_err(read_error)?;
+        let Some(end) = content.iter().rposition(|byte| *byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        self.position += end as u64 + 1;
+
+        Ok(String::from_utf8_lossy(&content[..end])
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
@@ -0,0 +1,39 @@
+use crate::printer::PrintTask;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Variables of the built-in `badge` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Badge {
+    name: String,
+    /// Company, project or role printed below the name.
+    affiliation: Option<String>,
+    /// URL of a profile page, printed as QR code.
+    profile_url: Option<String>,
+}
+
+/// Renders a name badge for meetups and workshops, see [`crate::printer::print_badge`].
+///
+/// # Arguments
+///
+/// * `variables` - `name`, an optional `affiliation` and an optional `profile_url`
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let badge: Badge = super::parse(variables)?;
+    if badge.name.trim().is_empty() {
+        return Err(PrinterError::Input("the badge has no name".to_string()));
+    }
+
+    Ok(PrintTask {
+        title: Some(badge.name.trim().to_string()),
+        message: badge.affiliation.unwrap_or_default(),
+        link: badge.profile_url.filter(|url| !url.is_empty()),
+        mode: Some("badge".to_string()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,136 @@
+use crate::counters;
+use crate::i18n::{self, Label};
+use crate::printer::PrintTask;
+use chrono::{Days, Local};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::info;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Alphabet of generated codes, without the easily confused `0`, `1`, `I` and `O`.
+const CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKLMNPQRSTUVWXYZ";
+
+/// Variables of the built-in `coupon` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Coupon {
+    headline: String,
+    /// Terms and conditions printed below the headline.
+    terms: Option<String>,
+    /// Expiry date as text, e.g. `31/12/2026`.
+    expires: Option<String>,
+    /// Number of days the coupon is valid, used instead of `expires`.
+    valid_days: Option<u64>,
+    /// The coupon code. Generated when omitted.
+    code: Option<String>,
+    /// Prefix of generated codes, e.g. `SPRING`.
+    prefix: Option<String>,
+    /// `code128` (default) or `qr`.
+    symbology: Option<String>,
+}
+
+/// Renders a coupon with its code as barcode or QR code.
+///
+/// Without a `code`, a unique code is generated for every print from a persistent
+/// counter and a random suffix, e.g. `SPRING-0042-K7QX`, and written to the log.
+///
+/// # Arguments
+///
+/// * `variables` - `headline`, optional `terms`, `expires` or `valid_days`, `code`,
+///   `prefix` and `symbology`
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let coupon: Coupon = super::parse(variables)?;
+    let qr = match coupon
+        .symbology
+        .as_deref()
+        .map(str::to_lowercase)
+        .as_deref()
+    {
+        None | Some("code128") => false,
+        Some("qr") => true,
+        Some(other) => {
+            return Err(PrinterError::Input(format!(
+                "unsupported coupon symbology: {}",
+                other
+            )));
+        }
+    };
+
+    let code = match coupon.code.filter(|code| !code.trim().is_empty()) {
+        Some(code) => code.trim().to_string(),
+        None => {
+            let code = generate_code(coupon.prefix.as_deref())?;
+            info!("Generated coupon code {}", code);
+            code
+        }
+    };
+    let expires = match (coupon.expires, coupon.valid_days) {
+        (Some(expires), _) if !expires.is_empty() => Some(expires),
+        (_, Some(days)) => Some(
+            (Local::now() + Days::new(days))
+                .format("%d/%m/%Y")
+                .to_string(),
+        ),
+        _ => None,
+    };
+
+    let mut lines = super::wrap(&coupon.headline.to_uppercase(), "");
+    if let Some(terms) = coupon.terms.as_deref().filter(|terms| !terms.is_empty()) {
+        lines.push(super::separator(""));
+        for paragraph in terms.lines() {
+            lines.extend(super::wrap(paragraph, ""));
+        }
+    }
+    if let Some(expires) = &expires {
+        lines.push(super::separator(""));
+        lines.extend(super::wrap(
+            &i18n::format(Label::ValidUntil, &[("date", expires)]),
+            "",
+        ));
+    }
+    if qr {
+        lines.extend(super::wrap(
+            &i18n::format(Label::Code, &[("code", &code)]),
+            "",
+        ));
+    }
+
+    Ok(PrintTask {
+        title: Some(i18n::text(Label::Coupon)),
+        message: lines.join("\n"),
+        link: qr.then(|| code.clone()),
+        footer_barcode: (!qr).then_some(code),
+        ..Default::default()
+    })
+}
+
+/// Generates a unique coupon code from a persistent counter and a random suffix.
+fn generate_code(prefix: Option<&str>) -> EscposResult<String> {
+    let number = counters::increment("coupon")?;
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let digest = Sha256::new()
+        .chain_update(number.to_le_bytes())
+        .chain_update(nanos.to_le_bytes())
+        .chain_update(std::process::id().to_le_bytes())
+        .finalize();
+    let suffix: String = digest[..4]
+        .iter()
+        .map(|byte| CODE_ALPHABET[(*byte as usize) % CODE_ALPHABET.len()] as char)
+        .collect();
+
+    Ok(
+        match prefix.map(str::trim).filter(|prefix| !prefix.is_empty()) {
+            Some(prefix) => format!("{}-{:04}-{}", prefix.to_uppercase(), number, suffix),
+            None => format!("{:04}-{}", number, suffix),
+        },
+    )
+}
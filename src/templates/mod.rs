@@ -0,0 +1,210 @@
+pub mod badge;
+pub mod coupon;
+pub mod packing;
+pub mod receipt;
+pub mod recipe;
+pub mod shopping;
+
+use crate::api::{AppState, PrintResponse, queue_task};
+use crate::config::TemplateConfig;
+use crate::printer::{FRAME_BODY_WIDTH, PrintTask, word_wrap};
+use crate::webhooks::WebhookTarget;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Renders the variables of a request into a task.
+type Builtin = fn(&Map<String, Value>) -> EscposResult<PrintTask>;
+
+/// Returns the built-in template with the given name.
+///
+/// # Arguments
+///
+/// * `name` - The template name used in the `/print/{template}` URL
+///
+/// # Returns
+///
+/// * `Option<Builtin>` - The render function of the template, or None if there is none
+pub fn builtin(name: &str) -> Option<Builtin> {
+    match name {
+        "shopping-list" => Some(shopping::render),
+        "recipe" => Some(recipe::render),
+        "packing-slip" => Some(packing::render),
+        "badge" => Some(badge::render),
+        "coupon" => Some(coupon::render),
+        "receipt" => Some(receipt::render),
+        _ => None,
+    }
+}
+
+/// Template print endpoint handler.
+///
+/// This function handles POST requests to `/print/{template}` for every template defined
+/// in a `[templates.<name>]` config section and for the built-in templates, see
+/// [`builtin`]. Templates from the config take precedence over built-in ones with the
+/// same name. The body is a JSON object holding exactly the variables declared by the
+/// template, e.g. `{"table": "4", "items": ["Pizza"]}`. The printer is taken from the
+/// query parameters, or from the template if omitted.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - A success response when the
+///   task was printed. HTTP 404 if no template with that name exists, HTTP 400 for
+///   missing or undeclared variables, otherwise the response of the print queue, see
+///   [`queue_task`].
+pub async fn template_print_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Query(target): Query<WebhookTarget>,
+    Json(variables): Json<Map<String, Value>>,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let task = match (state.config.templates.get(&name), builtin(&name)) {
+        (Some(template), _) => render(template, &variables),
+        (None, Some(render)) => render(&variables),
+        (None, None) => return Err(StatusCode::NOT_FOUND.into_response()),
+    };
+
+    info!("Received print request for template {}", name);
+    let task = task.map_err(|e| {
+        warn!("Cannot render template {}: {:?}", name, e);
+        StatusCode::BAD_REQUEST.into_response()
+    })?;
+    let task = PrintTask {
+        address: target.address.or(task.address),
+        port: target.port.or(task.port),
+        codepage: target.codepage.or(task.codepage),
+        ..task
+    };
+    queue_task(&state.queue, task, None, None).await
+}
+
+/// Renders a stored template with the variables of a request.
+///
+/// # Arguments
+///
+/// * `template` - The template definition
+/// * `variables` - The variables sent by the client
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error if a declared
+///   variable is missing, an undeclared variable is given or a value is an object
+pub fn render(
+    template: &TemplateConfig,
+    variables: &Map<String, Value>,
+) -> EscposResult<PrintTask> {
+    let mut values: HashMap<&str, String> = template
+        .defaults
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .collect();
+
+    for (name, value) in variables {
+        if !template.variables.contains(name) && !template.defaults.contains_key(name) {
+            return Err(PrinterError::Input(format!("unknown variable: {}", name)));
+        }
+        values.insert(name, text(name, value)?);
+    }
+    if let Some(missing) = template
+        .variables
+        .iter()
+        .find(|name| !values.contains_key(name.as_str()))
+    {
+        return Err(PrinterError::Input(format!(
+            "missing variable: {}",
+            missing
+        )));
+    }
+
+    let fill = |text: &Option<String>| text.as_deref().map(|text| substitute(text, &values));
+    Ok(PrintTask {
+        title: fill(&template.title),
+        message: substitute(&template.message, &values),
+        date: fill(&template.date),
+        link: fill(&template.link).filter(|link| !link.is_empty()),
+        mode: template.mode.clone(),
+        address: template.address.clone(),
+        port: template.port,
+        codepage: template.codepage.clone(),
+        header: template.header,
+        ..Default::default()
+    })
+}
+
+/// Deserializes the variables of a built-in template, rejecting unknown variables.
+fn parse<T: DeserializeOwned>(variables: &Map<String, Value>) -> EscposResult<T> {
+    serde_json::from_value(Value::Object(variables.clone()))
+        .map_err(|e| PrinterError::Input(format!("invalid template variables: {}", e)))
+}
+
+/// Formats a section separator spanning the frame, such as `── DAIRY ──────────`, or a
+/// plain rule for an empty label.
+fn separator(label: &str) -> String {
+    if label.is_empty() {
+        return "─".repeat(FRAME_BODY_WIDTH);
+    }
+    let label = format!("── {} ", label.to_uppercase());
+    let width = label.chars().count();
+    format!(
+        "{}{}",
+        label,
+        "─".repeat(FRAME_BODY_WIDTH.saturating_sub(width))
+    )
+}
+
+/// Word wraps a paragraph to the frame width with a hanging indent, see [`word_wrap`].
+fn wrap(text: &str, prefix: &str) -> Vec<String> {
+    word_wrap(text, FRAME_BODY_WIDTH, prefix)
+}
+
+/// Replaces every `{name}` placeholder of a variable with its value. Other braces,
+/// including print time placeholders such as `{date}`, are kept.
+fn substitute(text: &str, values: &HashMap<&str, String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let candidate = &rest[start + 1..];
+        match candidate
+            .find('}')
+            .and_then(|end| values.get(&candidate[..end]).map(|value| (value, end)))
+        {
+            Some((value, end)) => {
+                output.push_str(value);
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = candidate;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Converts a variable value to text. Arrays are printed one element per line.
+fn text(name: &str, value: &Value) -> EscposResult<String> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Null => Ok(String::new()),
+        Value::Array(items) => Ok(items
+            .iter()
+            .map(|item| text(name, item))
+            .collect::<EscposResult<Vec<_>>>()?
+            .join("\n")),
+        Value::Object(_) => Err(PrinterError::Input(format!(
+            "variable {} must be text, a number or a list",
+            name
+        ))),
+        other => Ok(other.to_string()),
+    }
+}
@@ -0,0 +1,93 @@
+use crate::i18n::{self, Label};
+use crate::printer::PrintTask;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Variables of the built-in `packing-slip` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackingSlip {
+    /// Order number, also printed as Code128 barcode.
+    order: Value,
+    /// Shipping address, one line per element.
+    ship_to: Vec<String>,
+    items: Vec<PackingItem>,
+    /// Optional note for the customer, e.g. a thank you.
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackingItem {
+    name: String,
+    #[serde(default = "one")]
+    qty: u32,
+    /// Optional article number printed below the name.
+    sku: Option<String>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+/// Renders a packing slip with the shipping address, the item table and the order
+/// number as Code128 barcode.
+///
+/// # Arguments
+///
+/// * `variables` - `order`, `ship_to` (address lines), `items` (objects with `name`,
+///   `qty` and `sku`) and an optional `note`
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let slip: PackingSlip = super::parse(variables)?;
+    let order = match &slip.order {
+        Value::String(order) => order.trim().to_string(),
+        Value::Number(order) => order.to_string(),
+        _ => String::new(),
+    };
+    if order.is_empty() {
+        return Err(PrinterError::Input(
+            "the order number must be text or a number".to_string(),
+        ));
+    }
+    if slip.items.is_empty() {
+        return Err(PrinterError::Input("the order has no items".to_string()));
+    }
+
+    let mut lines = super::wrap(&i18n::format(Label::Order, &[("number", &order)]), "");
+    lines.push(super::separator(&i18n::text(Label::ShipTo)));
+    for line in &slip.ship_to {
+        lines.extend(super::wrap(line, ""));
+    }
+
+    lines.push(super::separator(&i18n::text(Label::Items)));
+    lines.push(i18n::text(Label::QtyItem));
+    for item in &slip.items {
+        lines.extend(super::wrap(&item.name, &format!("{:>3} ", item.qty)));
+        if let Some(sku) = item.sku.as_deref().filter(|sku| !sku.is_empty()) {
+            lines.extend(super::wrap(sku, "    "));
+        }
+    }
+    let total: u32 = slip.items.iter().map(|item| item.qty).sum();
+    lines.push(super::separator(""));
+    lines.push(i18n::format(
+        Label::ItemCount,
+        &[("count", &format!("{:>3}", total))],
+    ));
+
+    if let Some(note) = slip.note.as_deref().filter(|note| !note.is_empty()) {
+        lines.push(String::new());
+        lines.extend(super::wrap(note, ""));
+    }
+
+    Ok(PrintTask {
+        title: Some(i18n::text(Label::PackingSlip)),
+        message: lines.join("\n"),
+        footer_barcode: Some(order),
+        ..Default::default()
+    })
+}
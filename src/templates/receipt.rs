@@ -0,0 +1,285 @@
+use crate::i18n::{self, Label};
+use crate::printer::{FRAME_BODY_WIDTH, PrintTask};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Variables of the built-in `receipt` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Receipt {
+    /// Title of the slip, defaults to "RECEIPT".
+    title: Option<String>,
+    /// Merchant or purpose printed above the items.
+    merchant: Option<String>,
+    items: Vec<ReceiptItem>,
+    /// Tax rate in percent of items without their own rate.
+    tax: Option<Value>,
+    /// Whether the prices include the tax (gross prices) instead of excluding it.
+    #[serde(default)]
+    tax_included: bool,
+    /// Currency code printed in the total line, e.g. `EUR`.
+    currency: Option<String>,
+    note: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReceiptItem {
+    name: String,
+    /// Quantity, may be fractional for goods sold by weight.
+    qty: Option<Value>,
+    /// Unit price, a number or decimal text such as `"3.49"`.
+    price: Value,
+    /// Tax rate in percent, e.g. `19` or `7.5`.
+    tax: Option<Value>,
+}
+
+/// Amounts are computed in cents, quantities in thousandths and tax rates in basis points,
+/// as 128 bit integers so that no product of user supplied values can overflow.
+const CENTS: i128 = 100;
+const MILLI: i128 = 1000;
+const BASIS_POINTS: i128 = 10000;
+
+/// Renders an expense receipt with a right-aligned amounts column.
+///
+/// Line amounts, the subtotal, the tax per rate and the total are computed with integer
+/// arithmetic, so no rounding errors of floating point numbers end up on the receipt.
+/// The tax is rounded once per rate. Every item is marked with a letter identifying its
+/// tax rate, as on receipts from cash registers.
+///
+/// # Arguments
+///
+/// * `variables` - `items` (objects with `name`, `qty`, `price` and `tax`), a default
+///   `tax` rate, `tax_included`, and optional `title`, `merchant`, `currency` and `note`
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let receipt: Receipt = super::parse(variables)?;
+    if receipt.items.is_empty() {
+        return Err(PrinterError::Input("the receipt has no items".to_string()));
+    }
+    let default_rate = match &receipt.tax {
+        Some(rate) => decimal(rate, BASIS_POINTS / 100, "tax")?,
+        None => 0,
+    };
+
+    let mut lines = Vec::new();
+    if let Some(merchant) = receipt.merchant.as_deref().filter(|m| !m.is_empty()) {
+        lines.extend(super::wrap(merchant, ""));
+        lines.push(super::separator(""));
+    }
+
+    let items = receipt
+        .items
+        .iter()
+        .map(|item| {
+            let qty = match &item.qty {
+                Some(qty) => decimal(qty, MILLI, "qty")?,
+                None => MILLI,
+            };
+            let price = decimal(&item.price, CENTS, "price")?;
+            let rate = match &item.tax {
+                Some(rate) => decimal(rate, BASIS_POINTS / 100, "tax")?,
+                None => default_rate,
+            };
+            Ok((item.name.as_str(), qty, price, rate))
+        })
+        .collect::<EscposResult<Vec<_>>>()?;
+    let taxed = items.iter().any(|(_, _, _, rate)| *rate != 0);
+
+    // Rates in order of first appearance, with the sum of the line amounts per rate.
+    let mut rates: Vec<(i128, i128)> = Vec::new();
+    for (name, qty, price, rate) in items {
+        let amount = divide(qty * price, MILLI);
+        let index = match rates.iter().position(|(r, _)| *r == rate) {
+            Some(index) => index,
+            None => {
+                rates.push((rate, 0));
+                rates.len() - 1
+            }
+        };
+        rates[index].1 += amount;
+
+        let mut amount = format_money(amount);
+        if taxed {
+            amount.push(' ');
+            amount.push(tax_letter(index));
+        }
+        let detail = if qty == MILLI {
+            String::new()
+        } else {
+            format!("  {} x {}", format_fixed(qty, MILLI), format_money(price))
+        };
+        lines.extend(super::wrap(name, ""));
+        lines.push(columns(&detail, &amount));
+    }
+
+    let subtotal: i128 = rates.iter().map(|(_, sum)| sum).sum();
+    let taxes: Vec<(usize, i128, i128)> = rates
+        .iter()
+        .enumerate()
+        .filter(|(_, (rate, _))| *rate != 0)
+        .map(|(index, (rate, sum))| {
+            let tax = if receipt.tax_included {
+                sum - divide(sum * BASIS_POINTS, BASIS_POINTS + rate)
+            } else {
+                divide(sum * rate, BASIS_POINTS)
+            };
+            (index, *rate, tax)
+        })
+        .collect();
+    let tax_total: i128 = taxes.iter().map(|(_, _, tax)| tax).sum();
+    let total = if receipt.tax_included {
+        subtotal
+    } else {
+        subtotal + tax_total
+    };
+
+    lines.push(super::separator(""));
+    if !taxes.is_empty() {
+        let label = if receipt.tax_included {
+            i18n::text(Label::Net)
+        } else {
+            i18n::text(Label::Subtotal)
+        };
+        let net = if receipt.tax_included {
+            subtotal - tax_total
+        } else {
+            subtotal
+        };
+        lines.push(columns(&label, &total_amount(net, taxed)));
+        for (index, rate, tax) in &taxes {
+            let label = i18n::format(
+                Label::Tax,
+                &[
+                    ("letter", &tax_letter(*index).to_string()),
+                    ("rate", &format_fixed(*rate, BASIS_POINTS / 100)),
+                ],
+            );
+            lines.push(columns(&label, &total_amount(*tax, taxed)));
+        }
+    }
+    let label = match receipt.currency.as_deref().filter(|c| !c.is_empty()) {
+        Some(currency) => format!("{} {}", i18n::text(Label::Total), currency.to_uppercase()),
+        None => i18n::text(Label::Total),
+    };
+    lines.push(columns(&label, &total_amount(total, taxed)));
+
+    if let Some(note) = receipt.note.as_deref().filter(|n| !n.is_empty()) {
+        lines.push(String::new());
+        lines.extend(super::wrap(note, ""));
+    }
+
+    Ok(PrintTask {
+        title: Some(receipt.title.unwrap_or_else(|| i18n::text(Label::Receipt))),
+        message: lines.join("\n"),
+        ..Default::default()
+    })
+}
+
+/// Parses a number or decimal text into a fixed point integer with the given scale,
+/// e.g. `3.499` with scale 100 into 350. Digits beyond the scale are rounded half up.
+fn decimal(value: &Value, scale: i128, field: &str) -> EscposResult<i128> {
+    let invalid = || PrinterError::Input(format!("{} must be a decimal number", field));
+    let text = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(text) => text.trim().replace(',', "."),
+        _ => return Err(invalid()),
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let mut result: i128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<i128>().map_err(|_| invalid())?
+    };
+    let mut unit = 1;
+    let mut fraction = fraction.chars().filter_map(|c| c.to_digit(10));
+    while unit < scale {
+        unit *= 10;
+        result = result
+            .checked_mul(10)
+            .and_then(|r| r.checked_add(fraction.next().unwrap_or(0) as i128))
+            .ok_or_else(invalid)?;
+    }
+    if fraction.next().is_some_and(|digit| digit >= 5) {
+        result += 1;
+    }
+
+    Ok(if negative { -result } else { result })
+}
+
+/// Divides and rounds half away from zero.
+fn divide(value: i128, divisor: i128) -> i128 {
+    let half = divisor / 2;
+    if value >= 0 {
+        (value + half) / divisor
+    } else {
+        (value - half) / divisor
+    }
+}
+
+/// Formats a fixed point integer without trailing zeros, e.g. 1500 with scale 1000 as `1.5`.
+fn format_fixed(value: i128, scale: i128) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let value = value.abs();
+    let digits = scale.to_string().len() - 1;
+    let fraction = format!("{:0width$}", value % scale, width = digits);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{}{}", sign, value / scale)
+    } else {
+        format!("{}{}.{}", sign, value / scale, fraction)
+    }
+}
+
+/// Formats an amount in cents with two decimals.
+fn format_money(cents: i128) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / CENTS, cents.abs() % CENTS)
+}
+
+/// Formats a sum, leaving room for the tax letters so the decimals line up with the items.
+fn total_amount(cents: i128, taxed: bool) -> String {
+    if taxed {
+        format!("{}  ", format_money(cents))
+    } else {
+        format_money(cents)
+    }
+}
+
+/// Returns the letter marking the items of the n-th tax rate.
+fn tax_letter(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+/// Puts text on the left and an amount on the right of a frame line. If both do not fit,
+/// the amount moves to its own line.
+fn columns(left: &str, right: &str) -> String {
+    let used = left.chars().count() + right.chars().count();
+    if used < FRAME_BODY_WIDTH || left.is_empty() {
+        format!(
+            "{}{}{}",
+            left,
+            " ".repeat(FRAME_BODY_WIDTH.saturating_sub(used)),
+            right
+        )
+    } else {
+        format!("{}\n{:>width$}", left, right, width = FRAME_BODY_WIDTH)
+    }
+}
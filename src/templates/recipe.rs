@@ -0,0 +1,77 @@
+use crate::printer::{FRAME_TITLE_WIDTH, PrintTask};
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Variables of the built-in `recipe` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Recipe {
+    title: String,
+    /// Number of servings, either a count such as `4` or a text such as `"2-3"`.
+    servings: Option<Value>,
+    #[serde(default)]
+    ingredients: Vec<String>,
+    #[serde(default)]
+    steps: Vec<String>,
+    /// URL of the original recipe, printed as QR code.
+    source: Option<String>,
+}
+
+/// Renders a recipe card with the ingredients and numbered steps.
+///
+/// Ingredients and steps are word wrapped with a hanging indent, so continuation lines
+/// stay aligned behind the bullet or step number. Titles too long for the title box are
+/// repeated in full above the ingredients.
+///
+/// # Arguments
+///
+/// * `variables` - `title`, `servings`, `ingredients` and `steps` (lists of text) and
+///   an optional `source` URL
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let recipe: Recipe = super::parse(variables)?;
+    if recipe.ingredients.is_empty() && recipe.steps.is_empty() {
+        return Err(PrinterError::Input(
+            "the recipe has neither ingredients nor steps".to_string(),
+        ));
+    }
+
+    let mut lines = Vec::new();
+    if recipe.title.chars().count() > FRAME_TITLE_WIDTH {
+        lines.extend(super::wrap(&recipe.title.to_uppercase(), ""));
+    }
+    match &recipe.servings {
+        Some(Value::String(servings)) if !servings.is_empty() => {
+            lines.push(format!("Serves {}", servings))
+        }
+        Some(Value::Number(servings)) => lines.push(format!("Serves {}", servings)),
+        _ => {}
+    }
+
+    if !recipe.ingredients.is_empty() {
+        lines.push(super::separator("Ingredients"));
+        for ingredient in &recipe.ingredients {
+            lines.extend(super::wrap(ingredient, "* "));
+        }
+    }
+
+    if !recipe.steps.is_empty() {
+        lines.push(super::separator("Steps"));
+        let number_width = recipe.steps.len().to_string().len();
+        for (index, step) in recipe.steps.iter().enumerate() {
+            let prefix = format!("{:>width$}. ", index + 1, width = number_width);
+            lines.extend(super::wrap(step, &prefix));
+        }
+    }
+
+    Ok(PrintTask {
+        title: Some(recipe.title),
+        message: lines.join("\n"),
+        link: recipe.source.filter(|source| !source.is_empty()),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,94 @@
+use crate::i18n::{self, Label};
+use crate::printer::PrintTask;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Variables of the built-in `shopping-list` template.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShoppingList {
+    /// Title of the slip, defaults to "SHOPPING".
+    title: Option<String>,
+    /// The items to buy.
+    items: Vec<ShoppingItem>,
+    /// Category order, e.g. the order of the aisles in the store. Categories missing
+    /// from this list follow in order of appearance.
+    #[serde(default)]
+    categories: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShoppingItem {
+    name: String,
+    /// Quantity, either a count such as `2` or a text such as `"500g"`.
+    qty: Option<Value>,
+    category: Option<String>,
+}
+
+/// Category of items without one, always printed last.
+const OTHER: &str = "Other";
+
+/// Renders a shopping list grouped by category with a checkbox per item.
+///
+/// # Arguments
+///
+/// * `variables` - `title`, `items` (objects with `name`, `qty` and `category`) and
+///   an optional `categories` order
+///
+/// # Returns
+///
+/// * `EscposResult<PrintTask>` - The task to print, or an input error for invalid variables
+pub fn render(variables: &Map<String, Value>) -> EscposResult<PrintTask> {
+    let list: ShoppingList = super::parse(variables)?;
+    if list.items.is_empty() {
+        return Err(PrinterError::Input(
+            "the shopping list has no items".to_string(),
+        ));
+    }
+
+    let mut categories: Vec<&str> = list.categories.iter().map(String::as_str).collect();
+    for item in &list.items {
+        let category = item.category.as_deref().unwrap_or(OTHER);
+        if category != OTHER && !categories.contains(&category) {
+            categories.push(category);
+        }
+    }
+    // Without any categories the list is printed without separators.
+    let grouped = !categories.is_empty();
+    if !categories.contains(&OTHER) {
+        categories.push(OTHER);
+    }
+
+    let mut lines = Vec::new();
+    for category in categories {
+        let items: Vec<&ShoppingItem> = list
+            .items
+            .iter()
+            .filter(|item| item.category.as_deref().unwrap_or(OTHER) == category)
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        if grouped {
+            lines.push(super::separator(category));
+        }
+        lines.extend(items.into_iter().map(checkbox));
+    }
+
+    Ok(PrintTask {
+        title: Some(list.title.unwrap_or_else(|| i18n::text(Label::Shopping))),
+        message: lines.join("\n"),
+        ..Default::default()
+    })
+}
+
+/// Formats an item as `[ ] 2x Milk` or `[ ] 500g Flour`.
+fn checkbox(item: &ShoppingItem) -> String {
+    match &item.qty {
+        Some(Value::Number(qty)) => format!("[ ] {}x {}", qty, item.name),
+        Some(Value::String(qty)) if !qty.is_empty() => format!("[ ] {} {}", qty, item.name),
+        _ => format!("[ ] {}", item.name),
+    }
+}
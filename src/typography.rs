@@ -0,0 +1,131 @@
+/// Maps typographic characters to equivalents available in every supported codepage.
+///
+/// Text pasted from phones and word processors often contains curly quotes, dashes,
+/// ellipses, bullets and special spaces. These characters are missing from the printer
+/// codepages and would otherwise be printed as garbage. Zero width characters are removed.
+///
+/// # Arguments
+///
+/// * `text` - The text to normalize
+///
+/// # Returns
+///
+/// * `String` - The normalized text
+///
+/// # Examples
+///
+/// ```
+/// use taskprinter::typography::normalize;
+///
+/// assert_eq!(normalize("“Done” – almost…"), "\"Done\" - almost...");
+/// ```
+pub fn normalize(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '‘' | '’' | '‚' | '‛' | '′' => output.push('\''),
+            '“' | '”' | '„' | '‟' | '″' => output.push('"'),
+            '‐' | '‑' | '‒' | '–' | '—' | '―' | '−' => output.push('-'),
+            '…' => output.push_str("..."),
+            '•' | '‣' | '◦' | '⁃' | '∙' | '●' | '▪' => output.push('*'),
+            '\u{00A0}' | '\u{2002}'..='\u{200A}' | '\u{202F}' | '\u{205F}' | '\u{3000}' => {
+                output.push(' ')
+            }
+            '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}' => {}
+            '→' => output.push_str("->"),
+            '←' => output.push_str("<-"),
+            '™' => output.push_str("TM"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+/// Collapses runs of blank lines into a single blank line and removes blank lines at
+/// the start and end. Lines containing only whitespace count as blank.
+///
+/// # Arguments
+///
+/// * `text` - The text to compress
+///
+/// # Returns
+///
+/// * `String` - The text with at most one blank line in a row
+///
+/// # Examples
+///
+/// ```
+/// use taskprinter::typography::squeeze_blank_lines;
+///
+/// assert_eq!(squeeze_blank_lines("\nstart\n\n \n\nend\n\n"), "start\n\nend");
+/// ```
+pub fn squeeze_blank_lines(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut blank = false;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            blank = !lines.is_empty();
+            continue;
+        }
+        if blank {
+            lines.push("");
+            blank = false;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Removes the whitespace at the end of every line.
+///
+/// # Arguments
+///
+/// * `text` - The text to trim
+///
+/// # Returns
+///
+/// * `String` - The text without trailing spaces and tabs
+pub fn trim_trailing_whitespace(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Removes the indentation shared by all non-blank lines, keeping relative indentation
+/// such as nested blocks intact.
+///
+/// # Arguments
+///
+/// * `text` - The text to dedent
+///
+/// # Returns
+///
+/// * `String` - The text with the common leading whitespace removed
+///
+/// # Examples
+///
+/// ```
+/// use taskprinter::typography::dedent;
+///
+/// assert_eq!(dedent("    if ok:\n        done"), "if ok:\n    done");
+/// ```
+pub fn dedent(text: &str) -> String {
+    let indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .reduce(|common, indent| {
+            let shared = common
+                .char_indices()
+                .zip(indent.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(indent.len()), |((index, _), _)| index);
+            &common[..shared]
+        })
+        .unwrap_or_default();
+    text.lines()
+        .map(|line| line.strip_prefix(indent).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
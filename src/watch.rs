@@ -0,0 +1,222 @@
+use crate::cli::Args;
+use crate::client;
+use crate::printer::{PrintTask, print_images, print_task};
+use crate::raster::{Bitmap, DEFAULT_WIDTH, Dither};
+use chrono::Local;
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Subdirectory receiving the printed files.
+const DONE_DIR: &str = "done";
+
+/// Subdirectory receiving the files that cannot be printed.
+const FAILED_DIR: &str = "failed";
+
+/// Options for the hot folder mode.
+#[derive(ClapArgs)]
+pub struct WatchArgs {
+    /// Directory to watch for .txt, .md and .png files
+    pub dir: PathBuf,
+
+    /// Seconds between two scans of the directory
+    #[arg(long, default_value = "2")]
+    pub interval: u64,
+}
+
+/// Kind of file printed from the hot folder.
+enum FileKind {
+    Text,
+    Image,
+}
+
+/// Size and modification time of a file, used to detect files still being written.
+type Snapshot = (u64, Option<SystemTime>);
+
+/// Watches a directory and prints every `.txt`, `.md` and `.png` file dropped into it.
+///
+/// The directory is scanned every `interval` seconds instead of relying on file system
+/// notifications, which are not delivered for network shares. A file is printed once
+/// its size and modification time did not change between two scans, so files still
+/// being copied are not printed half-written. Text files are printed as message with the
+/// file name as title, images are dithered and printed `--width` dots wide.
+///
+/// Printed files are moved to `done/`, files that cannot be read or decoded to
+/// `failed/`. If the printer is unreachable, the file is left in place and retried with
+/// the next scan. The function runs until the process is terminated.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `watch` - The hot folder options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - An error if the directory does not exist or its subdirectories
+///   cannot be created or read
+pub fn run_watch(args: &Args, watch: &WatchArgs) -> EscposResult<()> {
+    if !watch.dir.is_dir() {
+        return Err(PrinterError::Input(format!(
+            "{} is not a directory",
+            watch.dir.display()
+        )));
+    }
+    for dir in [DONE_DIR, FAILED_DIR] {
+        let dir = watch.dir.join(dir);
+        fs::create_dir_all(&dir)
+            .map_err(|e| PrinterError::Io(format!("cannot create {}: {}", dir.display(), e)))?;
+    }
+    info!("Watching {} for files to print", watch.dir.display());
+
+    let mut seen: HashMap<PathBuf, Snapshot> = HashMap::new();
+    // Files that were handled but could not be moved, so they are not printed again.
+    let mut stuck: HashMap<PathBuf, Snapshot> = HashMap::new();
+    loop {
+        let mut files = scan(&watch.dir)?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        stuck.retain(|path, snapshot| files.contains(&(path.clone(), *snapshot)));
+
+        let mut next = HashMap::new();
+        for (path, snapshot) in files {
+            if stuck.contains_key(&path) {
+                continue;
+            }
+            if seen.get(&path) != Some(&snapshot) {
+                // New or still growing, look again with the next scan.
+                next.insert(path, snapshot);
+                continue;
+            }
+            let target = match print_file(args, &path) {
+                Ok(()) => {
+                    info!("Printed {}", path.display());
+                    DONE_DIR
+                }
+                Err(PrinterError::Io(e)) => {
+                    warn!("Cannot print {}, retrying: {}", path.display(), e);
+                    next.insert(path, snapshot);
+                    continue;
+                }
+                Err(e) => {
+                    error!("Cannot print {}: {}", path.display(), e);
+                    FAILED_DIR
+                }
+            };
+            if !move_to(&path, &watch.dir.join(target)) {
+                stuck.insert(path, snapshot);
+            }
+        }
+        seen = next;
+
+        thread::sleep(Duration::from_secs(watch.interval.max(1)));
+    }
+}
+
+/// Lists the printable files of the directory with their current snapshot.
+fn scan(dir: &Path) -> EscposResult<Vec<(PathBuf, Snapshot)>> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| PrinterError::Io(format!("cannot read {}: {}", dir.display(), e)))?;
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter(|entry| file_kind(&entry.path()).is_some())
+        .filter_map(|entry| {
+            let metadata = entry
+                .metadata()
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            Some((entry.path(), (metadata.len(), metadata.modified().ok())))
+        })
+        .collect())
+}
+
+/// Determines how a file is printed from its extension, or None for other files.
+fn file_kind(path: &Path) -> Option<FileKind> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    match extension.as_str() {
+        "txt" | "md" => Some(FileKind::Text),
+        "png" => Some(FileKind::Image),
+        _ => None,
+    }
+}
+
+/// Prints a single file from the hot folder.
+fn print_file(args: &Args, path: &Path) -> EscposResult<()> {
+    let read_error =
+        |e: std::io::Error| PrinterError::Input(format!("cannot read {}: {}", path.display(), e));
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+
+    match file_kind(path) {
+        Some(FileKind::Text) => {
+            let content = fs::read(path).map_err(read_error)?;
+            let task = PrintTask {
+                title: args.title.clone().or(name),
+                message: String::from_utf8_lossy(&content).trim().to_string(),
+                address: args.address.clone(),
+                port: args.port,
+                codepage: args.codepage.clone(),
+                mode: args.mode.clone(),
+                hyphenate: args.hyphenate.clone(),
+                normalize: Some(!args.no_normalize),
+                squeeze_blank: Some(args.squeeze_blank),
+                trim_trailing: Some(args.trim_trailing),
+                dedent: Some(args.dedent),
+                from: args.from.clone(),
+                ..Default::default()
+            };
+            match &args.server {
+                Some(server) => client::submit(&client::base_url(Some(server)), &task),
+                None => print_task(task),
+            }
+        }
+        Some(FileKind::Image) => {
+            if args.server.is_some() {
+                return Err(PrinterError::Input(
+                    "images cannot be printed via --server".to_string(),
+                ));
+            }
+            let bitmap = Bitmap::decode(
+                &fs::read(path).map_err(read_error)?,
+                args.width.unwrap_or(DEFAULT_WIDTH),
+                Dither::parse(args.dithering.as_deref())?,
+            )?;
+            print_images(
+                PrintTask {
+                    address: args.address.clone(),
+                    port: args.port,
+                    codepage: args.codepage.clone(),
+                    ..Default::default()
+                },
+                &[bitmap],
+                args.chunking(),
+            )
+        }
+        None => Ok(()),
+    }
+}
+
+/// Moves a file into a subdirectory, prefixing the name with the time if it is taken.
+/// Returns whether the file was moved.
+fn move_to(path: &Path, dir: &Path) -> bool {
+    let Some(name) = path.file_name() else {
+        return false;
+    };
+    let mut target = dir.join(name);
+    if target.exists() {
+        let stamp = Local::now().format("%Y%m%d-%H%M%S");
+        target = dir.join(format!("{}-{}", stamp, name.to_string_lossy()));
+    }
+    match fs::rename(path, &target) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Cannot move {} to {}: {}", path.display(), dir.display(), e);
+            false
+        }
+    }
+}
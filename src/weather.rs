@@ -0,0 +1,173 @@
+use crate::cli::Args;
+use crate::i18n::{self, Label};
+use crate::printer::{PrintTask, print_task};
+use clap::Args as ClapArgs;
+use escpos::errors::{PrinterError, Result as EscposResult};
+use serde::Deserialize;
+
+/// Open-Meteo forecast endpoint (free, no API key required).
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+/// Options for the weather mode.
+#[derive(ClapArgs)]
+pub struct WeatherArgs {
+    /// Latitude of the forecast location
+    #[arg(long, allow_hyphen_values = true)]
+    pub latitude: f64,
+
+    /// Longitude of the forecast location
+    #[arg(long, allow_hyphen_values = true)]
+    pub longitude: f64,
+
+    /// Location name printed in the header
+    #[arg(long)]
+    pub location: Option<String>,
+}
+
+/// Daily forecast section of the Open-Meteo response, one entry per day.
+#[derive(Deserialize)]
+struct Daily {
+    weather_code: Vec<u8>,
+    temperature_2m_max: Vec<f64>,
+    temperature_2m_min: Vec<f64>,
+    precipitation_probability_max: Vec<Option<u8>>,
+}
+
+#[derive(Deserialize)]
+struct Forecast {
+    daily: Daily,
+}
+
+/// Fetches today's forecast from Open-Meteo and prints it as a weather slip.
+///
+/// # Arguments
+///
+/// * `args` - The parsed command line arguments providing the printer settings
+/// * `weather` - The location options
+///
+/// # Returns
+///
+/// * `EscposResult<()>` - Ok(()) on successful print, or an error if the forecast could
+///   not be fetched or printed
+pub fn run_weather(args: &Args, weather: &WeatherArgs) -> EscposResult<()> {
+    print_task(PrintTask {
+        title: Some(
+            weather
+                .location
+                .clone()
+                .unwrap_or_else(|| i18n::text(Label::Weather)),
+        ),
+        message: today(weather.latitude, weather.longitude)?,
+        address: args.address.clone(),
+        port: args.port,
+        codepage: args.codepage.clone(),
+        media: args.media.clone(),
+        label_length: args.label_length,
+        ..Default::default()
+    })
+}
+
+/// Fetches today's forecast from Open-Meteo and renders it as slip text.
+///
+/// # Arguments
+///
+/// * `latitude` - Latitude of the forecast location
+/// * `longitude` - Longitude of the forecast location
+///
+/// # Returns
+///
+/// * `EscposResult<String>` - The ASCII icon, condition, temperatures and rain chance, or
+///   an error if the forecast could not be fetched
+pub fn today(latitude: f64, longitude: f64) -> EscposResult<String> {
+    render_forecast(&fetch_forecast(latitude, longitude)?.daily)
+}
+
+fn fetch_forecast(latitude: f64, longitude: f64) -> EscposResult<Forecast> {
+    ureq::get(FORECAST_URL)
+        .query("latitude", &latitude.to_string())
+        .query("longitude", &longitude.to_string())
+        .query(
+            "daily",
+            "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max",
+        )
+        .query("timezone", "auto")
+        .query("forecast_days", "1")
+        .call()
+        .map_err(|e| PrinterError::Io(format!("weather request failed: {}", e)))?
+        .into_json()
+        .map_err(|e| PrinterError::InvalidResponse(format!("invalid weather response: {}", e)))
+}
+
+/// Renders today's forecast as an ASCII icon followed by condition, temperatures and rain chance.
+fn render_forecast(daily: &Daily) -> EscposResult<String> {
+    let (Some(code), Some(max), Some(min)) = (
+        daily.weather_code.first(),
+        daily.temperature_2m_max.first(),
+        daily.temperature_2m_min.first(),
+    ) else {
+        return Err(PrinterError::InvalidResponse(
+            "weather response contains no forecast for today".to_string(),
+        ));
+    };
+    let rain = daily
+        .precipitation_probability_max
+        .first()
+        .copied()
+        .flatten()
+        .unwrap_or(0);
+
+    let (icon, condition) = describe(*code);
+    let mut lines: Vec<String> = icon.iter().map(|line| line.to_string()).collect();
+    lines.push(String::new());
+    lines.push(condition.to_string());
+    lines.push(format!("High {:.0}C  Low {:.0}C", max, min));
+    lines.push(format!("Rain chance {}%", rain));
+    Ok(lines.join("\n"))
+}
+
+/// Maps a WMO weather interpretation code to an ASCII icon and a short description.
+fn describe(code: u8) -> (&'static [&'static str], &'static str) {
+    const SUN: &[&str] = &[
+        "    \\   /",
+        "     .-.",
+        "  - (   ) -",
+        "     `-'",
+        "    /   \\",
+    ];
+    const PARTLY: &[&str] = &["   \\  /", " _ /\"\".-.", "   \\_(   ).", "   /(___(__)"];
+    const CLOUD: &[&str] = &["     .--.", "  .-(    ).", " (___.__)__)"];
+    const FOG: &[&str] = &[" _ - _ - _ -", "  _ - _ - _", " _ - _ - _ -"];
+    const RAIN: &[&str] = &[
+        "     .-.",
+        "    (   ).",
+        "   (___(__)",
+        "    ' ' ' '",
+        "   ' ' ' '",
+    ];
+    const SNOW: &[&str] = &[
+        "     .-.",
+        "    (   ).",
+        "   (___(__)",
+        "    *  *  *",
+        "   *  *  *",
+    ];
+    const STORM: &[&str] = &[
+        "     .-.",
+        "    (   ).",
+        "   (___(__)",
+        "    /_ /_",
+        "     /  /",
+    ];
+
+    match code {
+        0 => (SUN, "Clear sky"),
+        1 | 2 => (PARTLY, "Partly cloudy"),
+        3 => (CLOUD, "Overcast"),
+        45 | 48 => (FOG, "Fog"),
+        51..=57 => (RAIN, "Drizzle"),
+        61..=67 | 80..=82 => (RAIN, "Rain"),
+        71..=77 | 85 | 86 => (SNOW, "Snow"),
+        95..=99 => (STORM, "Thunderstorm"),
+        _ => (CLOUD, "Unknown"),
+    }
+}
@@ -0,0 +1,193 @@
+use crate::api::{AppState, PrintResponse, queue_task};
+use crate::i18n::{self, Label};
+use crate::printer::PrintTask;
+use crate::webhooks::{WebhookTarget, ignored};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Json, Response},
+};
+use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Conclusions that count as a failed run.
+const FAILED_CONCLUSIONS: &[&str] = &[
+    "failure",
+    "failed",
+    "error",
+    "errored",
+    "timed_out",
+    "broken",
+    "startup_failure",
+];
+
+/// Query parameters of the CI webhook endpoint.
+#[derive(Deserialize)]
+pub struct CiQuery {
+    /// Also print successful runs instead of ignoring them.
+    pub include_success: Option<bool>,
+}
+
+/// A completed CI run, normalized from the supported payload formats.
+#[derive(Debug, Clone)]
+pub struct CiRun {
+    pub repo: String,
+    pub branch: String,
+    pub job: String,
+    pub actor: String,
+    pub url: Option<String>,
+    pub conclusion: String,
+}
+
+impl CiRun {
+    /// Whether the run failed.
+    pub fn failed(&self) -> bool {
+        FAILED_CONCLUSIONS.contains(&self.conclusion.to_lowercase().as_str())
+    }
+}
+
+/// CI webhook endpoint handler.
+///
+/// This function handles POST requests to `/webhook/ci`. It understands GitHub Actions
+/// `workflow_run` and `workflow_job` events (selected by the `X-GitHub-Event` header) as
+/// well as a generic JSON format for other CI systems. Failed runs are printed with the
+/// repository, branch, job name and actor, and the run URL as a QR code. Successful runs
+/// are ignored unless `include_success=true` is passed as a query parameter.
+///
+/// # Generic Payload Format
+///
+/// ```json
+/// {
+///   "repo": "acme/website",
+///   "branch": "main",
+///   "job": "build",
+///   "actor": "jane",
+///   "status": "failed",
+///   "url": "https://ci.example.com/runs/42"
+/// }
+/// ```
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - A success response when the
+///   run was printed or ignored, otherwise the response of the print queue, see
+///   [`queue_task`]
+pub async fn ci_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(target): Query<WebhookTarget>,
+    Query(query): Query<CiQuery>,
+    Json(payload): Json<Value>,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let event = headers
+        .get("x-github-event")
+        .and_then(|value| value.to_str().ok());
+
+    let Some(run) = parse_ci_run(event, &payload) else {
+        return Ok(ignored("Event is not a completed CI run"));
+    };
+
+    if !run.failed() && query.include_success != Some(true) {
+        info!(
+            "Ignoring {} run of {} on {}",
+            run.conclusion, run.job, run.repo
+        );
+        return Ok(ignored("Run did not fail"));
+    }
+
+    info!(
+        "Received CI run {} of {} on {}",
+        run.conclusion, run.job, run.repo
+    );
+    let title = if run.failed() {
+        i18n::text(Label::CiFailed)
+    } else {
+        i18n::text(Label::CiPassed)
+    };
+    queue_task(
+        &state.queue,
+        PrintTask {
+            title: Some(title),
+            message: format!(
+                "{}\nBranch: {}\nJob: {}\nBy: {}\nStatus: {}",
+                run.repo, run.branch, run.job, run.actor, run.conclusion
+            ),
+            address: target.address,
+            port: target.port,
+            codepage: target.codepage,
+            link: run.url,
+            ..Default::default()
+        },
+        None,
+        None,
+    )
+    .await
+}
+
+/// Normalizes a CI webhook payload into a `CiRun`.
+///
+/// # Arguments
+///
+/// * `event` - The value of the `X-GitHub-Event` header, if present
+/// * `payload` - The JSON payload
+///
+/// # Returns
+///
+/// * `Option<CiRun>` - The completed run, or None if the payload is not a completed run
+pub fn parse_ci_run(event: Option<&str>, payload: &Value) -> Option<CiRun> {
+    let text = |pointer: &str| payload.pointer(pointer).and_then(Value::as_str);
+
+    match event {
+        Some("workflow_run") => {
+            if text("/action") != Some("completed") {
+                return None;
+            }
+            Some(CiRun {
+                repo: text("/repository/full_name")?.to_string(),
+                branch: text("/workflow_run/head_branch").unwrap_or("-").to_string(),
+                job: text("/workflow_run/name").unwrap_or("-").to_string(),
+                actor: text("/workflow_run/actor/login")
+                    .or(text("/sender/login"))
+                    .unwrap_or("-")
+                    .to_string(),
+                url: text("/workflow_run/html_url").map(str::to_string),
+                conclusion: text("/workflow_run/conclusion")?.to_string(),
+            })
+        }
+        Some("workflow_job") => {
+            if text("/action") != Some("completed") {
+                return None;
+            }
+            let job = match (
+                text("/workflow_job/workflow_name"),
+                text("/workflow_job/name"),
+            ) {
+                (Some(workflow), Some(job)) => format!("{} / {}", workflow, job),
+                (None, Some(job)) => job.to_string(),
+                _ => "-".to_string(),
+            };
+            Some(CiRun {
+                repo: text("/repository/full_name")?.to_string(),
+                branch: text("/workflow_job/head_branch").unwrap_or("-").to_string(),
+                job,
+                actor: text("/sender/login").unwrap_or("-").to_string(),
+                url: text("/workflow_job/html_url").map(str::to_string),
+                conclusion: text("/workflow_job/conclusion")?.to_string(),
+            })
+        }
+        Some(_) => None,
+        None => Some(CiRun {
+            repo: text("/repo").or(text("/repository"))?.to_string(),
+            branch: text("/branch").unwrap_or("-").to_string(),
+            job: text("/job").or(text("/name")).unwrap_or("-").to_string(),
+            actor: text("/actor").or(text("/user")).unwrap_or("-").to_string(),
+            url: text("/url").map(str::to_string),
+            conclusion: text("/status")
+                .or(text("/conclusion"))
+                .or(text("/result"))?
+                .to_string(),
+        }),
+    }
+}
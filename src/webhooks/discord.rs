@@ -0,0 +1,165 @@
+use crate::api::AppState;
+use crate::printer::PrintTask;
+use crate::queue::JobOutcome;
+use crate::webhooks::WebhookTarget;
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use log::{error, info, warn};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// Interaction type sent by Discord to validate the endpoint.
+const INTERACTION_PING: u64 = 1;
+/// Interaction type of a slash command invocation.
+const INTERACTION_APPLICATION_COMMAND: u64 = 2;
+/// Response type acknowledging a ping.
+const RESPONSE_PONG: u64 = 1;
+/// Response type replying with a channel message.
+const RESPONSE_CHANNEL_MESSAGE: u64 = 4;
+/// Message flag making the reply only visible to the invoking user.
+const FLAG_EPHEMERAL: u64 = 64;
+
+/// Discord interactions endpoint handler.
+///
+/// This function handles POST requests to `/discord/interactions`, the "Interactions
+/// Endpoint URL" of a Discord application. Every request is verified with the
+/// application's public key from the `[discord]` config section. A `/print` slash
+/// command with a required `message` and optional `title` option prints the text,
+/// provided the invoking member has one of the configured `allowed_roles`.
+///
+/// # Returns
+///
+/// * `Result<Json<Value>, StatusCode>` - The interaction response. HTTP 404 if the
+///   integration is not configured, HTTP 401 if the signature is invalid and HTTP 400
+///   for malformed payloads.
+pub async fn discord_interactions_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(target): Query<WebhookTarget>,
+    body: Bytes,
+) -> Result<Json<Value>, StatusCode> {
+    let Some(config) = &state.config.discord else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+    if !verify_signature(&config.public_key, &headers, &body) {
+        warn!("Rejected Discord interaction with invalid signature");
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let interaction: Value = serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST)?;
+    match interaction.get("type").and_then(Value::as_u64) {
+        Some(INTERACTION_PING) => return Ok(Json(json!({ "type": RESPONSE_PONG }))),
+        Some(INTERACTION_APPLICATION_COMMAND) => {}
+        _ => return Err(StatusCode::BAD_REQUEST),
+    }
+
+    if interaction.pointer("/data/name").and_then(Value::as_str) != Some("print") {
+        return Ok(reply("Unknown command"));
+    }
+
+    let roles: Vec<&str> = interaction
+        .pointer("/member/roles")
+        .and_then(Value::as_array)
+        .map(|roles| roles.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    if !config.allowed_roles.is_empty()
+        && !config
+            .allowed_roles
+            .iter()
+            .any(|role| roles.contains(&role.as_str()))
+    {
+        return Ok(reply("You are not allowed to use the printer."));
+    }
+
+    let option = |name: &str| {
+        interaction
+            .pointer("/data/options")
+            .and_then(Value::as_array)
+            .and_then(|options| {
+                options
+                    .iter()
+                    .find(|option| option.get("name").and_then(Value::as_str) == Some(name))
+            })
+            .and_then(|option| option.get("value"))
+            .and_then(Value::as_str)
+    };
+    let Some(message) = option("message") else {
+        return Ok(reply("Missing message"));
+    };
+    let user = interaction
+        .pointer("/member/user/username")
+        .or(interaction.pointer("/user/username"))
+        .and_then(Value::as_str)
+        .unwrap_or("someone");
+
+    info!("Received Discord print command from {}", user);
+    let task = PrintTask {
+        title: Some(option("title").unwrap_or("DISCORD").to_string()),
+        message: format!("{}\n\n- {}", message, user),
+        address: target.address,
+        port: target.port,
+        codepage: target.codepage,
+        ..Default::default()
+    };
+    let outcome = match state.queue.submit(task, None, None) {
+        Ok((_, outcome)) => outcome.await,
+        Err(e) => {
+            warn!("Cannot queue Discord print command: {}", e);
+            return Ok(reply("The printer is busy, try again later."));
+        }
+    };
+
+    Ok(match outcome {
+        Ok(JobOutcome::Printed) => reply("Printed!"),
+        Ok(JobOutcome::Spooled) => reply("The printer is offline, it will print later."),
+        Ok(JobOutcome::Failed(e)) => {
+            error!("Print error: {}", e);
+            reply("Printing failed, is the printer on?")
+        }
+        Ok(JobOutcome::Expired | JobOutcome::Cancelled) | Err(_) => {
+            reply("The print job was cancelled.")
+        }
+    })
+}
+
+/// Verifies the Ed25519 signature Discord sends with every interaction.
+///
+/// The signed content is the `X-Signature-Timestamp` header followed by the raw body.
+fn verify_signature(public_key: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let (Some(signature), Some(timestamp)) = (
+        header("x-signature-ed25519"),
+        header("x-signature-timestamp"),
+    ) else {
+        return false;
+    };
+
+    let key = hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .map(|bytes| Signature::from_bytes(&bytes));
+    let (Some(key), Some(signature)) = (key, signature) else {
+        return false;
+    };
+
+    let mut message = timestamp.as_bytes().to_vec();
+    message.extend_from_slice(body);
+    key.verify(&message, &signature).is_ok()
+}
+
+/// Builds an ephemeral channel message reply.
+fn reply(content: &str) -> Json<Value> {
+    Json(json!({
+        "type": RESPONSE_CHANNEL_MESSAGE,
+        "data": { "content": content, "flags": FLAG_EPHEMERAL }
+    }))
+}
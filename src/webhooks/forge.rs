@@ -0,0 +1,132 @@
+use crate::api::{AppState, PrintResponse, queue_task};
+use crate::config::ForgeConfig;
+use crate::printer::PrintTask;
+use crate::webhooks::{WebhookTarget, ignored};
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Kind of item a forge event refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    Issue,
+    MergeRequest,
+}
+
+/// An issue or merge request event, normalized across forges.
+#[derive(Debug, Clone)]
+pub struct ForgeEvent {
+    /// Repository path, e.g. `group/project`.
+    pub repo: String,
+    pub kind: ItemKind,
+    /// Normalized action: `opened`, `closed`, `reopened`, `merged`, or the forge's own name.
+    pub action: String,
+    /// Issue or merge request number within the repository.
+    pub number: u64,
+    pub title: String,
+    /// User who triggered the event.
+    pub actor: String,
+    pub url: Option<String>,
+}
+
+/// A source forge whose webhooks can be verified and normalized into `ForgeEvent`s.
+///
+/// Adding support for another forge only requires implementing this trait and
+/// registering a route for `forge_webhook_handler::<NewForge>`.
+pub trait Forge {
+    /// Human-readable forge name used in logs.
+    const NAME: &'static str;
+
+    /// Returns the configuration section of this forge, if enabled.
+    fn config(state: &AppState) -> Option<&ForgeConfig>;
+
+    /// Checks the authenticity of the request against the configured secret.
+    fn verify(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool;
+
+    /// Converts the payload into a normalized event, or None for unsupported events.
+    fn parse(headers: &HeaderMap, payload: &Value) -> Option<ForgeEvent>;
+}
+
+/// Generic forge webhook endpoint handler.
+///
+/// Verifies the request with the forge specific scheme, normalizes the payload and
+/// prints a card for events whose action is listed in the forge's `actions` setting.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - A success response when the
+///   event was printed or ignored. HTTP 404 if the forge is not configured, HTTP 401 if
+///   no secret is configured or the verification fails, HTTP 400 for malformed payloads,
+///   otherwise the response of the print queue, see [`queue_task`].
+pub async fn forge_webhook_handler<F: Forge>(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(target): Query<WebhookTarget>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let Some(config) = F::config(&state) else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    if config.secret.is_empty() || !F::verify(&config.secret, &headers, &body) {
+        warn!("Rejected {} webhook with invalid secret", F::NAME);
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    }
+
+    let payload: Value =
+        serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+    let Some(event) = F::parse(&headers, &payload) else {
+        return Ok(ignored("not an issue or merge request event"));
+    };
+    if !config.actions.contains(&event.action) {
+        return Ok(ignored(&format!(
+            "action '{}' is not printed",
+            event.action
+        )));
+    }
+
+    info!(
+        "Received {} {:?} {} of {}",
+        F::NAME,
+        event.kind,
+        event.action,
+        event.repo
+    );
+    queue_task(
+        &state.queue,
+        PrintTask {
+            address: target.address,
+            port: target.port,
+            codepage: target.codepage,
+            ..event_card(&event)
+        },
+        None,
+        None,
+    )
+    .await
+}
+
+/// Builds the printed card for a forge event.
+pub fn event_card(event: &ForgeEvent) -> PrintTask {
+    let title = match event.kind {
+        ItemKind::Issue => format!("ISSUE #{}", event.number),
+        ItemKind::MergeRequest => format!("MR !{}", event.number),
+    };
+    PrintTask {
+        title: Some(title),
+        message: format!(
+            "{}\n{}\n\n{} by {}",
+            event.repo,
+            event.title,
+            event.action.to_uppercase(),
+            event.actor
+        ),
+        link: event.url.clone(),
+        ..Default::default()
+    }
+}
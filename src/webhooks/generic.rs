@@ -0,0 +1,145 @@
+use crate::api::{AppState, PrintResponse, queue_task};
+use crate::config::GenericWebhookConfig;
+use crate::printer::PrintTask;
+use crate::webhooks::{WebhookTarget, verify_hmac_sha256};
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Generic webhook endpoint handler.
+///
+/// This function handles POST requests to `/webhook/{name}` for every webhook defined
+/// in a `[webhooks.<name>]` config section. The request body must be signed with the
+/// configured secret as hex encoded HMAC-SHA256 in the configured header. The task is
+/// built from the JSON payload with the field mapping of the section, see
+/// [`GenericWebhookConfig`].
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - A success response when the
+///   task was printed. HTTP 404 if no webhook with that name is configured, HTTP 401 if
+///   the signature is missing or invalid, HTTP 400 if the payload is not JSON or the
+///   message mapping yields no text, otherwise the response of the print queue, see
+///   [`queue_task`].
+pub async fn generic_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Query(target): Query<WebhookTarget>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let Some(config) = state.config.webhooks.get(&name) else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let signature = headers
+        .get(config.header.as_str())
+        .and_then(|value| value.to_str().ok());
+    if !signature.is_some_and(|signature| verify_hmac_sha256(&config.secret, &body, signature)) {
+        warn!("Rejected {} webhook with invalid signature", name);
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    }
+
+    let payload: Value =
+        serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+    let task = map_task(config, &payload);
+    if task.message.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST.into_response());
+    }
+
+    info!("Received {} webhook", name);
+    queue_task(
+        &state.queue,
+        PrintTask {
+            address: target.address,
+            port: target.port,
+            codepage: target.codepage,
+            ..task
+        },
+        None,
+        None,
+    )
+    .await
+}
+
+/// Builds the task from the payload using the field mapping of a webhook.
+fn map_task(config: &GenericWebhookConfig, payload: &Value) -> PrintTask {
+    let field = |mapping: &Option<String>| {
+        mapping
+            .as_deref()
+            .map(|mapping| render(mapping, payload))
+            .filter(|value| !value.is_empty())
+    };
+    PrintTask {
+        title: field(&config.title),
+        message: render(&config.message, payload),
+        date: field(&config.date),
+        link: field(&config.link),
+        ..Default::default()
+    }
+}
+
+/// Renders a mapping against the payload.
+///
+/// A mapping that starts with `$` is a single JSONPath expression. Otherwise it is a
+/// template where every `{$...}` placeholder is replaced with the selected value,
+/// e.g. `"{$.alert.name} on {$.host}"`. Missing values render as empty text.
+fn render(mapping: &str, payload: &Value) -> String {
+    if mapping.starts_with('$') {
+        return select(payload, mapping).map(text).unwrap_or_default();
+    }
+
+    let mut output = String::new();
+    let mut rest = mapping;
+    while let Some(start) = rest.find("{$") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let path = &rest[start + 1..start + end];
+        output.push_str(&select(payload, path).map(text).unwrap_or_default());
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Selects a value with a JSONPath subset: `$`, `.key`, `['key']` and `[index]`.
+fn select<'a>(payload: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current = payload;
+    let mut rest = path.strip_prefix('$')?;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            current = current.get(&after[..end])?;
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let key = &after[..end];
+            current = match key.trim_matches(['\'', '"']) {
+                quoted if quoted.len() != key.len() => current.get(quoted)?,
+                index => current.get(index.parse::<usize>().ok()?)?,
+            };
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(current)
+}
+
+/// Converts a selected value to printable text. Strings are printed without quotes,
+/// null as empty text and arrays or objects as compact JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,57 @@
+use crate::api::AppState;
+use crate::config::ForgeConfig;
+use crate::webhooks::forge::{Forge, ForgeEvent, ItemKind};
+use crate::webhooks::verify_hmac_sha256;
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+/// Gitea (and Forgejo) webhooks, signed with HMAC-SHA256 in the `X-Gitea-Signature` header.
+pub struct Gitea;
+
+impl Forge for Gitea {
+    const NAME: &'static str = "Gitea";
+
+    fn config(state: &AppState) -> Option<&ForgeConfig> {
+        state.config.gitea.as_ref()
+    }
+
+    fn verify(secret: &str, headers: &HeaderMap, body: &[u8]) -> bool {
+        headers
+            .get("x-gitea-signature")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|signature| verify_hmac_sha256(secret, body, signature))
+    }
+
+    fn parse(headers: &HeaderMap, payload: &Value) -> Option<ForgeEvent> {
+        let text = |pointer: &str| payload.pointer(pointer).and_then(Value::as_str);
+        let event = headers.get("x-gitea-event")?.to_str().ok()?;
+        let (kind, item) = match event {
+            "issues" => (ItemKind::Issue, "/issue"),
+            "pull_request" => (ItemKind::MergeRequest, "/pull_request"),
+            _ => return None,
+        };
+        let field = |name: &str| text(&format!("{}/{}", item, name));
+
+        let merged = payload
+            .pointer(&format!("{}/merged", item))
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let action = match text("/action")? {
+            "closed" if merged => "merged",
+            other => other,
+        };
+
+        Some(ForgeEvent {
+            repo: text("/repository/full_name")?.to_string(),
+            kind,
+            action: action.to_string(),
+            number: payload
+                .pointer(&format!("{}/number", item))
+                .and_then(Value::as_u64)
+                .or(payload.get("number").and_then(Value::as_u64))?,
+            title: field("title").unwrap_or("").to_string(),
+            actor: text("/sender/login").unwrap_or("-").to_string(),
+            url: field("html_url").map(str::to_string),
+        })
+    }
+}
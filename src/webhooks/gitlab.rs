@@ -0,0 +1,54 @@
+use crate::api::AppState;
+use crate::config::ForgeConfig;
+use crate::webhooks::forge::{Forge, ForgeEvent, ItemKind};
+use crate::webhooks::verify_token;
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+/// GitLab webhooks, authenticated with the plain `X-Gitlab-Token` header.
+pub struct GitLab;
+
+impl Forge for GitLab {
+    const NAME: &'static str = "GitLab";
+
+    fn config(state: &AppState) -> Option<&ForgeConfig> {
+        state.config.gitlab.as_ref()
+    }
+
+    fn verify(secret: &str, headers: &HeaderMap, _body: &[u8]) -> bool {
+        headers
+            .get("x-gitlab-token")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|token| verify_token(secret, token))
+    }
+
+    fn parse(_headers: &HeaderMap, payload: &Value) -> Option<ForgeEvent> {
+        let text = |pointer: &str| payload.pointer(pointer).and_then(Value::as_str);
+        let kind = match text("/object_kind")? {
+            "issue" => ItemKind::Issue,
+            "merge_request" => ItemKind::MergeRequest,
+            _ => return None,
+        };
+        let action = match text("/object_attributes/action")? {
+            "open" => "opened",
+            "close" => "closed",
+            "reopen" => "reopened",
+            "merge" => "merged",
+            "update" => "updated",
+            other => other,
+        };
+
+        Some(ForgeEvent {
+            repo: text("/project/path_with_namespace")?.to_string(),
+            kind,
+            action: action.to_string(),
+            number: payload.pointer("/object_attributes/iid")?.as_u64()?,
+            title: text("/object_attributes/title").unwrap_or("").to_string(),
+            actor: text("/user/username")
+                .or(text("/user/name"))
+                .unwrap_or("-")
+                .to_string(),
+            url: text("/object_attributes/url").map(str::to_string),
+        })
+    }
+}
@@ -0,0 +1,149 @@
+use crate::api::{AppState, PrintResponse, queue_task};
+use crate::printer::PrintTask;
+use crate::webhooks::{WebhookTarget, ignored, verify_hmac_sha256, verify_token};
+use axum::{
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Query parameters of the Jira webhook endpoint.
+#[derive(Deserialize)]
+pub struct JiraQuery {
+    /// Shared secret for Jira Server/Data Center webhooks, which cannot sign requests.
+    pub secret: Option<String>,
+}
+
+/// Jira webhook endpoint handler.
+///
+/// This function handles POST requests to `/webhook/jira`. Requests are authenticated
+/// with the shared secret from the `[jira]` config section, either through the
+/// `X-Hub-Signature` HMAC header sent by Jira Cloud or a `secret` query parameter.
+/// Newly created issues and issues assigned to the configured `assignee` are printed
+/// as a compact issue card; all other events are ignored.
+///
+/// # Returns
+///
+/// * `Result<(StatusCode, Json<PrintResponse>), Response>` - A success response when the
+///   issue was printed or ignored. HTTP 404 if the integration is not configured, HTTP
+///   401 if the secret does not match, HTTP 400 for malformed payloads, otherwise the
+///   response of the print queue, see [`queue_task`].
+pub async fn jira_webhook_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(target): Query<WebhookTarget>,
+    Query(query): Query<JiraQuery>,
+    body: Bytes,
+) -> Result<(StatusCode, Json<PrintResponse>), Response> {
+    let Some(config) = &state.config.jira else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+
+    let signature = headers
+        .get("x-hub-signature")
+        .and_then(|value| value.to_str().ok());
+    let authorized = match (signature, &query.secret) {
+        (Some(signature), _) => verify_hmac_sha256(&config.secret, &body, signature),
+        (None, Some(secret)) => verify_token(&config.secret, secret),
+        (None, None) => false,
+    };
+    if !authorized {
+        warn!("Rejected Jira webhook with invalid secret");
+        return Err(StatusCode::UNAUTHORIZED.into_response());
+    }
+
+    let payload: Value =
+        serde_json::from_slice(&body).map_err(|_| StatusCode::BAD_REQUEST.into_response())?;
+    let event = payload
+        .get("webhookEvent")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+
+    let reason = match event {
+        "jira:issue_created" => "NEW",
+        "jira:issue_updated" if assigned_to(&payload, config.assignee.as_deref()) => "ASSIGNED",
+        _ => {
+            return Ok(ignored(&format!(
+                "{} is not a created or assigned issue",
+                event
+            )));
+        }
+    };
+
+    let Some(task) = issue_card(&payload, reason) else {
+        return Err(StatusCode::BAD_REQUEST.into_response());
+    };
+    info!(
+        "Received Jira issue {}",
+        task.title.as_deref().unwrap_or("")
+    );
+    queue_task(
+        &state.queue,
+        PrintTask {
+            address: target.address,
+            port: target.port,
+            codepage: target.codepage,
+            ..task
+        },
+        None,
+        None,
+    )
+    .await
+}
+
+/// Checks whether the changelog of an update event assigns the issue to the given user.
+fn assigned_to(payload: &Value, assignee: Option<&str>) -> bool {
+    let Some(assignee) = assignee else {
+        return false;
+    };
+    payload
+        .pointer("/changelog/items")
+        .and_then(Value::as_array)
+        .is_some_and(|items| {
+            items.iter().any(|item| {
+                item.get("field").and_then(Value::as_str) == Some("assignee")
+                    && [item.get("to"), item.get("toString")]
+                        .into_iter()
+                        .flatten()
+                        .any(|value| value.as_str() == Some(assignee))
+            })
+        })
+}
+
+/// Builds the printed issue card.
+///
+/// The title holds the issue key prefixed with one to three `!` depending on the
+/// priority, so urgent issues stand out on the printed slip. The link to the issue
+/// is printed as a QR code.
+fn issue_card(payload: &Value, reason: &str) -> Option<PrintTask> {
+    let text = |pointer: &str| payload.pointer(pointer).and_then(Value::as_str);
+    let key = text("/issue/key")?;
+    let summary = text("/issue/fields/summary").unwrap_or("");
+    let priority = text("/issue/fields/priority/name").unwrap_or("None");
+    let reporter = text("/issue/fields/reporter/displayName").unwrap_or("-");
+
+    let marks = match priority.to_lowercase().as_str() {
+        "highest" | "blocker" => "!!! ",
+        "high" | "critical" => "!! ",
+        "medium" | "major" => "! ",
+        _ => "",
+    };
+    let link = text("/issue/self")
+        .and_then(|url| url.split("/rest/").next())
+        .map(|base| format!("{}/browse/{}", base, key));
+
+    Some(PrintTask {
+        title: Some(format!("{}{}", marks, key)),
+        message: format!(
+            "{}\n\n{}\nPriority: {}\nReporter: {}",
+            summary, reason, priority, reporter
+        ),
+        link,
+        ..Default::default()
+    })
+}
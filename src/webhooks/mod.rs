@@ -0,0 +1,90 @@
+pub mod ci;
+pub mod discord;
+pub mod forge;
+pub mod generic;
+pub mod gitea;
+pub mod gitlab;
+pub mod jira;
+
+use crate::api::PrintResponse;
+use axum::{http::StatusCode, response::Json};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+/// Printer settings passed as query parameters to webhook endpoints.
+///
+/// Webhook payloads are defined by the sending service, so the target printer is
+/// configured in the webhook URL instead, e.g. `/webhook/ci?address=10.0.1.100`.
+#[derive(Deserialize, Default)]
+pub struct WebhookTarget {
+    /// Network address of the target printer.
+    pub address: Option<String>,
+    /// Network port of the target printer.
+    pub port: Option<u16>,
+    /// Character encoding codepage for the printer.
+    pub codepage: Option<String>,
+}
+
+/// Verifies a hex encoded HMAC-SHA256 signature of a request body.
+///
+/// The signature may carry a `sha256=` prefix as sent by GitHub, Jira and Gitea.
+/// The comparison runs in constant time. An empty secret never matches, as anyone can
+/// sign a body with an empty key.
+///
+/// # Arguments
+///
+/// * `secret` - The shared secret
+/// * `body` - The raw request body
+/// * `signature` - The signature taken from the request header
+///
+/// # Returns
+///
+/// * `bool` - Whether the secret is not empty and the signature matches the body
+pub fn verify_hmac_sha256(secret: &str, body: &[u8], signature: &str) -> bool {
+    if secret.is_empty() {
+        return false;
+    }
+    let Ok(expected) = hex::decode(signature.trim().trim_start_matches("sha256=")) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Compares a plain shared token in constant time.
+///
+/// An empty secret never matches, so a receiver without a configured secret rejects
+/// every request.
+///
+/// # Arguments
+///
+/// * `expected` - The configured secret
+/// * `provided` - The token sent with the request
+///
+/// # Returns
+///
+/// * `bool` - Whether both tokens are equal and not empty
+pub fn verify_token(expected: &str, provided: &str) -> bool {
+    !expected.is_empty()
+        && expected.len() == provided.len()
+        && expected
+            .bytes()
+            .zip(provided.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Builds the response for a webhook event that is valid but intentionally not printed.
+pub(crate) fn ignored(reason: &str) -> (StatusCode, Json<PrintResponse>) {
+    (
+        StatusCode::OK,
+        Json(PrintResponse {
+            success: true,
+            message: format!("Ignored: {}", reason),
+        }),
+    )
+}